@@ -0,0 +1,214 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use rand::Rng;
+
+use crate::{dns::Resolver, primitives::YahaLoadBalancingPolicy};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+// `(host, port)` of the requests.
+type HostPort = (String, u16);
+
+/// A backend address of a host with its own client (connection pool).
+///
+/// hyper's pool is keyed by the authority of the request, so the connections of a host are pinned to the address
+/// connected first. Having a client per address lets the requests (and HTTP/2 streams) be spread across the addresses.
+pub struct Endpoint<T> {
+    pub address: IpAddr,
+    pub client: T,
+    outstanding: AtomicUsize,
+}
+
+/// Counts the request as outstanding on the endpoint until it is dropped.
+pub struct OutstandingGuard<T>(Arc<Endpoint<T>>);
+
+impl<T> Drop for OutstandingGuard<T> {
+    fn drop(&mut self) {
+        self.0.outstanding.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// The addresses of the endpoints used by the requests sharing this value. (e.g. the hedged requests)
+#[derive(Clone, Default)]
+pub struct UsedEndpoints(Arc<Mutex<Vec<IpAddr>>>);
+
+impl UsedEndpoints {
+    fn contains(&self, address: IpAddr) -> bool {
+        self.0.lock().unwrap().contains(&address)
+    }
+
+    fn push(&self, address: IpAddr) {
+        self.0.lock().unwrap().push(address);
+    }
+}
+
+struct EndpointGroup<T> {
+    endpoints: Mutex<Arc<Vec<Arc<Endpoint<T>>>>>,
+    resolved_at: Mutex<Option<Instant>>,
+    refreshing: AtomicBool,
+    next: AtomicUsize,
+}
+
+impl<T> EndpointGroup<T> {
+    fn new() -> Self {
+        EndpointGroup {
+            endpoints: Mutex::new(Arc::new(Vec::new())),
+            resolved_at: Mutex::new(None),
+            refreshing: AtomicBool::new(false),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    fn endpoints(&self) -> Arc<Vec<Arc<Endpoint<T>>>> {
+        self.endpoints.lock().unwrap().clone()
+    }
+
+    // Returns true if the caller should resolve the host again. Only one request refreshes the group at a time.
+    fn start_refresh(&self, interval: Duration) -> bool {
+        let expired = match *self.resolved_at.lock().unwrap() {
+            Some(resolved_at) => resolved_at.elapsed() >= interval,
+            None => true,
+        };
+        expired && !self.refreshing.swap(true, Ordering::AcqRel)
+    }
+
+    // Replaces the endpoints with the new addresses. The existing endpoints keep their connections and counters.
+    fn update(&self, addresses: Vec<IpAddr>, new_client: &impl Fn(IpAddr) -> T) {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let mut updated = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            if updated.iter().any(|endpoint: &Arc<Endpoint<T>>| endpoint.address == address) {
+                continue;
+            }
+            let endpoint = match endpoints.iter().find(|endpoint| endpoint.address == address) {
+                Some(endpoint) => endpoint.clone(),
+                None => Arc::new(Endpoint {
+                    address,
+                    client: new_client(address),
+                    outstanding: AtomicUsize::new(0),
+                }),
+            };
+            updated.push(endpoint);
+        }
+        *endpoints = Arc::new(updated);
+        *self.resolved_at.lock().unwrap() = Some(Instant::now());
+    }
+
+    fn finish_refresh(&self) {
+        self.refreshing.store(false, Ordering::Release);
+    }
+
+    fn pick(&self, policy: YahaLoadBalancingPolicy, is_available: impl Fn(IpAddr) -> bool, used: Option<&UsedEndpoints>) -> Option<Arc<Endpoint<T>>> {
+        let mut endpoints = self
+            .endpoints()
+            .iter()
+            .filter(|endpoint| is_available(endpoint.address))
+            .cloned()
+            .collect::<Vec<_>>();
+        if endpoints.is_empty() {
+            return None;
+        }
+        // Prefer the endpoints not used yet, but reuse them if there are no others.
+        if let Some(used) = used {
+            if endpoints.iter().any(|endpoint| !used.contains(endpoint.address)) {
+                endpoints.retain(|endpoint| !used.contains(endpoint.address));
+            }
+        }
+        let len = endpoints.len();
+        let index = match policy {
+            YahaLoadBalancingPolicy::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed) % len,
+            YahaLoadBalancingPolicy::LeastOutstandingRequests => {
+                // Start from the round-robin position so that the ties are spread across the endpoints.
+                let start = self.next.fetch_add(1, Ordering::Relaxed);
+                (0..len)
+                    .map(|i| (start + i) % len)
+                    .min_by_key(|&i| endpoints[i].outstanding.load(Ordering::Relaxed))
+                    .unwrap_or(0)
+            }
+            YahaLoadBalancingPolicy::PowerOfTwoChoices => {
+                let mut rng = rand::thread_rng();
+                let a = rng.gen_range(0..len);
+                let b = if len > 1 { (a + rng.gen_range(1..len)) % len } else { a };
+                if endpoints[b].outstanding.load(Ordering::Relaxed) < endpoints[a].outstanding.load(Ordering::Relaxed) {
+                    b
+                } else {
+                    a
+                }
+            }
+        };
+        Some(endpoints[index].clone())
+    }
+}
+
+/// Distributes the requests to a host across its resolved addresses.
+pub struct LoadBalancer<T> {
+    policy: YahaLoadBalancingPolicy,
+    re_resolve_interval: Duration,
+    groups: Mutex<HashMap<HostPort, Arc<EndpointGroup<T>>>>,
+}
+
+impl<T> LoadBalancer<T> {
+    pub fn new(policy: YahaLoadBalancingPolicy, re_resolve_interval: Duration) -> Self {
+        LoadBalancer {
+            policy,
+            re_resolve_interval,
+            groups: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Selects the endpoint for `host:port` from the available endpoints. The host is resolved on the first request and again after the re-resolve interval.
+    // The endpoints in `used` are avoided if possible, and the selected one is added to it.
+    // Returns `None` if no endpoints are available. The returned guard must be kept until the request completes.
+    pub async fn select(
+        &self,
+        host: &str,
+        port: u16,
+        resolver: &Resolver,
+        new_client: impl Fn(IpAddr) -> T,
+        is_available: impl Fn(IpAddr) -> bool,
+        used: Option<&UsedEndpoints>,
+    ) -> Result<Option<(Arc<Endpoint<T>>, OutstandingGuard<T>)>, BoxError> {
+        let group = self
+            .groups
+            .lock()
+            .unwrap()
+            .entry((host.to_ascii_lowercase(), port))
+            .or_insert_with(|| Arc::new(EndpointGroup::new()))
+            .clone();
+
+        // The requests during the refresh use the current endpoints. If there are no endpoints yet, every request resolves the host.
+        let is_empty = group.endpoints().is_empty();
+        let refreshing = group.start_refresh(self.re_resolve_interval);
+        if refreshing || is_empty {
+            let result = resolver.resolve(host).await;
+            if let Ok(addresses) = &result {
+                group.update(addresses.clone(), &new_client);
+            }
+            if refreshing {
+                group.finish_refresh();
+            }
+            // Keep the current endpoints if the resolution fails.
+            if let (Err(err), true) = (result, is_empty) {
+                return Err(err);
+            }
+        }
+
+        let endpoint = match group.pick(self.policy, is_available, used) {
+            Some(endpoint) => endpoint,
+            None => return Ok(None),
+        };
+        if let Some(used) = used {
+            used.push(endpoint.address);
+        }
+        endpoint.outstanding.fetch_add(1, Ordering::Relaxed);
+        Ok(Some((endpoint.clone(), OutstandingGuard(endpoint))))
+    }
+}
@@ -1,22 +1,32 @@
 use std::{
-    error::Error, num::NonZeroIsize, ptr::null, sync::{Arc, Mutex}, time::Duration
+    error::Error, net::{IpAddr, SocketAddr}, num::NonZeroIsize, ptr::null, sync::{Arc, Mutex}, time::Duration
 };
 
-use http_body_util::{combinators::BoxBody, BodyExt};
+use http_body_util::{combinators::BoxBody, BodyExt, Empty};
 use hyper::{
     body::{Body, Bytes, Frame},
-    http::{HeaderName, HeaderValue},
+    http::{uri::Scheme, HeaderName, HeaderValue},
     Request, StatusCode, Uri, Version,
 };
-use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::pki_types::{CertificateDer, CertificateRevocationListDer, PrivateKeyDer};
 use tokio::{select, sync::oneshot};
 use tokio_util::sync::CancellationToken;
 
+use crate::dns::{parse_addresses, ResolveSender};
 use crate::interop::{ByteBuffer, StringBuffer};
-use crate::primitives::{CompletionReason, YahaHttpVersion};
+use crate::cookie_jar::CookieFile;
+use crate::cache;
+use crate::compress;
+use crate::primitives::{CompletionReason, YahaAddressFamilyPreference, YahaCookieFileFormat, YahaHttpVersion, YahaLoadBalancingPolicy, YahaRequestBodyCompression};
+#[cfg(feature = "rustls")]
+use crate::primitives::{YahaRevocationCheckDepth, YahaUnknownRevocationStatusPolicy};
+use crate::proxy::{Proxy, ProxyAuthentication, ProxyCredentials, ProxySelectionInternal};
+use crate::redirect::RedirectedUri;
+#[cfg(feature = "rustls")]
+use crate::verification::VerificationSender;
 use crate::{
     context::{
-        YahaNativeContext, YahaNativeContextInternal, YahaNativeRequestContext,
+        RequestError, YahaNativeContext, YahaNativeContextInternal, YahaNativeRequestContext, YahaProxySelection,
         YahaNativeRequestContextInternal, YahaNativeRuntimeContext,
         YahaNativeRuntimeContextInternal,
     },
@@ -105,13 +115,88 @@ pub extern "C" fn yaha_client_config_add_root_certificates(
         rustls_pemfile::certs(&mut (*root_certs).to_bytes())
             .filter_map(Result::ok)
             .map(|cert| root_certificates.add(cert))
-            .filter_map(|result| result.is_ok().then(|| 1))
+            .filter_map(|result| result.is_ok().then_some(1))
             .sum()
     };
 
     valid
 }
 
+#[cfg(feature = "rustls")]
+#[no_mangle]
+pub extern "C" fn yaha_client_config_use_system_root_certificates(
+    ctx: *mut YahaNativeContext,
+    val: bool,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.use_system_root_certificates = Some(val);
+}
+
+#[cfg(feature = "rustls")]
+#[no_mangle]
+pub extern "C" fn yaha_client_config_use_built_in_root_certificates(
+    ctx: *mut YahaNativeContext,
+    val: bool,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.use_built_in_root_certificates = Some(val);
+}
+
+#[cfg(feature = "rustls")]
+#[no_mangle]
+pub extern "C" fn yaha_client_config_add_certificate_revocation_lists(
+    ctx: *mut YahaNativeContext,
+    crls: *const StringBuffer,
+) -> usize {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    let data = unsafe { (*crls).to_bytes() };
+    // The data is PEM which may contain multiple CRLs, or a DER-encoded CRL.
+    let mut crls = rustls_pemfile::crls(&mut &data[..])
+        .filter_map(Result::ok)
+        .collect::<Vec<_>>();
+    if crls.is_empty() {
+        crls.push(CertificateRevocationListDer::from(data.to_vec()));
+    }
+    // The invalid CRLs are dropped here, as the verifier fails to build with them.
+    crls.retain(|crl| webpki::BorrowedCertRevocationList::from_der(crl.as_ref()).is_ok());
+    let valid = crls.len();
+    ctx.certificate_revocation_lists.extend(crls);
+
+    valid
+}
+
+#[cfg(feature = "rustls")]
+#[no_mangle]
+pub extern "C" fn yaha_client_config_revocation_check_depth(
+    ctx: *mut YahaNativeContext,
+    value: i32,
+) -> bool {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    match YahaRevocationCheckDepth::try_from(value) {
+        Ok(value) => {
+            ctx.revocation_check_depth = Some(value);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(feature = "rustls")]
+#[no_mangle]
+pub extern "C" fn yaha_client_config_unknown_revocation_status_policy(
+    ctx: *mut YahaNativeContext,
+    value: i32,
+) -> bool {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    match YahaUnknownRevocationStatusPolicy::try_from(value) {
+        Ok(value) => {
+            ctx.unknown_revocation_status_policy = Some(value);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn yaha_client_config_add_override_server_name(
     ctx: *mut YahaNativeContext,
@@ -122,6 +207,228 @@ pub extern "C" fn yaha_client_config_add_override_server_name(
     ctx.override_server_name.get_or_insert(server_name.to_string());
 }
 
+#[no_mangle]
+pub extern "C" fn yaha_client_config_proxy(
+    ctx: *mut YahaNativeContext,
+    proxy_uri: *const StringBuffer,
+) -> bool {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    let proxy_uri = unsafe { (*proxy_uri).to_str() };
+    match Proxy::parse(proxy_uri) {
+        Ok(proxy) => {
+            ctx.proxy = Some(proxy);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_use_environment_proxy(
+    ctx: *mut YahaNativeContext,
+    val: bool,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.use_environment_proxy = Some(val);
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_add_resolve_override(
+    ctx: *mut YahaNativeContext,
+    host_port: *const StringBuffer,
+    address: *const StringBuffer,
+) -> bool {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    let (host_port, address) = unsafe { ((*host_port).to_str(), (*address).to_str()) };
+    ctx.connect_overrides.add_address(host_port, address).is_ok()
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_add_connect_to(
+    ctx: *mut YahaNativeContext,
+    from: *const StringBuffer,
+    to: *const StringBuffer,
+) -> bool {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    let (from, to) = unsafe { ((*from).to_str(), (*to).to_str()) };
+    ctx.connect_overrides.add_connect_to(from, to).is_ok()
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_set_resolve_handler(
+    ctx: *mut YahaNativeContext,
+    handler: Option<extern "C" fn(state: NonZeroIsize, host: *const u8, host_len: usize, task_handle: usize)>,
+    callback_state: NonZeroIsize
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.resolve_handler = handler.map(|x| (x, callback_state));
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_use_built_in_dns_resolver(
+    ctx: *mut YahaNativeContext,
+    val: bool,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.use_built_in_dns_resolver = Some(val);
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_add_dns_server(
+    ctx: *mut YahaNativeContext,
+    address: *const StringBuffer,
+) -> bool {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    let address = unsafe { (*address).to_str() };
+    // The port can be omitted. (e.g. `192.0.2.53`)
+    let address = match address.parse::<SocketAddr>() {
+        Ok(address) => address,
+        Err(_) => match address.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+            Ok(ip) => SocketAddr::new(ip, 53),
+            Err(_) => return false,
+        },
+    };
+    ctx.dns_servers.push(address);
+    true
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_srv_discovery(
+    ctx: *mut YahaNativeContext,
+    val: bool,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.srv_discovery = Some(val);
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_dns_over_https_endpoint(
+    ctx: *mut YahaNativeContext,
+    endpoint: *const StringBuffer,
+) -> bool {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    let endpoint = unsafe { (*endpoint).to_str() };
+    match endpoint.parse::<Uri>() {
+        // DNS-over-HTTPS is not available with the native TLS backend.
+        Ok(uri) if cfg!(feature = "rustls") && uri.scheme() == Some(&Scheme::HTTPS) && uri.host().is_some() => {
+            ctx.dns_over_https_endpoint = Some(uri);
+            true
+        }
+        _ => false,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_add_dns_over_https_bootstrap_address(
+    ctx: *mut YahaNativeContext,
+    address: *const StringBuffer,
+) -> bool {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    let address = unsafe { (*address).to_str() };
+    match address.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(address) => {
+            ctx.dns_over_https_bootstrap_addresses.push(address);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_dns_cache(
+    ctx: *mut YahaNativeContext,
+    val: bool,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.dns_cache = Some(val);
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_dns_cache_default_ttl(
+    ctx: *mut YahaNativeContext,
+    val_milliseconds: u64,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.dns_cache_options.default_ttl = Duration::from_millis(val_milliseconds);
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_dns_cache_max_ttl(
+    ctx: *mut YahaNativeContext,
+    val_milliseconds: u64,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.dns_cache_options.max_ttl = Duration::from_millis(val_milliseconds);
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_dns_cache_negative_ttl(
+    ctx: *mut YahaNativeContext,
+    val_milliseconds: u64,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.dns_cache_options.negative_ttl = Duration::from_millis(val_milliseconds);
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_dns_cache_stale_while_revalidate(
+    ctx: *mut YahaNativeContext,
+    val_milliseconds: u64,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.dns_cache_options.stale_while_revalidate = Duration::from_millis(val_milliseconds);
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_set_proxy_selection_handler(
+    ctx: *mut YahaNativeContext,
+    handler: Option<extern "C" fn(state: NonZeroIsize, uri: *const u8, uri_len: usize, selection: *mut YahaProxySelection) -> bool>,
+    callback_state: NonZeroIsize
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.proxy_selection_handler = handler.map(|x| (x, callback_state));
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_proxy_selection_set_proxy(
+    selection: *mut YahaProxySelection,
+    proxy_uri: *const StringBuffer,
+) -> bool {
+    let selection = ProxySelectionInternal::from_raw(selection);
+    let proxy_uri = unsafe { (*proxy_uri).to_str() };
+    match Proxy::parse(proxy_uri) {
+        Ok(proxy) => {
+            selection.proxy = Some(proxy);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_add_proxy_basic_credentials(
+    ctx: *mut YahaNativeContext,
+    username: *const StringBuffer,
+    password: *const StringBuffer,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    let (username, password) = unsafe { ((*username).to_str(), (*password).to_str()) };
+    ctx.proxy_authentications.push(ProxyAuthentication::Basic(ProxyCredentials {
+        username: username.to_string(),
+        password: password.to_string(),
+    }));
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_add_proxy_bearer_token(
+    ctx: *mut YahaNativeContext,
+    token: *const StringBuffer,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    let token = unsafe { (*token).to_str() };
+    ctx.proxy_authentications.push(ProxyAuthentication::Bearer(token.to_string()));
+}
+
 #[no_mangle]
 pub extern "C" fn yaha_client_config_add_client_auth_certificates(
     ctx: *mut YahaNativeContext,
@@ -131,7 +438,6 @@ pub extern "C" fn yaha_client_config_add_client_auth_certificates(
     let certs: Vec<CertificateDer> = unsafe {
         rustls_pemfile::certs(&mut (*auth_certs).to_bytes())
             .filter_map(Result::ok)
-            .map(CertificateDer::from)
             .collect()
     };
 
@@ -178,13 +484,49 @@ pub extern "C" fn yaha_client_config_skip_certificate_verification(
 #[no_mangle]
 pub extern "C" fn yaha_client_config_set_server_certificate_verification_handler(
     ctx: *mut YahaNativeContext,
-    handler: Option<extern "C" fn(state: NonZeroIsize, server_name: *const u8, server_name_len: usize, certificate_der: *const u8, certificate_der_len: usize, now: u64) -> bool>,
+    handler: Option<extern "C" fn(state: NonZeroIsize, server_name: *const u8, server_name_len: usize, certificates: *const StringBuffer, certificates_len: usize, ocsp_response: *const u8, ocsp_response_len: usize, default_verification_error: *const u8, default_verification_error_len: usize, now: u64) -> bool>,
     callback_state: NonZeroIsize
 ) {
     let ctx = YahaNativeContextInternal::from_raw_context(ctx);
     ctx.server_certificate_verification_handler = handler.map(|x| (x, callback_state));
 }
 
+#[cfg(feature = "rustls")]
+#[no_mangle]
+pub extern "C" fn yaha_client_config_set_server_certificate_verification_async_handler(
+    ctx: *mut YahaNativeContext,
+    handler: Option<extern "C" fn(state: NonZeroIsize, server_name: *const u8, server_name_len: usize, certificates: *const StringBuffer, certificates_len: usize, ocsp_response: *const u8, ocsp_response_len: usize, default_verification_error: *const u8, default_verification_error_len: usize, now: u64, task_handle: usize)>,
+    callback_state: NonZeroIsize
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.server_certificate_verification_async_handler = handler.map(|x| (x, callback_state));
+}
+
+#[cfg(feature = "rustls")]
+#[no_mangle]
+pub extern "C" fn yaha_client_config_server_certificate_verification_timeout(
+    ctx: *mut YahaNativeContext,
+    val_milliseconds: u64,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.server_certificate_verification_timeout = Some(Duration::from_millis(val_milliseconds));
+}
+
+#[cfg(feature = "rustls")]
+#[no_mangle]
+pub extern "C" fn yaha_client_config_add_public_key_pins(
+    ctx: *mut YahaNativeContext,
+    host_pattern: *const StringBuffer,
+    pins: *const StringBuffer,
+    expires_at_unix_seconds: u64,
+) -> bool {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    let (host_pattern, pins) = unsafe { ((*host_pattern).to_str(), (*pins).to_str()) };
+    // 0 means the pins never expire.
+    let expires_at = (expires_at_unix_seconds != 0).then(|| std::time::UNIX_EPOCH + Duration::from_secs(expires_at_unix_seconds));
+    ctx.public_key_pins.add(host_pattern, pins, expires_at).is_ok()
+}
+
 #[no_mangle]
 pub extern "C" fn yaha_client_config_pool_idle_timeout(
     ctx: *mut YahaNativeContext,
@@ -302,6 +644,302 @@ pub extern "C" fn yaha_client_config_connect_timeout(
     ctx.connect_timeout.get_or_insert(Duration::from_millis(timeout_milliseconds));
 }
 
+// The value is received as `i32` because an out-of-range value is undefined behavior for a Rust enum.
+#[no_mangle]
+pub extern "C" fn yaha_client_config_address_family_preference(
+    ctx: *mut YahaNativeContext,
+    value: i32,
+) -> bool {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    match YahaAddressFamilyPreference::try_from(value) {
+        Ok(value) => {
+            ctx.address_family_preference = Some(value);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_happy_eyeballs_fallback_delay(
+    ctx: *mut YahaNativeContext,
+    delay_milliseconds: u64,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.happy_eyeballs_fallback_delay = Some(Duration::from_millis(delay_milliseconds));
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_load_balancing_policy(
+    ctx: *mut YahaNativeContext,
+    value: i32,
+) -> bool {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    match YahaLoadBalancingPolicy::try_from(value) {
+        Ok(value) => {
+            ctx.load_balancing_policy = Some(value);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_load_balancing_re_resolve_interval(
+    ctx: *mut YahaNativeContext,
+    interval_milliseconds: u64,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.load_balancing_re_resolve_interval = Some(Duration::from_millis(interval_milliseconds));
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_circuit_breaker(
+    ctx: *mut YahaNativeContext,
+    value: bool,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.circuit_breaker_enabled = Some(value);
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_circuit_breaker_consecutive_failures(
+    ctx: *mut YahaNativeContext,
+    value: u32,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.circuit_breaker_options.consecutive_failures = value;
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_circuit_breaker_failure_rate(
+    ctx: *mut YahaNativeContext,
+    value: f64,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.circuit_breaker_options.failure_rate = value;
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_circuit_breaker_sampling_requests(
+    ctx: *mut YahaNativeContext,
+    value: usize,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.circuit_breaker_options.sampling_requests = value;
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_circuit_breaker_cooldown(
+    ctx: *mut YahaNativeContext,
+    cooldown_milliseconds: u64,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.circuit_breaker_options.cooldown = Duration::from_millis(cooldown_milliseconds);
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_retry(
+    ctx: *mut YahaNativeContext,
+    value: bool,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.retry_enabled = Some(value);
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_retry_max_attempts(
+    ctx: *mut YahaNativeContext,
+    value: u32,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.retry_options.max_attempts = value;
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_retry_initial_backoff(
+    ctx: *mut YahaNativeContext,
+    backoff_milliseconds: u64,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.retry_options.initial_backoff = Duration::from_millis(backoff_milliseconds);
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_retry_max_backoff(
+    ctx: *mut YahaNativeContext,
+    backoff_milliseconds: u64,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.retry_options.max_backoff = Duration::from_millis(backoff_milliseconds);
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_retry_non_idempotent_requests(
+    ctx: *mut YahaNativeContext,
+    value: bool,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.retry_options.retry_non_idempotent = value;
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_retry_budget_ratio(
+    ctx: *mut YahaNativeContext,
+    value: f64,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.retry_options.budget_ratio = value;
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_hedging_delay(
+    ctx: *mut YahaNativeContext,
+    delay_milliseconds: u64,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.hedging_delay = Some(Duration::from_millis(delay_milliseconds));
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_hedging_max_attempts(
+    ctx: *mut YahaNativeContext,
+    value: u32,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.hedging_max_attempts = Some(value);
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_redirect(
+    ctx: *mut YahaNativeContext,
+    value: bool,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.redirect_enabled = Some(value);
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_max_redirects(
+    ctx: *mut YahaNativeContext,
+    value: u32,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.redirect_options.max_redirects = value;
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_allow_https_to_http_redirect(
+    ctx: *mut YahaNativeContext,
+    value: bool,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.redirect_options.allow_https_to_http = value;
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_cookie_jar(
+    ctx: *mut YahaNativeContext,
+    val: bool,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.cookie_jar_enabled = Some(val);
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_cookie_file(
+    ctx: *mut YahaNativeContext,
+    path: *const StringBuffer,
+    format: i32,
+) -> bool {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    let path = unsafe { (*path).to_str() };
+    let format = match YahaCookieFileFormat::try_from(format) {
+        Ok(format) => format,
+        Err(_) => return false,
+    };
+    // The cookies are loaded here to report the invalid file on the configuration.
+    let file = CookieFile::new(path.into(), format);
+    match file.load() {
+        Ok(store) => {
+            ctx.cookie_store = Some(store);
+            ctx.cookie_file = Some(file);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_cookie_public_suffix_list(
+    ctx: *mut YahaNativeContext,
+    path: *const StringBuffer,
+) -> bool {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    let path = unsafe { (*path).to_str() };
+    match std::fs::read(path).ok().and_then(|list| publicsuffix::List::from_bytes(&list).ok()) {
+        Some(list) => {
+            ctx.cookie_public_suffix_list = Some(list);
+            true
+        }
+        None => false,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_decompression(
+    ctx: *mut YahaNativeContext,
+    val: bool,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.decompression = Some(val);
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_http_cache(
+    ctx: *mut YahaNativeContext,
+    val: bool,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.cache_enabled = Some(val);
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_http_cache_directory(
+    ctx: *mut YahaNativeContext,
+    path: *const StringBuffer,
+) -> bool {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    let path = unsafe { (*path).to_str() };
+    // The responses are stored in the dedicated subdirectory not to touch the other files in the directory.
+    let directory = std::path::Path::new(path).join(cache::DIRECTORY_NAME);
+    match std::fs::create_dir_all(&directory) {
+        Ok(_) => {
+            ctx.cache_options.directory = Some(directory);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_http_cache_max_size(
+    ctx: *mut YahaNativeContext,
+    value: u64,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.cache_options.max_size = value;
+}
+
+#[no_mangle]
+pub extern "C" fn yaha_client_config_http_cache_max_entry_size(
+    ctx: *mut YahaNativeContext,
+    value: u64,
+) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.cache_options.max_entry_size = value;
+}
+
 #[no_mangle]
 pub extern "C" fn yaha_client_config_http2_max_concurrent_reset_streams(
     ctx: *mut YahaNativeContext,
@@ -311,7 +949,7 @@ pub extern "C" fn yaha_client_config_http2_max_concurrent_reset_streams(
     ctx.client_builder
         .as_mut()
         .unwrap()
-        .http2_max_concurrent_reset_streams(max.try_into().unwrap());
+        .http2_max_concurrent_reset_streams(max);
 }
 
 #[no_mangle]
@@ -350,6 +988,12 @@ pub extern "C" fn yaha_client_config_unix_domain_socket_path(
     ctx.uds_socket_path.get_or_insert(uds_socket_path.into());
 }
 
+#[no_mangle]
+pub extern "C" fn yaha_client_flush_dns_cache(ctx: *mut YahaNativeContext) {
+    let ctx = YahaNativeContextInternal::from_raw_context(ctx);
+    ctx.flush_dns_cache();
+}
+
 #[no_mangle]
 pub extern "C" fn yaha_build_client(ctx: *mut YahaNativeContext) {
     let ctx = YahaNativeContextInternal::from_raw_context(ctx);
@@ -364,10 +1008,12 @@ pub unsafe extern "C" fn yaha_request_new(
     let builder = Request::builder();
 
     let req_ctx = Arc::new(Mutex::new(YahaNativeRequestContextInternal {
-        seq: seq,
+        seq,
         builder: Some(builder),
         sender: None,
         has_body: false,
+        body_compression: None,
+        body_compression_level: None,
         completed: false,
         cancellation_token: CancellationToken::new(),
         last_error: None,
@@ -375,6 +1021,7 @@ pub unsafe extern "C" fn yaha_request_new(
         response_version: YahaHttpVersion::Http10,
         response_trailers: None,
         response_headers: None,
+        response_uri: None,
         response_status: StatusCode::OK,
     }));
     Arc::into_raw(req_ctx) as *const YahaNativeRequestContext
@@ -407,6 +1054,40 @@ pub unsafe extern "C" fn yaha_request_set_has_body(
     true
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn yaha_request_set_body_compression(
+    ctx: *const YahaNativeContext,
+    req_ctx: *const YahaNativeRequestContext,
+    value: i32,
+) -> bool {
+    let mut req_ctx = crate::context::to_internal(req_ctx).lock().unwrap();
+    assert!(req_ctx.builder.is_some());
+
+    match YahaRequestBodyCompression::try_from(value) {
+        Ok(value) => {
+            req_ctx.body_compression = Some(value);
+            true
+        }
+        Err(_) => {
+            req_ctx.last_error = Some(format!("Unsupported request body compression: {}", value));
+            false
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn yaha_request_set_body_compression_level(
+    ctx: *const YahaNativeContext,
+    req_ctx: *const YahaNativeRequestContext,
+    value: i32,
+) -> bool {
+    let mut req_ctx = crate::context::to_internal(req_ctx).lock().unwrap();
+    assert!(req_ctx.builder.is_some());
+
+    req_ctx.body_compression_level = Some(value);
+    true
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn yaha_request_set_uri(
     ctx: *const YahaNativeContext,
@@ -485,13 +1166,22 @@ pub extern "C" fn yaha_request_begin(
     {
         let mut req_ctx = req_ctx.lock().unwrap();
 
-        let (tx, rx) = futures_channel::mpsc::channel::<Bytes>(0);
-        body = BoxBody::new(http_body_util::StreamBody::new(rx.map(|data| Result::Ok(Frame::data(data)))));
-        
         if req_ctx.has_body {
+            let (tx, rx) = futures_channel::mpsc::channel::<Bytes>(0);
+            match req_ctx.body_compression {
+                Some(compression) => {
+                    body = compress::compress(rx.map(Ok), compression, req_ctx.body_compression_level);
+                    if let Some(headers) = req_ctx.builder.as_mut().and_then(|builder| builder.headers_mut()) {
+                        compress::set_content_encoding(headers, compression);
+                    }
+                }
+                None => {
+                    body = BoxBody::new(http_body_util::StreamBody::new(rx.map(|data| Result::Ok(Frame::data(data)))));
+                }
+            }
             req_ctx.sender = Some(tx);
         } else {
-            drop(tx); // close
+            body = BoxBody::new(Empty::<Bytes>::new().map_err(|never| match never {}));
         }
     }
     {
@@ -526,23 +1216,28 @@ pub extern "C" fn yaha_request_begin(
             }
 
             // Send a request and wait for response status and headers.
-            let res = select! {
+            let mut res = select! {
                 _ = cancellation_token.cancelled() => {
                     (ctx.on_complete)(seq, state, CompletionReason::Aborted, 0);
                     return;
                 }
                 res = ctx.request(req) => {
-                    if let Err(err) = res {
-                        complete_with_error(ctx, req_ctx, seq, state, err);
-                        return;
-                    } else {
-                        res
+                    match res {
+                        Err(RequestError::Client(err)) => {
+                            complete_with_error(ctx, req_ctx, seq, state, err);
+                            return;
+                        }
+                        Err(RequestError::CircuitBreakerOpen(message)) => {
+                            req_ctx.lock().unwrap().last_error = Some(message);
+                            (ctx.on_complete)(seq, state, CompletionReason::CircuitBreakerOpen, 0);
+                            return;
+                        }
+                        Ok(res) => res,
                     }
                 }
             };
 
             // Status code and response headers are received.
-            let mut res = res.unwrap();
             {
                 let mut req_ctx = req_ctx.lock().unwrap();
                 req_ctx.response_headers = Some(
@@ -557,6 +1252,7 @@ pub extern "C" fn yaha_request_begin(
                         .collect::<Vec<(String, String)>>(),
                 );
                 req_ctx.response_status = res.status();
+                req_ctx.response_uri = res.extensions().get::<RedirectedUri>().map(|uri| uri.0.to_string());
                 req_ctx.response_version = YahaHttpVersion::from(res.version());
             }
             (ctx.on_status_code_and_headers_receive)(
@@ -646,7 +1342,7 @@ pub extern "C" fn yaha_request_begin(
                                             .and_then(|e| e.downcast_ref::<h2::Error>())
                                             .and_then(|e| e.reason());
 
-                                        let rc = reason.map(|r| u32::from(r));
+                                        let rc = reason.map(u32::from);
 
                                         (ctx.on_complete)(seq, state, CompletionReason::Error, rc.unwrap_or_default());
                                         return;
@@ -687,13 +1383,13 @@ fn complete_with_error(ctx: &mut YahaNativeContextInternal, req_ctx: Arc<Mutex<Y
         let mut req_ctx = req_ctx.lock().unwrap();
         // If the error has the inner error, use its error message instead.
         if let Some(error_inner) = err.source() {
-            req_ctx.last_error = Some(format!("{}: {}", err.to_string(), error_inner.to_string()));
+            req_ctx.last_error = Some(format!("{}: {}", err, error_inner));
 
             // If the Error has `h2::Error` as inner error, the error has HTTP/2 error code.
             h2_error_code = error_inner.source()
                 .and_then(|e| e.downcast_ref::<h2::Error>())
                 .and_then(|e| e.reason())
-                .map(|e| u32::from(e));
+                .map(u32::from);
         } else {
             req_ctx.last_error = Some(err.to_string());
         }
@@ -790,6 +1486,23 @@ pub unsafe extern "C" fn yaha_request_response_get_header_value(
     Box::into_raw(Box::new(buf))
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn yaha_request_response_get_uri(
+    ctx: *const YahaNativeContext,
+    req_ctx: *const YahaNativeRequestContext,
+) -> *const ByteBuffer {
+    let req_ctx = crate::context::to_internal(req_ctx).lock().unwrap();
+    debug_assert!(!req_ctx.completed);
+
+    match req_ctx.response_uri.as_ref() {
+        Some(uri) => {
+            let buf = ByteBuffer::from_vec(uri.clone().into_bytes());
+            Box::into_raw(Box::new(buf))
+        }
+        None => null(),
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn yaha_request_response_get_trailers_count(
     ctx: *const YahaNativeContext,
@@ -843,6 +1556,33 @@ pub extern "C" fn yaha_request_destroy(
     true
 }
 
+#[no_mangle]
+pub extern "C" fn yaha_complete_resolve(task_handle: usize, addresses: *const StringBuffer, error: *const StringBuffer) {
+    let tx = unsafe { Box::from_raw(task_handle as *mut ResolveSender) };
+    let result = if error.is_null() {
+        let addresses = unsafe { (*addresses).to_str() };
+        parse_addresses(addresses)
+    } else {
+        let error = unsafe { (*error).to_str().to_string() };
+        Err(error)
+    };
+    // The receiver may have been dropped if the connection attempt was canceled.
+    let _ = tx.send(result);
+}
+
+#[cfg(feature = "rustls")]
+#[no_mangle]
+pub extern "C" fn yaha_complete_server_certificate_verification(task_handle: usize, error: *const StringBuffer) {
+    let tx = unsafe { Box::from_raw(task_handle as *mut VerificationSender) };
+    let result = if error.is_null() {
+        Ok(())
+    } else {
+        Err(unsafe { (*error).to_str().to_string() })
+    };
+    // The receiver may have been dropped if the verification timed out or the connection attempt was canceled.
+    let _ = tx.send(result);
+}
+
 #[no_mangle]
 pub extern "C" fn yaha_complete_task(task_handle: usize, error: *const StringBuffer) {
     let tx = unsafe { Box::from_raw(task_handle as *mut oneshot::Sender<Result<(), String>>) };
@@ -0,0 +1,842 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File},
+    future::Future,
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use http_body_util::{combinators::BoxBody, Empty, Full};
+use hyper::{
+    body::{Body, Bytes, Frame, Incoming, SizeHint},
+    header::{self, HeaderMap, HeaderName, HeaderValue},
+    Method, Request, Response, StatusCode, Uri, Version,
+};
+
+use crate::context::{to_response_body, RequestBody, RequestError, ResponseBody};
+
+#[derive(Clone, Debug)]
+pub struct CacheOptions {
+    // The maximum total size of the cached responses. The least recently used ones are evicted.
+    pub max_size: u64,
+    // The maximum size of a cached response. The larger responses are not cached.
+    pub max_entry_size: u64,
+    // The directory dedicated to the cached responses (see `DIRECTORY_NAME`). They are kept in memory if this is None.
+    pub directory: Option<PathBuf>,
+}
+
+impl Default for CacheOptions {
+    fn default() -> Self {
+        CacheOptions {
+            max_size: 64 * 1024 * 1024,
+            max_entry_size: 8 * 1024 * 1024,
+            directory: None,
+        }
+    }
+}
+
+// RFC 9211
+const CACHE_STATUS: HeaderName = HeaderName::from_static("cache-status");
+const CACHE_NAME: &str = "yaha";
+
+// The subdirectory created in the directory given by the application. Only the files named by the cache are read or removed in it.
+pub const DIRECTORY_NAME: &str = "yaha-http-cache";
+const FILE_MAGIC: &str = "YAHA-CACHE/1";
+const FILE_PREFIX: &str = "yaha-";
+const FILE_SUFFIX: &str = ".cache";
+const TMP_FILE_SUFFIX: &str = ".cache.tmp";
+
+/// The private HTTP cache as specified in RFC 9111.
+///
+/// Only the responses of `GET` requests are stored. The stale responses are revalidated with `ETag` and `Last-Modified`.
+/// `Cache-Status` header (RFC 9211) is added to the responses to tell whether the response came from the cache.
+pub struct HttpCache {
+    inner: Arc<CacheInner>,
+}
+
+struct CacheInner {
+    options: CacheOptions,
+    index: Mutex<Index>,
+}
+
+impl HttpCache {
+    pub fn new(options: CacheOptions) -> Self {
+        let mut index = Index::default();
+        if let Some(directory) = &options.directory {
+            for entry in load_directory(directory) {
+                index.insert(entry);
+            }
+            remove_files(index.evict(options.max_size));
+        }
+        HttpCache {
+            inner: Arc::new(CacheInner {
+                options,
+                index: Mutex::new(index),
+            }),
+        }
+    }
+
+    // Returns the stored response if it can be used, otherwise sends the request with `send` (conditionally if stale) and stores the response.
+    pub async fn execute<F, Fut>(&self, req: Request<RequestBody>, send: F) -> Result<Response<ResponseBody>, RequestError>
+    where
+        F: Fn(Request<RequestBody>) -> Fut,
+        Fut: Future<Output = Result<Response<Incoming>, RequestError>>,
+    {
+        let key = req.uri().to_string();
+
+        if req.method() != Method::GET {
+            let unsafe_method = !matches!(*req.method(), Method::HEAD | Method::OPTIONS | Method::TRACE);
+            let uri = req.uri().clone();
+            let res = send(req).await?;
+            // RFC 9111 4.4: The unsafe requests invalidate the stored responses.
+            if unsafe_method && (res.status().is_success() || res.status().is_redirection()) {
+                self.inner.invalidate(&uri, &res);
+            }
+            return Ok(with_cache_status(res.map(to_response_body), "fwd=method"));
+        }
+
+        let request_cc = CacheControl::from_request(req.headers());
+        // The conditional and range requests of the application are sent as is.
+        let bypass = request_cc.no_store
+            || [header::IF_NONE_MATCH, header::IF_MODIFIED_SINCE, header::IF_MATCH, header::IF_UNMODIFIED_SINCE, header::IF_RANGE, header::RANGE]
+                .iter()
+                .any(|name| req.headers().contains_key(name));
+        if bypass {
+            let res = send(req).await?;
+            return Ok(with_cache_status(res.map(to_response_body), "fwd=bypass"));
+        }
+
+        let (lookup, key_exists) = self.inner.lookup(&key, req.headers());
+        let now = SystemTime::now();
+        if let Some((id, stored, body)) = &lookup {
+            if !request_cc.no_cache && stored.can_serve(&request_cc, now) {
+                match self.inner.read_body(body).await {
+                    Some(body) => {
+                        let ttl = stored.freshness_lifetime().as_secs() as i64 - stored.current_age(now).as_secs() as i64;
+                        return Ok(stored.to_response(body, now, &format!("hit; ttl={ttl}")));
+                    }
+                    None => self.inner.remove(*id),
+                }
+            }
+        }
+        if request_cc.only_if_cached {
+            let mut res = Response::new(to_response_body(Empty::<Bytes>::new()));
+            *res.status_mut() = StatusCode::GATEWAY_TIMEOUT;
+            return Ok(with_cache_status(res, "detail=only-if-cached"));
+        }
+        let fwd = match &lookup {
+            _ if request_cc.no_cache => "fwd=request",
+            Some(_) => "fwd=stale",
+            None if key_exists => "fwd=vary-miss",
+            None => "fwd=uri-miss",
+        };
+
+        // Validate the stored response with the validators.
+        let (parts, body) = req.into_parts();
+        let mut forward = Request::from_parts(parts.clone(), body);
+        if let Some((_, stored, _)) = &lookup {
+            if let Some(etag) = stored.headers.get(header::ETAG) {
+                forward.headers_mut().insert(header::IF_NONE_MATCH, etag.clone());
+            }
+            if let Some(last_modified) = stored.headers.get(header::LAST_MODIFIED) {
+                forward.headers_mut().insert(header::IF_MODIFIED_SINCE, last_modified.clone());
+            }
+        }
+
+        let request_time = SystemTime::now();
+        let res = send(forward).await?;
+        let response_time = SystemTime::now();
+
+        if res.status() == StatusCode::NOT_MODIFIED {
+            if let Some((id, stored, body)) = lookup {
+                if let Some(body) = self.inner.read_body(&body).await {
+                    // RFC 9111 4.3.4: Update the stored response with the headers of 304 response.
+                    let mut headers = stored.headers.clone();
+                    for name in storable_headers(res.headers()).keys() {
+                        if name != header::CONTENT_LENGTH {
+                            headers.remove(name);
+                        }
+                    }
+                    for (name, value) in storable_headers(res.headers()).iter() {
+                        if name != header::CONTENT_LENGTH {
+                            headers.append(name, value.clone());
+                        }
+                    }
+                    let updated = StoredResponse {
+                        status: stored.status,
+                        version: stored.version,
+                        headers,
+                        vary: stored.vary.clone(),
+                        request_time,
+                        response_time,
+                    };
+                    let res = updated.to_response(body.clone(), SystemTime::now(), &format!("{fwd}; fwd-status=304"));
+                    self.inner.store(key, updated, body);
+                    return Ok(res);
+                }
+                self.inner.remove(id);
+            }
+        }
+
+        let status = format!("{fwd}; fwd-status={}", res.status().as_u16());
+        match StoredResponse::new(&parts.headers, &res, request_time, response_time) {
+            Some(stored) if stored.is_storable() && res.body().size_hint().lower() <= self.inner.options.max_entry_size => {
+                let (res_parts, body) = res.into_parts();
+                let body = CachingBody::new(BoxBody::new(body), self.inner.clone(), key, stored);
+                Ok(with_cache_status(Response::from_parts(res_parts, to_response_body(body)), &format!("{status}; stored")))
+            }
+            _ => Ok(with_cache_status(res.map(to_response_body), &status)),
+        }
+    }
+}
+
+impl CacheInner {
+    // Returns the stored response matching the request, and whether any response is stored for the URI.
+    fn lookup(&self, key: &str, headers: &HeaderMap) -> (Option<(u64, Arc<StoredResponse>, StoredBody)>, bool) {
+        let mut index = self.index.lock().unwrap();
+        let key_exists = index.keys.contains_key(key);
+        (index.lookup(key, headers), key_exists)
+    }
+
+    async fn read_body(&self, body: &StoredBody) -> Option<Bytes> {
+        match body {
+            StoredBody::Memory(body) => Some(body.clone()),
+            StoredBody::Disk { path, offset } => {
+                let (path, offset) = (path.clone(), *offset);
+                let data = tokio::task::spawn_blocking(move || fs::read(path)).await.ok()?.ok()?;
+                (data.len() >= offset).then(|| Bytes::from(data).slice(offset..))
+            }
+        }
+    }
+
+    fn store(self: &Arc<Self>, key: String, response: StoredResponse, body: Bytes) {
+        let size = key.len() as u64 + response.header_size() + body.len() as u64;
+        if size > self.options.max_entry_size || !response.is_storable() {
+            return;
+        }
+        match &self.options.directory {
+            None => {
+                let mut index = self.index.lock().unwrap();
+                index.insert(Entry { key, response: Arc::new(response), body: StoredBody::Memory(body), size });
+                index.evict(self.options.max_size);
+            }
+            Some(directory) => {
+                let inner = self.clone();
+                let path = directory.join(format!("{FILE_PREFIX}{:016x}{:016x}{FILE_SUFFIX}", rand::random::<u64>(), rand::random::<u64>()));
+                tokio::task::spawn_blocking(move || {
+                    // The response is not cached if it can't be written.
+                    let offset = match write_file(&path, &key, &response, &body) {
+                        Ok(offset) => offset,
+                        Err(_) => return,
+                    };
+                    let evicted = {
+                        let mut index = inner.index.lock().unwrap();
+                        let mut evicted = index.insert(Entry { key, response: Arc::new(response), body: StoredBody::Disk { path, offset }, size });
+                        evicted.extend(index.evict(inner.options.max_size));
+                        evicted
+                    };
+                    remove_files(evicted);
+                });
+            }
+        }
+    }
+
+    fn remove(&self, id: u64) {
+        let removed = self.index.lock().unwrap().remove(id);
+        self.remove_files_in_background(removed.into_iter().collect());
+    }
+
+    fn invalidate<B>(&self, uri: &Uri, res: &Response<B>) {
+        let mut uris = vec![uri.to_string()];
+        // The locations of the same origin are also invalidated.
+        for name in [header::LOCATION, header::CONTENT_LOCATION] {
+            let location = res
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| url::Url::parse(&uri.to_string()).ok()?.join(value).ok());
+            if let Some(location) = location {
+                if location.host_str() == uri.host() {
+                    uris.push(location.to_string());
+                }
+            }
+        }
+        let removed = {
+            let mut index = self.index.lock().unwrap();
+            uris.iter().flat_map(|uri| index.remove_key(uri)).collect::<Vec<_>>()
+        };
+        self.remove_files_in_background(removed);
+    }
+
+    fn remove_files_in_background(&self, entries: Vec<Entry>) {
+        if self.options.directory.is_some() && !entries.is_empty() {
+            tokio::task::spawn_blocking(move || remove_files(entries));
+        }
+    }
+}
+
+/// The response stored in the cache except the body.
+struct StoredResponse {
+    status: StatusCode,
+    version: Version,
+    headers: HeaderMap,
+    // The values of the request headers nominated by `Vary` header.
+    vary: Vec<(HeaderName, String)>,
+    request_time: SystemTime,
+    response_time: SystemTime,
+}
+
+impl StoredResponse {
+    // Returns the response to store if it is storable. (RFC 9111 3)
+    fn new<B>(request_headers: &HeaderMap, res: &Response<B>, request_time: SystemTime, response_time: SystemTime) -> Option<Self> {
+        let cc = CacheControl::parse(res.headers());
+        if cc.no_store || res.status() == StatusCode::PARTIAL_CONTENT || res.status() == StatusCode::NOT_MODIFIED || res.status().is_informational() {
+            return None;
+        }
+        let explicit = cc.max_age.is_some() || cc.public || cc.private || res.headers().contains_key(header::EXPIRES);
+        if !explicit && !is_heuristically_cacheable(res.status()) {
+            return None;
+        }
+
+        let mut vary = Vec::new();
+        for name in list_values(res.headers(), header::VARY) {
+            // `Vary: *` never matches.
+            if name == "*" {
+                return None;
+            }
+            let name = HeaderName::from_bytes(name.as_bytes()).ok()?;
+            vary.push((name.clone(), joined_values(request_headers, &name)));
+        }
+
+        Some(StoredResponse {
+            status: res.status(),
+            version: res.version(),
+            headers: storable_headers(res.headers()),
+            vary,
+            request_time,
+            response_time,
+        })
+    }
+
+    fn matches(&self, headers: &HeaderMap) -> bool {
+        self.vary.iter().all(|(name, value)| joined_values(headers, name) == *value)
+    }
+
+    fn header_size(&self) -> u64 {
+        self.headers.iter().map(|(name, value)| (name.as_str().len() + value.len()) as u64).sum::<u64>()
+            + self.vary.iter().map(|(name, value)| (name.as_str().len() + value.len()) as u64).sum::<u64>()
+    }
+
+    // The response which is never fresh and can't be validated can't be served from the cache.
+    fn is_storable(&self) -> bool {
+        self.freshness_lifetime() > Duration::ZERO || self.headers.contains_key(header::ETAG) || self.headers.contains_key(header::LAST_MODIFIED)
+    }
+
+    // RFC 9111 4.2.1
+    fn freshness_lifetime(&self) -> Duration {
+        let cc = CacheControl::parse(&self.headers);
+        if let Some(max_age) = cc.max_age {
+            return Duration::from_secs(max_age);
+        }
+        let date = self.date();
+        if let Some(expires) = self.headers.get(header::EXPIRES) {
+            // The invalid `Expires` means already expired.
+            return parse_date(expires)
+                .and_then(|expires| expires.duration_since(date).ok())
+                .unwrap_or_default();
+        }
+        // RFC 9111 4.2.2: 10% of the time since the last modification.
+        match self.headers.get(header::LAST_MODIFIED).and_then(parse_date) {
+            Some(last_modified) if is_heuristically_cacheable(self.status) => date.duration_since(last_modified).unwrap_or_default() / 10,
+            _ => Duration::ZERO,
+        }
+    }
+
+    // RFC 9111 4.2.3
+    fn current_age(&self, now: SystemTime) -> Duration {
+        let age_value = self
+            .headers
+            .get(header::AGE)
+            .and_then(|age| age.to_str().ok()?.trim().parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .unwrap_or_default();
+        let apparent_age = self.response_time.duration_since(self.date()).unwrap_or_default();
+        let response_delay = self.response_time.duration_since(self.request_time).unwrap_or_default();
+        let corrected_initial_age = apparent_age.max(age_value + response_delay);
+        let resident_time = now.duration_since(self.response_time).unwrap_or_default();
+        corrected_initial_age + resident_time
+    }
+
+    fn date(&self) -> SystemTime {
+        self.headers.get(header::DATE).and_then(parse_date).unwrap_or(self.response_time)
+    }
+
+    // Whether the stored response can be used without the validation. (RFC 9111 4.2.4 and 5.2.1)
+    fn can_serve(&self, request_cc: &CacheControl, now: SystemTime) -> bool {
+        let response_cc = CacheControl::parse(&self.headers);
+        if response_cc.no_cache {
+            return false;
+        }
+        let age = self.current_age(now);
+        let lifetime = self.freshness_lifetime();
+        if request_cc.max_age.is_some_and(|max_age| age > Duration::from_secs(max_age)) {
+            return false;
+        }
+        if let Some(min_fresh) = request_cc.min_fresh {
+            return lifetime >= age + Duration::from_secs(min_fresh);
+        }
+        if lifetime > age {
+            return true;
+        }
+        match request_cc.max_stale {
+            Some(max_stale) if !response_cc.must_revalidate => age - lifetime <= Duration::from_secs(max_stale),
+            _ => false,
+        }
+    }
+
+    fn to_response(&self, body: Bytes, now: SystemTime, cache_status: &str) -> Response<ResponseBody> {
+        let mut res = Response::new(to_response_body(Full::new(body.clone())));
+        *res.status_mut() = self.status;
+        *res.version_mut() = self.version;
+        *res.headers_mut() = self.headers.clone();
+        res.headers_mut().insert(header::CONTENT_LENGTH, HeaderValue::from(body.len()));
+        res.headers_mut().insert(header::AGE, HeaderValue::from(self.current_age(now).as_secs()));
+        with_cache_status(res, cache_status)
+    }
+}
+
+#[derive(Default)]
+struct CacheControl {
+    no_store: bool,
+    no_cache: bool,
+    must_revalidate: bool,
+    public: bool,
+    private: bool,
+    only_if_cached: bool,
+    max_age: Option<u64>,
+    // `max-stale` without the value accepts any staleness.
+    max_stale: Option<u64>,
+    min_fresh: Option<u64>,
+}
+
+impl CacheControl {
+    fn parse(headers: &HeaderMap) -> Self {
+        let mut cc = CacheControl::default();
+        for directive in list_values(headers, header::CACHE_CONTROL) {
+            let (name, value) = match directive.split_once('=') {
+                Some((name, value)) => (name.trim().to_ascii_lowercase(), Some(value.trim().trim_matches('"'))),
+                None => (directive.to_ascii_lowercase(), None),
+            };
+            let seconds = value.and_then(|value| value.parse::<u64>().ok());
+            match name.as_str() {
+                "no-store" => cc.no_store = true,
+                // The qualified `no-cache` is handled as unqualified.
+                "no-cache" => cc.no_cache = true,
+                "must-revalidate" | "proxy-revalidate" => cc.must_revalidate = true,
+                "public" => cc.public = true,
+                "private" => cc.private = true,
+                "only-if-cached" => cc.only_if_cached = true,
+                // The invalid `max-age` means already stale.
+                "max-age" => cc.max_age = Some(seconds.unwrap_or(0)),
+                "max-stale" => cc.max_stale = Some(seconds.unwrap_or(u64::MAX)),
+                "min-fresh" => cc.min_fresh = seconds,
+                _ => {}
+            }
+        }
+        cc
+    }
+
+    fn from_request(headers: &HeaderMap) -> Self {
+        let mut cc = CacheControl::parse(headers);
+        // RFC 9111 5.4: `Pragma: no-cache` is used if `Cache-Control` is not present.
+        if !headers.contains_key(header::CACHE_CONTROL) && list_values(headers, header::PRAGMA).iter().any(|value| value.eq_ignore_ascii_case("no-cache")) {
+            cc.no_cache = true;
+        }
+        cc
+    }
+}
+
+/// Passes through the response body, and stores the response when the body is completely received.
+struct CachingBody {
+    inner: BoxBody<Bytes, hyper::Error>,
+    cache: Arc<CacheInner>,
+    // The response to store. It is taken when stored or abandoned.
+    pending: Option<(String, StoredResponse, Vec<u8>)>,
+}
+
+impl CachingBody {
+    fn new(inner: BoxBody<Bytes, hyper::Error>, cache: Arc<CacheInner>, key: String, response: StoredResponse) -> Self {
+        let mut body = CachingBody {
+            inner,
+            cache,
+            pending: Some((key, response, Vec::new())),
+        };
+        // The empty body is never polled.
+        if body.inner.is_end_stream() {
+            body.complete();
+        }
+        body
+    }
+
+    fn complete(&mut self) {
+        if let Some((key, response, body)) = self.pending.take() {
+            self.cache.store(key, response, Bytes::from(body));
+        }
+    }
+}
+
+impl Body for CachingBody {
+    type Data = Bytes;
+    type Error = hyper::Error;
+
+    fn poll_frame(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = &mut *self;
+        match Pin::new(&mut this.inner).poll_frame(cx) {
+            Poll::Ready(Some(Ok(frame))) => {
+                match (frame.data_ref(), &mut this.pending) {
+                    (Some(data), Some((_, _, body))) => {
+                        if (body.len() + data.len()) as u64 <= this.cache.options.max_entry_size {
+                            body.extend_from_slice(data);
+                        } else {
+                            this.pending = None;
+                        }
+                    }
+                    // The responses with trailers are not cached.
+                    (None, _) => this.pending = None,
+                    _ => {}
+                }
+                // The reader may stop polling at the end of the stream.
+                if this.inner.is_end_stream() {
+                    this.complete();
+                }
+                Poll::Ready(Some(Ok(frame)))
+            }
+            Poll::Ready(Some(Err(err))) => {
+                this.pending = None;
+                Poll::Ready(Some(Err(err)))
+            }
+            Poll::Ready(None) => {
+                this.complete();
+                Poll::Ready(None)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+enum StoredBody {
+    Memory(Bytes),
+    // The body starts at `offset` of the file.
+    Disk { path: PathBuf, offset: usize },
+}
+
+impl Clone for StoredBody {
+    fn clone(&self) -> Self {
+        match self {
+            StoredBody::Memory(body) => StoredBody::Memory(body.clone()),
+            StoredBody::Disk { path, offset } => StoredBody::Disk { path: path.clone(), offset: *offset },
+        }
+    }
+}
+
+struct Entry {
+    key: String,
+    response: Arc<StoredResponse>,
+    body: StoredBody,
+    size: u64,
+}
+
+/// The stored responses ordered by the last use.
+#[derive(Default)]
+struct Index {
+    entries: HashMap<u64, (Entry, u64)>,
+    // The variants of each URI.
+    keys: HashMap<String, Vec<u64>>,
+    lru: BTreeMap<u64, u64>,
+    size: u64,
+    tick: u64,
+    next_id: u64,
+}
+
+impl Index {
+    fn lookup(&mut self, key: &str, headers: &HeaderMap) -> Option<(u64, Arc<StoredResponse>, StoredBody)> {
+        let id = self
+            .keys
+            .get(key)?
+            .iter()
+            .filter(|id| self.entries[id].0.response.matches(headers))
+            .max_by_key(|id| self.entries[id].0.response.response_time)
+            .copied()?;
+        self.touch(id);
+        let (entry, _) = &self.entries[&id];
+        Some((id, entry.response.clone(), entry.body.clone()))
+    }
+
+    fn touch(&mut self, id: u64) {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some((_, last_used)) = self.entries.get_mut(&id) {
+            self.lru.remove(last_used);
+            *last_used = tick;
+            self.lru.insert(tick, id);
+        }
+    }
+
+    // Inserts the entry and returns the replaced variant.
+    fn insert(&mut self, entry: Entry) -> Vec<Entry> {
+        let replaced = self
+            .keys
+            .get(&entry.key)
+            .map(|ids| {
+                ids.iter()
+                    .filter(|id| self.entries[id].0.response.vary == entry.response.vary)
+                    .copied()
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let replaced = replaced.into_iter().filter_map(|id| self.remove(id)).collect();
+
+        self.next_id += 1;
+        self.tick += 1;
+        let id = self.next_id;
+        self.size += entry.size;
+        self.keys.entry(entry.key.clone()).or_default().push(id);
+        self.lru.insert(self.tick, id);
+        self.entries.insert(id, (entry, self.tick));
+        replaced
+    }
+
+    fn remove(&mut self, id: u64) -> Option<Entry> {
+        let (entry, last_used) = self.entries.remove(&id)?;
+        self.lru.remove(&last_used);
+        self.size -= entry.size;
+        if let Some(ids) = self.keys.get_mut(&entry.key) {
+            ids.retain(|x| *x != id);
+            if ids.is_empty() {
+                self.keys.remove(&entry.key);
+            }
+        }
+        Some(entry)
+    }
+
+    fn remove_key(&mut self, key: &str) -> Vec<Entry> {
+        let ids = self.keys.get(key).cloned().unwrap_or_default();
+        ids.into_iter().filter_map(|id| self.remove(id)).collect()
+    }
+
+    // Evicts the least recently used entries until the total size is within `max_size`.
+    fn evict(&mut self, max_size: u64) -> Vec<Entry> {
+        let mut evicted = Vec::new();
+        while self.size > max_size {
+            let id = match self.lru.values().next() {
+                Some(id) => *id,
+                None => break,
+            };
+            evicted.extend(self.remove(id));
+        }
+        evicted
+    }
+}
+
+fn with_cache_status(mut res: Response<ResponseBody>, status: &str) -> Response<ResponseBody> {
+    if let Ok(value) = HeaderValue::from_str(&format!("{CACHE_NAME}; {status}")) {
+        res.headers_mut().insert(CACHE_STATUS, value);
+    }
+    res
+}
+
+// RFC 9111 4.2.2
+fn is_heuristically_cacheable(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 200 | 203 | 204 | 206 | 300 | 301 | 308 | 404 | 405 | 410 | 414 | 501)
+}
+
+// The headers except the hop-by-hop headers and `Set-Cookie` (the cookies are not replayed from the cache).
+fn storable_headers(headers: &HeaderMap) -> HeaderMap {
+    let mut headers = headers.clone();
+    for name in [
+        header::CONNECTION,
+        header::TRANSFER_ENCODING,
+        header::TE,
+        header::TRAILER,
+        header::UPGRADE,
+        header::PROXY_AUTHENTICATE,
+        header::SET_COOKIE,
+        HeaderName::from_static("keep-alive"),
+        HeaderName::from_static("proxy-connection"),
+        CACHE_STATUS,
+    ] {
+        headers.remove(name);
+    }
+    headers
+}
+
+fn list_values(headers: &HeaderMap, name: HeaderName) -> Vec<String> {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+fn joined_values(headers: &HeaderMap, name: &HeaderName) -> String {
+    headers
+        .get_all(name)
+        .iter()
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).trim().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn parse_date(value: &HeaderValue) -> Option<SystemTime> {
+    httpdate::parse_http_date(value.to_str().ok()?).ok()
+}
+
+fn remove_files(entries: Vec<Entry>) {
+    for entry in entries {
+        if let StoredBody::Disk { path, .. } = entry.body {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+fn to_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+// Writes the response to the file, and returns the offset of the body.
+// The file consists of the magic, the URI, the status line with the times, the `Vary` request headers, the response headers and the body.
+fn write_file(path: &Path, key: &str, response: &StoredResponse, body: &[u8]) -> io::Result<usize> {
+    let mut buf = Vec::new();
+    writeln!(buf, "{FILE_MAGIC}")?;
+    writeln!(buf, "{key}")?;
+    writeln!(
+        buf,
+        "{} {:?} {} {}",
+        response.status.as_u16(),
+        response.version,
+        to_millis(response.request_time),
+        to_millis(response.response_time)
+    )?;
+    for (name, value) in &response.vary {
+        writeln!(buf, "{name}: {value}")?;
+    }
+    writeln!(buf)?;
+    for (name, value) in &response.headers {
+        buf.extend_from_slice(name.as_str().as_bytes());
+        buf.extend_from_slice(b": ");
+        buf.extend_from_slice(value.as_bytes());
+        buf.push(b'\n');
+    }
+    writeln!(buf)?;
+    let offset = buf.len();
+    buf.extend_from_slice(body);
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, buf)?;
+    fs::rename(&tmp, path)?;
+    Ok(offset)
+}
+
+// Reads the response except the body from the file.
+fn read_file(path: &Path) -> Option<Entry> {
+    let size = fs::metadata(path).ok()?.len();
+    let mut reader = BufReader::new(File::open(path).ok()?);
+    let mut offset = 0;
+    let mut read_line = || -> Option<Vec<u8>> {
+        let mut line = Vec::new();
+        offset += reader.read_until(b'\n', &mut line).ok()?;
+        (line.pop() == Some(b'\n')).then_some(line)
+    };
+    let read_header = |line: &[u8]| -> Option<(HeaderName, Vec<u8>)> {
+        let colon = line.iter().position(|x| *x == b':')?;
+        let name = HeaderName::from_bytes(&line[..colon]).ok()?;
+        let value = line[colon + 1..].strip_prefix(b" ").unwrap_or(&line[colon + 1..]);
+        Some((name, value.to_vec()))
+    };
+
+    if read_line()? != FILE_MAGIC.as_bytes() {
+        return None;
+    }
+    let key = String::from_utf8(read_line()?).ok()?;
+    let status_line = String::from_utf8(read_line()?).ok()?;
+    let mut fields = status_line.split(' ');
+    let status = StatusCode::from_u16(fields.next()?.parse().ok()?).ok()?;
+    let version = match fields.next()? {
+        "HTTP/0.9" => Version::HTTP_09,
+        "HTTP/1.0" => Version::HTTP_10,
+        "HTTP/1.1" => Version::HTTP_11,
+        "HTTP/2.0" => Version::HTTP_2,
+        "HTTP/3.0" => Version::HTTP_3,
+        _ => return None,
+    };
+    let request_time = UNIX_EPOCH + Duration::from_millis(fields.next()?.parse().ok()?);
+    let response_time = UNIX_EPOCH + Duration::from_millis(fields.next()?.parse().ok()?);
+
+    let mut vary = Vec::new();
+    loop {
+        let line = read_line()?;
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = read_header(&line)?;
+        vary.push((name, String::from_utf8(value).ok()?));
+    }
+    let mut headers = HeaderMap::new();
+    loop {
+        let line = read_line()?;
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = read_header(&line)?;
+        headers.append(name, HeaderValue::from_bytes(&value).ok()?);
+    }
+
+    let response = StoredResponse { status, version, headers, vary, request_time, response_time };
+    Some(Entry {
+        size: key.len() as u64 + response.header_size() + (size - offset as u64),
+        key,
+        response: Arc::new(response),
+        body: StoredBody::Disk { path: path.to_path_buf(), offset },
+    })
+}
+
+// Loads the stored responses from the directory in the order of the response time. The invalid files written by the cache are removed,
+// and the other files are left untouched.
+fn load_directory(directory: &Path) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for path in fs::read_dir(directory).into_iter().flatten().filter_map(|entry| Some(entry.ok()?.path())) {
+        let name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) if name.starts_with(FILE_PREFIX) => name,
+            _ => continue,
+        };
+        if name.ends_with(FILE_SUFFIX) {
+            match read_file(&path) {
+                Some(entry) => entries.push(entry),
+                None => {
+                    let _ = fs::remove_file(&path);
+                }
+            }
+        } else if name.ends_with(TMP_FILE_SUFFIX) {
+            // The files left by the interrupted writes.
+            let _ = fs::remove_file(&path);
+        }
+    }
+    entries.sort_by_key(|entry| entry.response.response_time);
+    entries
+}
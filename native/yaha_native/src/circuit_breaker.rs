@@ -0,0 +1,169 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use hyper::StatusCode;
+
+#[derive(Clone, Debug)]
+pub struct CircuitBreakerOptions {
+    // Opens the circuit after this number of consecutive failures. 0 disables this condition.
+    pub consecutive_failures: u32,
+    // Opens the circuit if the failure rate of the last `sampling_requests` requests reaches this rate. 0 disables this condition.
+    pub failure_rate: f64,
+    pub sampling_requests: usize,
+    // How long the circuit stays open before a probe request is allowed. (half-open)
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerOptions {
+    fn default() -> Self {
+        CircuitBreakerOptions {
+            consecutive_failures: 5,
+            failure_rate: 0.0,
+            sampling_requests: 20,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+enum CircuitState {
+    Closed,
+    Open { until: Instant },
+    // A probe request is in flight. The other requests fail fast until the probe completes.
+    HalfOpen,
+}
+
+struct Circuit {
+    state: CircuitState,
+    consecutive_failures: u32,
+    // The outcomes of the recent requests. `true` is a failure.
+    outcomes: VecDeque<bool>,
+}
+
+impl Circuit {
+    fn new() -> Self {
+        Circuit {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            outcomes: VecDeque::new(),
+        }
+    }
+
+    fn close(&mut self) {
+        self.state = CircuitState::Closed;
+        self.consecutive_failures = 0;
+        self.outcomes.clear();
+    }
+
+    fn open(&mut self, cooldown: Duration) {
+        self.state = CircuitState::Open { until: Instant::now() + cooldown };
+        self.consecutive_failures = 0;
+        self.outcomes.clear();
+    }
+}
+
+/// Fails the requests fast while the destination (an authority or an endpoint of the load balancer) keeps failing.
+pub struct CircuitBreaker {
+    options: CircuitBreakerOptions,
+    circuits: Mutex<HashMap<String, Circuit>>,
+}
+
+/// Allows a request to the destination. The outcome of the request must be reported with `record`.
+pub struct CircuitPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    key: String,
+    recorded: bool,
+}
+
+impl CircuitPermit<'_> {
+    pub fn record(mut self, failed: bool) {
+        self.recorded = true;
+        self.breaker.record(&self.key, failed);
+    }
+}
+
+impl Drop for CircuitPermit<'_> {
+    fn drop(&mut self) {
+        if !self.recorded {
+            // The request was cancelled. Allow another probe if this was the probe request.
+            if let Some(circuit) = self.breaker.circuits.lock().unwrap().get_mut(&self.key) {
+                if let CircuitState::HalfOpen = circuit.state {
+                    circuit.state = CircuitState::Open { until: Instant::now() };
+                }
+            }
+        }
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(options: CircuitBreakerOptions) -> Self {
+        CircuitBreaker {
+            options,
+            circuits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Returns whether a request to the destination would be allowed.
+    pub fn is_available(&self, key: &str) -> bool {
+        match self.circuits.lock().unwrap().get(key).map(|circuit| &circuit.state) {
+            None | Some(CircuitState::Closed) => true,
+            Some(CircuitState::Open { until }) => Instant::now() >= *until,
+            Some(CircuitState::HalfOpen) => false,
+        }
+    }
+
+    // Returns the permit to send a request to the destination, or the error message if the circuit is open.
+    pub fn try_acquire(&self, key: &str) -> Result<CircuitPermit<'_>, String> {
+        let mut circuits = self.circuits.lock().unwrap();
+        if let Some(circuit) = circuits.get_mut(key) {
+            match circuit.state {
+                CircuitState::Closed => {}
+                CircuitState::Open { until } if Instant::now() >= until => circuit.state = CircuitState::HalfOpen,
+                CircuitState::Open { .. } | CircuitState::HalfOpen => {
+                    return Err(format!("The circuit breaker for '{}' is open.", key));
+                }
+            }
+        }
+        Ok(CircuitPermit { breaker: self, key: key.to_string(), recorded: false })
+    }
+
+    fn record(&self, key: &str, failed: bool) {
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits.entry(key.to_string()).or_insert_with(Circuit::new);
+        match circuit.state {
+            CircuitState::Closed => {}
+            CircuitState::HalfOpen => {
+                if failed {
+                    circuit.open(self.options.cooldown);
+                } else {
+                    circuit.close();
+                }
+                return;
+            }
+            // The request was sent before the circuit was opened.
+            CircuitState::Open { .. } => return,
+        }
+
+        circuit.consecutive_failures = if failed { circuit.consecutive_failures + 1 } else { 0 };
+        circuit.outcomes.push_back(failed);
+        if circuit.outcomes.len() > self.options.sampling_requests {
+            circuit.outcomes.pop_front();
+        }
+
+        let consecutive_failures_reached = self.options.consecutive_failures > 0
+            && circuit.consecutive_failures >= self.options.consecutive_failures;
+        let failure_rate_reached = self.options.failure_rate > 0.0
+            && circuit.outcomes.len() >= self.options.sampling_requests
+            && circuit.outcomes.iter().filter(|&&failed| failed).count() as f64 / circuit.outcomes.len() as f64 >= self.options.failure_rate;
+        if consecutive_failures_reached || failure_rate_reached {
+            circuit.open(self.options.cooldown);
+        }
+    }
+}
+
+// The responses that indicate the server is failing or overloaded. (same as the transient errors of Polly)
+pub fn is_failure_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::REQUEST_TIMEOUT || status == StatusCode::TOO_MANY_REQUESTS
+}
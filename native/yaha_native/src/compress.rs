@@ -0,0 +1,53 @@
+use std::io;
+
+use async_compression::{
+    tokio::bufread::{BrotliEncoder, GzipEncoder, ZstdEncoder},
+    Level,
+};
+use futures_util::{Stream, TryStreamExt};
+use http_body_util::{combinators::BoxBody, StreamBody};
+use hyper::{
+    body::{Bytes, Frame},
+    header::{self, HeaderValue},
+    HeaderMap,
+};
+use tokio::io::AsyncRead;
+use tokio_util::io::{ReaderStream, StreamReader};
+
+use crate::{context::RequestBody, primitives::YahaRequestBodyCompression};
+
+// Sets `Content-Encoding` header for the compressed body, and removes `Content-Length` header to send the body in chunks. (or DATA frames on HTTP/2)
+// If the application has already encoded the body, the coding is appended to the list in the order applied.
+pub fn set_content_encoding(headers: &mut HeaderMap, compression: YahaRequestBodyCompression) {
+    let coding = match compression {
+        YahaRequestBodyCompression::Gzip => "gzip",
+        YahaRequestBodyCompression::Brotli => "br",
+        YahaRequestBodyCompression::Zstd => "zstd",
+    };
+    let value = match headers.get(header::CONTENT_ENCODING).and_then(|value| value.to_str().ok()) {
+        Some(value) if !value.trim().is_empty() => HeaderValue::try_from(format!("{}, {}", value.trim(), coding)).unwrap(),
+        _ => HeaderValue::from_static(coding),
+    };
+    headers.insert(header::CONTENT_ENCODING, value);
+    headers.remove(header::CONTENT_LENGTH);
+}
+
+// Compresses the chunks written by the application while they are streamed.
+// The level is clamped to the range of the algorithm. (gzip: 0-9, brotli: 0-11, zstd: 1-22)
+pub fn compress<S>(data: S, compression: YahaRequestBodyCompression, level: Option<i32>) -> RequestBody
+where
+    S: Stream<Item = io::Result<Bytes>> + Send + Sync + Unpin + 'static,
+{
+    let level = level.map_or(Level::Default, Level::Precise);
+    let reader = StreamReader::new(data);
+    match compression {
+        YahaRequestBodyCompression::Gzip => to_body(GzipEncoder::with_quality(reader, level)),
+        YahaRequestBodyCompression::Brotli => to_body(BrotliEncoder::with_quality(reader, level)),
+        YahaRequestBodyCompression::Zstd => to_body(ZstdEncoder::with_quality(reader, level)),
+    }
+}
+
+fn to_body<R: AsyncRead + Send + Sync + 'static>(reader: R) -> RequestBody {
+    let frames = ReaderStream::new(reader).map_ok(Frame::data).map_err(Into::into);
+    BoxBody::new(StreamBody::new(frames))
+}
@@ -1,24 +1,25 @@
 use std::{
+    net::{IpAddr, SocketAddr},
     num::NonZeroIsize,
     sync::{Arc, Mutex},
     time::Duration,
 };
 use futures_channel::mpsc::Sender;
-use http_body_util::combinators::BoxBody;
+use http_body_util::{combinators::BoxBody, BodyExt, Empty};
 use tokio::runtime::{Builder, Handle, Runtime};
 
 use hyper::{
-    body::Bytes, 
-    Request, StatusCode
+    body::{Body, Bytes, Incoming},
+    header::{PROXY_AUTHENTICATE, PROXY_AUTHORIZATION},
+    http::uri::Scheme,
+    Request, Response, StatusCode, Uri
 };
 
 use hyper_util::{
-    client::{self, legacy::{connect::HttpConnector, Client, ResponseFuture}},
+    client::{self, legacy::{connect::HttpConnector, Client}},
     rt::{TokioExecutor, TokioTimer},
 };
 
-use hyper_rustls::ConfigBuilderExt;
-
 #[cfg(feature = "rustls")]
 use hyper_rustls::HttpsConnector;
 #[cfg(feature = "native")]
@@ -27,15 +28,35 @@ use hyper_tls::HttpsConnector;
 use hyperlocal::UnixConnector;
 
 use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+#[cfg(feature = "rustls")]
+use rustls::{client::WebPkiServerVerifier, pki_types::CertificateRevocationListDer};
+#[cfg(feature = "rustls")]
+use crate::doh::DohResolver;
+#[cfg(feature = "rustls")]
+use crate::primitives::{YahaRevocationCheckDepth, YahaUnknownRevocationStatusPolicy};
+#[cfg(feature = "rustls")]
+use crate::pinning::PinStore;
+#[cfg(feature = "rustls")]
+use crate::verification::{AsyncCertificateVerification, OnServerCertificateVerificationAsyncHandler, VerifyingConnector};
 use tokio_util::sync::CancellationToken;
 
-use crate::{primitives::{CompletionReason, YahaHttpVersion}};
+use crate::{balancer::{LoadBalancer, UsedEndpoints}, cache::{CacheOptions, HttpCache}, circuit_breaker::{self, CircuitBreaker, CircuitBreakerOptions, CircuitPermit}, cookie_jar::{CookieFile, CookieJar, SiteForCookies}, decompress, dns::{self, DnsCache, DnsCacheOptions, ResolveSource, Resolver}, hedge::{HedgingOptions, HedgingPolicy}, interop::StringBuffer, overrides::{ConnectOverrides, OverrideConnector}, primitives::{CompletionReason, YahaAddressFamilyPreference, YahaHttpVersion, YahaLoadBalancingPolicy, YahaRequestBodyCompression}, proxy::{EnvironmentProxy, Proxy, ProxyAuthentication, ProxyAuthenticator, ProxyConnector, ProxySelector}, redirect::{RedirectOptions, RedirectPolicy}, retry::{RetryOptions, RetryPolicy}, srv::{SrvConnector, SrvResolver}};
 
 type OnStatusCodeAndHeadersReceive =
     extern "C" fn(req_seq: i32, state: NonZeroIsize, status_code: i32, version: YahaHttpVersion);
 type OnReceive = extern "C" fn(req_seq: i32, state: NonZeroIsize, length: usize, buf: *const u8, task_handle: usize);
 type OnComplete = extern "C" fn(req_seq: i32, state: NonZeroIsize, reason: CompletionReason, h2_error_code: u32);
-type OnServerCertificateVerificationHandler = extern "C" fn(callback_state: NonZeroIsize, server_name: *const u8, server_name_len: usize, certificate_der: *const u8, certificate_der_len: usize, now: u64) -> bool;
+// The certificates are the end-entity certificate followed by the intermediates presented by the server.
+// The default verification error is empty if the chain is valid for the root certificates.
+type OnServerCertificateVerificationHandler = extern "C" fn(callback_state: NonZeroIsize, server_name: *const u8, server_name_len: usize, certificates: *const StringBuffer, certificates_len: usize, ocsp_response: *const u8, ocsp_response_len: usize, default_verification_error: *const u8, default_verification_error_len: usize, now: u64) -> bool;
+pub type OnResolveHandler = extern "C" fn(callback_state: NonZeroIsize, host: *const u8, host_len: usize, task_handle: usize);
+pub type OnProxySelectionHandler = extern "C" fn(callback_state: NonZeroIsize, uri: *const u8, uri_len: usize, selection: *mut YahaProxySelection) -> bool;
+
+#[cfg(feature = "rustls")]
+type TcpConnector = VerifyingConnector<HttpsConnector<ProxyConnector<SrvConnector<OverrideConnector<HttpConnector<Resolver>>>>>>;
+#[cfg(feature = "native")]
+type TcpConnector = HttpsConnector<ProxyConnector<SrvConnector<OverrideConnector<HttpConnector<Resolver>>>>>;
+type TcpClient = Client<TcpConnector, RequestBody>;
 
 pub struct YahaNativeRuntimeContext;
 pub struct YahaNativeRuntimeContextInternal {
@@ -61,24 +82,122 @@ impl YahaNativeRuntimeContextInternal {
     }
 }
 
+pub struct YahaProxySelection;
+
+pub enum RequestError {
+    Client(client::legacy::Error),
+    // The request was not sent because the circuit breaker for the destination is open.
+    CircuitBreakerOpen(String),
+}
+
+impl From<client::legacy::Error> for RequestError {
+    fn from(err: client::legacy::Error) -> Self {
+        RequestError::Client(err)
+    }
+}
+
+// The body of the request sent from the application. It is the body written by the application or the compressed one.
+pub type RequestBody = BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>;
+
+// The body of the response passed to the application. It is the body received from the server, the cached one, or the decompressed one.
+pub type ResponseBody = BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>;
+
+pub fn to_response_body<B>(body: B) -> ResponseBody
+where
+    B: Body<Data = Bytes> + Send + Sync + 'static,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    BoxBody::new(body.map_err(Into::into))
+}
+
 pub struct YahaNativeContext;
 pub struct YahaNativeContextInternal<'a> {
     pub runtime: tokio::runtime::Handle,
     pub client_builder: Option<client::legacy::Builder>,
     pub skip_certificate_verification: Option<bool>,
     pub server_certificate_verification_handler: Option<(OnServerCertificateVerificationHandler, NonZeroIsize)>,
+    #[cfg(feature = "rustls")]
+    pub server_certificate_verification_async_handler: Option<(OnServerCertificateVerificationAsyncHandler, NonZeroIsize)>,
+    #[cfg(feature = "rustls")]
+    pub server_certificate_verification_timeout: Option<Duration>,
+    #[cfg(feature = "rustls")]
+    pub async_certificate_verification: Option<Arc<AsyncCertificateVerification>>,
     pub root_certificates: Option<rustls::RootCertStore>,
+    #[cfg(feature = "rustls")]
+    pub use_system_root_certificates: Option<bool>,
+    #[cfg(feature = "rustls")]
+    pub use_built_in_root_certificates: Option<bool>,
+    // The root certificates combined from the custom roots, the system trust store and the bundled roots.
+    #[cfg(feature = "rustls")]
+    pub root_store: Option<rustls::RootCertStore>,
+    #[cfg(feature = "rustls")]
+    pub certificate_revocation_lists: Vec<CertificateRevocationListDer<'static>>,
+    #[cfg(feature = "rustls")]
+    pub revocation_check_depth: Option<YahaRevocationCheckDepth>,
+    #[cfg(feature = "rustls")]
+    pub unknown_revocation_status_policy: Option<YahaUnknownRevocationStatusPolicy>,
+    #[cfg(feature = "rustls")]
+    pub public_key_pins: PinStore,
     pub override_server_name: Option<String>,
     pub connect_timeout: Option<Duration>,
+    pub proxy: Option<Proxy>,
+    pub use_environment_proxy: Option<bool>,
+    pub proxy_selection_handler: Option<(OnProxySelectionHandler, NonZeroIsize)>,
+    pub proxy_authentications: Vec<ProxyAuthentication>,
+    pub proxy_selector: Option<Arc<ProxySelector>>,
+    pub proxy_authenticator: Arc<ProxyAuthenticator>,
+    pub connect_overrides: ConnectOverrides,
+    pub resolve_handler: Option<(OnResolveHandler, NonZeroIsize)>,
+    pub use_built_in_dns_resolver: Option<bool>,
+    pub dns_servers: Vec<SocketAddr>,
+    pub dns_over_https_endpoint: Option<Uri>,
+    pub dns_over_https_bootstrap_addresses: Vec<IpAddr>,
+    pub dns_cache: Option<bool>,
+    pub dns_cache_options: DnsCacheOptions,
+    pub address_family_preference: Option<YahaAddressFamilyPreference>,
+    pub happy_eyeballs_fallback_delay: Option<Duration>,
+    pub resolver: Option<Resolver>,
+    pub srv_discovery: Option<bool>,
+    pub srv_resolver: Option<Arc<SrvResolver>>,
     pub client_auth_certificates: Option<Vec<CertificateDer<'a>>>,
     pub client_auth_key: Option<PrivateKeyDer<'a>>,
-    pub tcp_client: Option<Client<HttpsConnector<HttpConnector>, BoxBody<Bytes, hyper::Error>>>,
+    pub tcp_client: Option<TcpClient>,
+    pub load_balancing_policy: Option<YahaLoadBalancingPolicy>,
+    pub load_balancing_re_resolve_interval: Option<Duration>,
+    pub load_balancer: Option<LoadBalancer<TcpClient>>,
+    // The builder to create the clients for the endpoints of the load balancer.
+    pub endpoint_client_builder: Option<client::legacy::Builder>,
+    // The TLS configuration to create the connectors of the default client and the endpoint clients.
+    #[cfg(feature = "rustls")]
+    pub tls_config: Option<rustls::ClientConfig>,
+    pub circuit_breaker_enabled: Option<bool>,
+    pub circuit_breaker_options: CircuitBreakerOptions,
+    pub circuit_breaker: Option<CircuitBreaker>,
+    pub retry_enabled: Option<bool>,
+    pub retry_options: RetryOptions,
+    pub retry_policy: Option<RetryPolicy>,
+    pub hedging_delay: Option<Duration>,
+    pub hedging_max_attempts: Option<u32>,
+    pub hedging_policy: Option<HedgingPolicy>,
+    pub redirect_enabled: Option<bool>,
+    pub redirect_options: RedirectOptions,
+    pub redirect_policy: Option<RedirectPolicy>,
+    pub cookie_jar_enabled: Option<bool>,
+    // The cookies loaded from the cookie file, and the file to save the cookies.
+    pub cookie_store: Option<cookie_store::CookieStore>,
+    pub cookie_file: Option<CookieFile>,
+    pub cookie_public_suffix_list: Option<publicsuffix::List>,
+    pub cookie_jar: Option<CookieJar>,
+    pub cache_enabled: Option<bool>,
+    pub cache_options: CacheOptions,
+    pub cache: Option<HttpCache>,
+    pub decompression: Option<bool>,
     pub on_status_code_and_headers_receive: OnStatusCodeAndHeadersReceive,
     pub on_receive: OnReceive,
     pub on_complete: OnComplete,
 
     #[cfg(unix)]
-    pub uds_client: Option<Client<UnixConnector, BoxBody<Bytes, hyper::Error>>>,
+    pub uds_client: Option<Client<UnixConnector, RequestBody>>,
     #[cfg(unix)]
     pub uds_socket_path: Option<std::path::PathBuf>,
 }
@@ -97,12 +216,78 @@ impl YahaNativeContextInternal<'_> {
         YahaNativeContextInternal {
             runtime: runtime_handle,
             tcp_client: None,
+            load_balancing_policy: None,
+            load_balancing_re_resolve_interval: None,
+            load_balancer: None,
+            endpoint_client_builder: None,
+            #[cfg(feature = "rustls")]
+            tls_config: None,
+            circuit_breaker_enabled: None,
+            circuit_breaker_options: CircuitBreakerOptions::default(),
+            circuit_breaker: None,
+            retry_enabled: None,
+            retry_options: RetryOptions::default(),
+            retry_policy: None,
+            hedging_delay: None,
+            hedging_max_attempts: None,
+            hedging_policy: None,
+            redirect_enabled: None,
+            redirect_options: RedirectOptions::default(),
+            redirect_policy: None,
+            cookie_jar_enabled: None,
+            cookie_store: None,
+            cookie_file: None,
+            cookie_public_suffix_list: None,
+            cookie_jar: None,
+            cache_enabled: None,
+            cache_options: CacheOptions::default(),
+            cache: None,
+            decompression: None,
             client_builder: Some(Client::builder(TokioExecutor::new())),
             skip_certificate_verification: None,
             server_certificate_verification_handler: None,
+            #[cfg(feature = "rustls")]
+            server_certificate_verification_async_handler: None,
+            #[cfg(feature = "rustls")]
+            server_certificate_verification_timeout: None,
+            #[cfg(feature = "rustls")]
+            async_certificate_verification: None,
             root_certificates: None,
+            #[cfg(feature = "rustls")]
+            use_system_root_certificates: None,
+            #[cfg(feature = "rustls")]
+            use_built_in_root_certificates: None,
+            #[cfg(feature = "rustls")]
+            root_store: None,
+            #[cfg(feature = "rustls")]
+            certificate_revocation_lists: Vec::new(),
+            #[cfg(feature = "rustls")]
+            revocation_check_depth: None,
+            #[cfg(feature = "rustls")]
+            unknown_revocation_status_policy: None,
+            #[cfg(feature = "rustls")]
+            public_key_pins: PinStore::default(),
             override_server_name: None,
             connect_timeout: None,
+            proxy: None,
+            use_environment_proxy: None,
+            proxy_selection_handler: None,
+            proxy_authentications: Vec::new(),
+            proxy_selector: None,
+            proxy_authenticator: Arc::default(),
+            connect_overrides: ConnectOverrides::default(),
+            resolve_handler: None,
+            use_built_in_dns_resolver: None,
+            dns_servers: Vec::new(),
+            dns_over_https_endpoint: None,
+            dns_over_https_bootstrap_addresses: Vec::new(),
+            dns_cache: None,
+            dns_cache_options: DnsCacheOptions::default(),
+            address_family_preference: None,
+            happy_eyeballs_fallback_delay: None,
+            resolver: None,
+            srv_discovery: None,
+            srv_resolver: None,
             client_auth_certificates: None,
             client_auth_key: None,
             on_status_code_and_headers_receive,
@@ -119,33 +304,146 @@ impl YahaNativeContextInternal<'_> {
         let mut builder = self.client_builder.take().unwrap();
         builder.timer(TokioTimer::new());
 
+        // The proxy selection handler takes precedence over the explicitly specified proxy and the environment variables.
+        self.proxy_selector = if let Some(handler) = self.proxy_selection_handler {
+            Some(Arc::new(ProxySelector::Callback(handler)))
+        } else if let Some(proxy) = &self.proxy {
+            Some(Arc::new(ProxySelector::Fixed(proxy.clone())))
+        } else if self.use_environment_proxy.unwrap_or_default() {
+            Some(Arc::new(ProxySelector::Environment(Box::new(EnvironmentProxy::from_env()))))
+        } else {
+            None
+        };
+        self.proxy_authenticator = Arc::new(ProxyAuthenticator::new(self.proxy_authentications.clone()));
+        #[cfg(feature = "rustls")]
+        {
+            // Loading the system trust store is expensive, so the roots are combined only once.
+            self.root_store = Some(self.new_root_store());
+            self.async_certificate_verification = self.server_certificate_verification_async_handler.map(|handler| {
+                let timeout = self.server_certificate_verification_timeout.unwrap_or(Duration::from_secs(30));
+                Arc::new(AsyncCertificateVerification::new(handler, self.new_webpki_verifier(), timeout))
+            });
+        }
+        #[cfg(feature = "rustls")]
+        {
+            self.tls_config = Some(self.new_tls_config());
+        }
+        self.resolver = Some(self.new_resolver());
+        self.srv_resolver = self.new_srv_resolver();
+        if let Some(policy) = self.load_balancing_policy {
+            let re_resolve_interval = self.load_balancing_re_resolve_interval.unwrap_or(Duration::from_secs(30));
+            self.load_balancer = Some(LoadBalancer::new(policy, re_resolve_interval));
+            self.endpoint_client_builder = Some(builder.clone());
+        }
+        self.circuit_breaker = self.circuit_breaker_enabled
+            .unwrap_or_default()
+            .then(|| CircuitBreaker::new(self.circuit_breaker_options.clone()));
+        self.retry_policy = self.retry_enabled
+            .unwrap_or_default()
+            .then(|| RetryPolicy::new(self.retry_options.clone()));
+        self.hedging_policy = self.hedging_delay.map(|delay| HedgingPolicy::new(HedgingOptions {
+            delay,
+            max_attempts: self.hedging_max_attempts.unwrap_or(2),
+        }));
+        self.redirect_policy = self.redirect_enabled
+            .unwrap_or_default()
+            .then(|| RedirectPolicy::new(self.redirect_options.clone()));
+        if self.cookie_jar_enabled.unwrap_or_default() {
+            self.cookie_jar = Some(CookieJar::new(self.cookie_store.take(), self.cookie_public_suffix_list.take(), self.cookie_file.take()));
+        }
+        self.cache = self.cache_enabled
+            .unwrap_or_default()
+            .then(|| HttpCache::new(self.cache_options.clone()));
+
         #[cfg(unix)]
         {
             if self.uds_socket_path.is_some() {
                 self.uds_client = Some(builder.build(UnixConnector));
             } else {
-                let https = self.new_connector();
+                let https = self.new_connector(Arc::new(self.connect_overrides.clone()));
                 self.tcp_client = Some(builder.build(https));
             }
         }
         #[cfg(not(unix))]
         {
-            let https = self.new_connector();
+            let https = self.new_connector(Arc::new(self.connect_overrides.clone()));
             self.tcp_client = Some(builder.build(https));
         }
     }
 
+    fn new_resolver(&self) -> Resolver {
+        // The resolver callback takes precedence over DNS-over-HTTPS and the built-in resolver.
+        let source = if let Some(handler) = self.resolve_handler {
+            ResolveSource::Callback(handler, self.connect_timeout.unwrap_or(dns::DEFAULT_RESOLVE_CALLBACK_TIMEOUT))
+        } else if let Some(source) = self.new_dns_over_https_source() {
+            source
+        } else if self.use_built_in_dns_resolver.unwrap_or_default() {
+            // The built-in resolver requires the runtime context to be created.
+            let _guard = self.runtime.enter();
+            // Fall back to getaddrinfo if the system DNS configuration is not available.
+            ResolveSource::built_in(&self.dns_servers).unwrap_or_default()
+        } else {
+            ResolveSource::default()
+        };
+
+        let cache = self.dns_cache
+            .unwrap_or_default()
+            .then(|| Arc::new(DnsCache::new(self.dns_cache_options.clone())));
+        Resolver::new(source, cache)
+            .with_family_preference(self.address_family_preference.unwrap_or_default())
+    }
+
+    #[cfg(feature = "rustls")]
+    fn new_dns_over_https_source(&self) -> Option<ResolveSource> {
+        let endpoint = self.dns_over_https_endpoint.as_ref()?;
+        // The connection to the DoH server uses the same root store and verification settings as the destination.
+        let tls_config = self.auxiliary_tls_config(self.tls_config.as_ref().unwrap());
+        let _guard = self.runtime.enter();
+        Some(match DohResolver::new(endpoint.clone(), &self.dns_over_https_bootstrap_addresses, tls_config, self.connect_timeout) {
+            Ok(resolver) => ResolveSource::DnsOverHttps(Arc::new(resolver)),
+            Err(_) => ResolveSource::default(),
+        })
+    }
+
+    // DNS-over-HTTPS requires rustls. The endpoint is rejected by `yaha_client_config_dns_over_https_endpoint`.
+    #[cfg(feature = "native")]
+    fn new_dns_over_https_source(&self) -> Option<ResolveSource> {
+        None
+    }
+
+    fn new_srv_resolver(&self) -> Option<Arc<SrvResolver>> {
+        if !self.srv_discovery.unwrap_or_default() {
+            return None;
+        }
+        // SRV records are always looked up with the built-in resolver.
+        let (config, opts) = dns::built_in_config(&self.dns_servers)?;
+        let _guard = self.runtime.enter();
+        Some(Arc::new(SrvResolver::new(config, opts)))
+    }
+
+    pub fn flush_dns_cache(&self) {
+        if let Some(resolver) = &self.resolver {
+            resolver.flush();
+        }
+    }
+
     #[cfg(feature = "rustls")]
-    fn new_connector(&mut self) -> HttpsConnector<HttpConnector> {
+    fn new_tls_config(&self) -> rustls::ClientConfig {
         let tls_config_builder = rustls::ClientConfig::builder();
 
         // Configure certificate root store.
         let tls_config: rustls::ClientConfig;
-        if let Some(server_certificate_verification_handler) = self.server_certificate_verification_handler {
+        if let Some(async_certificate_verification) = &self.async_certificate_verification {
+            // Verify the server certificate with the asynchronous handler after the handshake.
+            tls_config = tls_config_builder
+                .dangerous()
+                .with_custom_certificate_verifier(async_certificate_verification.verifier())
+                .with_no_client_auth();
+        } else if let Some(server_certificate_verification_handler) = self.server_certificate_verification_handler {
             // Use custom certificate verification handler
             tls_config = tls_config_builder
                 .dangerous()
-                .with_custom_certificate_verifier(Arc::new(danger::CustomCerficateVerification { handler: server_certificate_verification_handler }))
+                .with_custom_certificate_verifier(Arc::new(danger::CustomCerficateVerification::new(server_certificate_verification_handler, self.new_webpki_verifier())))
                 .with_no_client_auth();
         } else if self.skip_certificate_verification.unwrap_or_default() {
             // Skip certificate verification
@@ -154,17 +452,8 @@ impl YahaNativeContextInternal<'_> {
                 .with_custom_certificate_verifier(Arc::new(danger::NoCertificateVerification{}))
                 .with_no_client_auth();
         } else {
-            // Configure to use built-in certification store and client authentication.
-            let tls_config_builder_root: rustls::ConfigBuilder<
-                rustls::ClientConfig,
-                rustls::client::WantsClientCert,
-            >;
-            if let Some(root_certificates) = &self.root_certificates {
-                tls_config_builder_root =
-                    tls_config_builder.with_root_certificates(root_certificates.to_owned());
-            } else {
-                tls_config_builder_root = tls_config_builder.with_webpki_roots();
-            }
+            // Configure to use the root certificates, the certificate revocation lists and client authentication.
+            let tls_config_builder_root = self.with_webpki_verifier(tls_config_builder);
 
             tls_config = if let Some(client_auth_certificates) = &self.client_auth_certificates {
                 if let Some(client_auth_key) = &self.client_auth_key {
@@ -187,6 +476,72 @@ impl YahaNativeContextInternal<'_> {
                 tls_config_builder_root.with_no_client_auth()
             }
         }
+        tls_config
+    }
+
+    // The TLS configuration for the connections to the proxy and DNS-over-HTTPS server.
+    // The asynchronous verification handler is not called for them, so the standard verification is used instead.
+    #[cfg(feature = "rustls")]
+    fn auxiliary_tls_config(&self, tls_config: &rustls::ClientConfig) -> rustls::ClientConfig {
+        if self.async_certificate_verification.is_none() {
+            return tls_config.clone();
+        }
+        self.with_webpki_verifier(rustls::ClientConfig::builder())
+            .with_no_client_auth()
+    }
+
+    #[cfg(feature = "rustls")]
+    fn with_webpki_verifier(&self, builder: rustls::ConfigBuilder<rustls::ClientConfig, rustls::WantsVerifier>) -> rustls::ConfigBuilder<rustls::ClientConfig, rustls::client::WantsClientCert> {
+        match self.new_webpki_verifier() {
+            Ok(verifier) => builder.with_webpki_verifier(verifier),
+            // The verifier can't be built without the root certificates. All the servers are untrusted in this case.
+            Err(_) => builder.with_root_certificates(self.root_store()),
+        }
+    }
+
+    // The verifier for the standard verification. The revocation status is checked if the certificate revocation lists are configured.
+    #[cfg(feature = "rustls")]
+    fn new_webpki_verifier(&self) -> Result<Arc<WebPkiServerVerifier>, String> {
+        let mut builder = WebPkiServerVerifier::builder(Arc::new(self.root_store()))
+            .with_crls(self.certificate_revocation_lists.iter().cloned());
+        if self.revocation_check_depth == Some(YahaRevocationCheckDepth::EndEntity) {
+            builder = builder.only_check_end_entity_revocation();
+        }
+        if self.unknown_revocation_status_policy == Some(YahaUnknownRevocationStatusPolicy::Allow) {
+            builder = builder.allow_unknown_revocation_status();
+        }
+        builder.build().map_err(|err| err.to_string())
+    }
+
+    // The root certificates for the standard verification.
+    #[cfg(feature = "rustls")]
+    fn root_store(&self) -> rustls::RootCertStore {
+        self.root_store.clone().unwrap_or_else(|| self.new_root_store())
+    }
+
+    // Combines the custom roots, the system trust store and the bundled Mozilla's roots into one store.
+    // The bundled roots are used by default only if no custom roots are configured, as the custom roots used to replace them.
+    #[cfg(feature = "rustls")]
+    fn new_root_store(&self) -> rustls::RootCertStore {
+        let mut root_store = self.root_certificates.clone().unwrap_or_else(rustls::RootCertStore::empty);
+        if self.use_system_root_certificates.unwrap_or_default() {
+            // `SSL_CERT_FILE` and `SSL_CERT_DIR` take precedence over the platform store.
+            // The certificates which can't be loaded or parsed are skipped.
+            root_store.add_parsable_certificates(rustls_native_certs::load_native_certs().certs);
+        }
+        if self.use_built_in_root_certificates.unwrap_or(self.root_certificates.is_none()) {
+            root_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        }
+        root_store
+    }
+
+    #[cfg(feature = "rustls")]
+    fn new_connector(&self, connect_overrides: Arc<ConnectOverrides>) -> TcpConnector {
+        let tls_config = self.tls_config.clone().unwrap();
+        // The connection to HTTPS proxy uses the same root store and verification settings as the destination, except the asynchronous verification handler.
+        // The proxy speaks HTTP/1.1 for CONNECT and absolute-form requests.
+        let mut proxy_tls_config = self.auxiliary_tls_config(&tls_config);
+        proxy_tls_config.alpn_protocols = vec![b"http/1.1".to_vec()];
 
         let builder = hyper_rustls::HttpsConnectorBuilder::new()
             .with_tls_config(tls_config)
@@ -202,21 +557,95 @@ impl YahaNativeContextInternal<'_> {
             .enable_all_versions();
 
         // Almost the same as `builder.build()`, but specify `set_nodelay(true)`.
-        let mut http_conn = HttpConnector::new();
+        let resolver = self.resolver.clone().unwrap_or_default();
+        let mut http_conn = HttpConnector::new_with_resolver(resolver.clone());
         http_conn.set_nodelay(true);
         http_conn.enforce_http(false);
         http_conn.set_connect_timeout(self.connect_timeout);
-        builder.wrap_connector(http_conn)
+        if let Some(happy_eyeballs_fallback_delay) = self.happy_eyeballs_fallback_delay {
+            http_conn.set_happy_eyeballs_timeout(Some(happy_eyeballs_fallback_delay));
+        }
+        let override_conn = OverrideConnector::new(http_conn, connect_overrides);
+        let srv_conn = SrvConnector::new(override_conn, self.srv_resolver.clone());
+        let proxy_conn = ProxyConnector::new(srv_conn, self.proxy_selector.clone(), self.proxy_authenticator.clone())
+            .with_tls_config(Arc::new(proxy_tls_config))
+            .with_resolver(resolver);
+        // The public key pins are enforced after the standard verification (or the verification handler) trusts the server.
+        // They are checked against the path to one of the root certificates, even if the verification handler trusts the server without it.
+        let pin_store = (!self.public_key_pins.is_empty()).then(|| Arc::new(self.public_key_pins.clone()));
+        let root_store = Arc::new(if pin_store.is_some() { self.root_store() } else { rustls::RootCertStore::empty() });
+        VerifyingConnector::new(builder.wrap_connector(proxy_conn), self.async_certificate_verification.clone(), self.override_server_name.clone())
+            .with_pin_store(pin_store, root_store)
     }
 
     #[cfg(feature = "native")]
-    fn new_connector(&mut self, server_certificate_verification_handler: Option<OnServerCertificateVerificationHandler>) -> HttpsConnector<HttpConnector> {
-        let https = HttpsConnector::new();
-        https
+    fn new_connector(&self, connect_overrides: Arc<ConnectOverrides>) -> TcpConnector {
+        let resolver = self.resolver.clone().unwrap_or_default();
+        let mut http_conn = HttpConnector::new_with_resolver(resolver.clone());
+        http_conn.set_nodelay(true);
+        http_conn.enforce_http(false);
+        http_conn.set_connect_timeout(self.connect_timeout);
+        if let Some(happy_eyeballs_fallback_delay) = self.happy_eyeballs_fallback_delay {
+            http_conn.set_happy_eyeballs_timeout(Some(happy_eyeballs_fallback_delay));
+        }
+        let override_conn = OverrideConnector::new(http_conn, connect_overrides);
+        let srv_conn = SrvConnector::new(override_conn, self.srv_resolver.clone());
+        let proxy_conn = ProxyConnector::new(srv_conn, self.proxy_selector.clone(), self.proxy_authenticator.clone())
+            .with_resolver(resolver);
+        HttpsConnector::new_with_connector(proxy_conn)
+    }
+
+    pub async fn request(&self, mut req: Request<RequestBody>) -> Result<Response<ResponseBody>, RequestError> {
+        if self.cookie_jar.is_some() {
+            let site = SiteForCookies(req.uri().clone());
+            req.extensions_mut().insert(site);
+        }
+        // The response is not decompressed if the application sets `Accept-Encoding` header by itself.
+        let decompress = self.decompression.unwrap_or_default() && decompress::add_accept_encoding(req.headers_mut());
+        let res = match &self.redirect_policy {
+            Some(redirect_policy) => redirect_policy.execute(req, |req| self.request_with_cookies(req)).await?,
+            None => self.request_with_cookies(req).await?,
+        };
+        Ok(if decompress { decompress::decompress(res) } else { res })
+    }
+
+    async fn request_with_cookies(&self, mut req: Request<RequestBody>) -> Result<Response<ResponseBody>, RequestError> {
+        // The cookies are added and stored for each redirect.
+        let cookie_jar = match &self.cookie_jar {
+            Some(cookie_jar) => cookie_jar,
+            None => return self.request_with_cache(req).await,
+        };
+        let uri = req.uri().clone();
+        cookie_jar.add_request_cookies(&mut req);
+        let res = self.request_with_cache(req).await?;
+        cookie_jar.store_response_cookies(&uri, &res);
+        Ok(res)
+    }
+
+    async fn request_with_cache(&self, req: Request<RequestBody>) -> Result<Response<ResponseBody>, RequestError> {
+        match &self.cache {
+            Some(cache) => cache.execute(req, |req| self.request_with_hedging(req)).await,
+            None => Ok(self.request_with_hedging(req).await?.map(to_response_body)),
+        }
+    }
+
+    async fn request_with_hedging(&self, req: Request<RequestBody>) -> Result<Response<Incoming>, RequestError> {
+        // Each hedged request is retried independently.
+        match &self.hedging_policy {
+            Some(hedging_policy) => hedging_policy.execute(req, |req| self.request_with_retry(req)).await,
+            None => self.request_with_retry(req).await,
+        }
+    }
+
+    async fn request_with_retry(&self, req: Request<RequestBody>) -> Result<Response<Incoming>, RequestError> {
+        match &self.retry_policy {
+            Some(retry_policy) => retry_policy.execute(req, |req| self.send_attempt(req)).await,
+            None => self.send_attempt(req).await,
+        }
     }
 
     #[cfg(unix)]
-    pub fn request(&self, mut req: Request<BoxBody<Bytes, hyper::Error>>) -> ResponseFuture {
+    async fn send_attempt(&self, mut req: Request<RequestBody>) -> Result<Response<Incoming>, RequestError> {
         // Precondition (`uds_client` or `tcp_client` is set) ensured by `Self::build_client` and `yaha_request_begin`
         if let Some(uds_socket_path) = &self.uds_socket_path {
             // Transform HTTP URIs to the format expected by hyperlocal
@@ -228,33 +657,191 @@ impl YahaNativeContextInternal<'_> {
             let uds_uri = hyperlocal::Uri::new(uds_socket_path, path_and_query);
             *req.uri_mut() = uds_uri.into();
 
-            self.uds_client.as_ref().unwrap().request(req)
+            Ok(self.uds_client.as_ref().unwrap().request(req).await?)
         } else {
-            self.tcp_client.as_ref().unwrap().request(req)
+            self.request_tcp(req).await
         }
     }
     #[cfg(not(unix))]
-    pub fn request(&self, req: Request<BoxBody<Bytes, hyper::Error>>) -> ResponseFuture {
-        self.tcp_client.as_ref().unwrap().request(req)
+    async fn send_attempt(&self, req: Request<RequestBody>) -> Result<Response<Incoming>, RequestError> {
+        self.request_tcp(req).await
+    }
+
+    // Returns the host and port if the request to the URI is load balanced.
+    // The requests through proxies, to IP addresses, and to the hosts with the static overrides are not load balanced.
+    fn balanced_host_port(&self, uri: &Uri) -> Option<(String, u16)> {
+        self.load_balancer.as_ref()?;
+        let port = match uri.scheme() {
+            Some(scheme) if scheme == &Scheme::HTTPS => uri.port_u16().unwrap_or(443),
+            Some(scheme) if scheme == &Scheme::HTTP => uri.port_u16().unwrap_or(80),
+            _ => return None,
+        };
+        let host = uri.host()?;
+        if host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>().is_ok()
+            || self.connect_overrides.contains(host, port)
+            || (self.srv_resolver.is_some() && SrvResolver::is_service_name(host))
+        {
+            return None;
+        }
+        if let Some(selector) = &self.proxy_selector {
+            if !matches!(selector.select(uri), Ok(None)) {
+                return None;
+            }
+        }
+        Some((host.to_string(), port))
+    }
+
+    async fn request_tcp(&self, req: Request<RequestBody>) -> Result<Response<Incoming>, RequestError> {
+        if let (Some(load_balancer), Some((host, port))) = (&self.load_balancer, self.balanced_host_port(req.uri())) {
+            let new_client = |address: IpAddr| {
+                // The client for the endpoint connects to the address, and keeps the pool separated from the other endpoints.
+                let mut connect_overrides = self.connect_overrides.clone();
+                connect_overrides.pin_address(&host, port, SocketAddr::new(address, port));
+                let connector = self.new_connector(Arc::new(connect_overrides));
+                self.endpoint_client_builder.as_ref().unwrap().build(connector)
+            };
+            // The endpoints with the open circuit are ejected from the load balancing.
+            let circuit_key = circuit_key(req.uri());
+            let endpoint_circuit_key = |address: IpAddr| format!("{} ({})", circuit_key, address);
+            let is_available = |address: IpAddr| match &self.circuit_breaker {
+                Some(circuit_breaker) => circuit_breaker.is_available(&endpoint_circuit_key(address)),
+                None => true,
+            };
+            // If the host can't be resolved, fall back to the default client to report the error.
+            let used_endpoints = req.extensions().get::<UsedEndpoints>().cloned();
+            match load_balancer.select(&host, port, self.resolver.as_ref().unwrap(), new_client, is_available, used_endpoints.as_ref()).await {
+                Ok(Some((endpoint, guard))) => {
+                    let permit = self.acquire_circuit(&endpoint_circuit_key(endpoint.address))?;
+                    let mut res = record_outcome(permit, endpoint.client.request(req).await)?;
+                    // The request is outstanding until the response (and the body) is dropped.
+                    res.extensions_mut().insert(Arc::new(guard));
+                    return Ok(res);
+                }
+                Ok(None) => {
+                    return Err(RequestError::CircuitBreakerOpen(format!("All endpoints of '{}' are ejected by the circuit breaker.", circuit_key)));
+                }
+                Err(_) => {}
+            }
+        }
+
+        let permit = self.acquire_circuit(&circuit_key(req.uri()))?;
+        Ok(record_outcome(permit, self.send_tcp(req).await)?)
+    }
+
+    fn acquire_circuit(&self, key: &str) -> Result<Option<CircuitPermit<'_>>, RequestError> {
+        match &self.circuit_breaker {
+            Some(circuit_breaker) => circuit_breaker.try_acquire(key).map(Some).map_err(RequestError::CircuitBreakerOpen),
+            None => Ok(None),
+        }
+    }
+
+    async fn send_tcp(&self, mut req: Request<RequestBody>) -> Result<Response<Incoming>, client::legacy::Error> {
+        let client = self.tcp_client.as_ref().unwrap();
+
+        // Plain HTTP requests are forwarded by the proxy, so the credentials must be sent with each request.
+        // HTTPS requests are tunneled and the credentials are sent in CONNECT request by `ProxyConnector`.
+        let proxy = match &self.proxy_selector {
+            Some(selector) if req.uri().scheme() == Some(&Scheme::HTTP) => selector.select(req.uri()).ok().flatten(),
+            _ => None,
+        };
+        let proxy = proxy.filter(|proxy| proxy.is_http());
+        let proxy = match proxy {
+            Some(proxy) => proxy,
+            None => return client.request(req).await,
+        };
+
+        if !req.headers().contains_key(PROXY_AUTHORIZATION) {
+            if let Some(authorization) = self.proxy_authenticator.preemptive(&proxy) {
+                req.headers_mut().insert(PROXY_AUTHORIZATION, authorization);
+            }
+        }
+
+        // A request without body can be sent again after the authentication challenge.
+        let replay = req.body().is_end_stream().then(|| {
+            (req.method().clone(), req.uri().clone(), req.version(), req.headers().clone())
+        });
+
+        let res = client.request(req).await?;
+        if res.status() != StatusCode::PROXY_AUTHENTICATION_REQUIRED {
+            return Ok(res);
+        }
+        let (method, uri, version, headers) = match replay {
+            Some(replay) => replay,
+            None => return Ok(res),
+        };
+        let retry_authorization = self.proxy_authenticator
+            .respond(res.headers().get_all(PROXY_AUTHENTICATE).iter().map(|v| v.as_bytes()))
+            .filter(|a| headers.get(PROXY_AUTHORIZATION) != Some(a));
+        let retry_authorization = match retry_authorization {
+            Some(authorization) => authorization,
+            None => return Ok(res),
+        };
+        drop(res);
+
+        let mut retry_req = Request::builder()
+            .method(method)
+            .uri(uri)
+            .version(version)
+            .body(BoxBody::new(Empty::<Bytes>::new().map_err(|never| match never {})))
+            .unwrap();
+        *retry_req.headers_mut() = headers;
+        retry_req.headers_mut().insert(PROXY_AUTHORIZATION, retry_authorization.clone());
+
+        let res = client.request(retry_req).await?;
+        if res.status() != StatusCode::PROXY_AUTHENTICATION_REQUIRED {
+            self.proxy_authenticator.accept(&proxy, retry_authorization);
+        }
+        Ok(res)
     }
 }
 
+// The circuits are separated by the scheme, host and port of the request.
+fn circuit_key(uri: &Uri) -> String {
+    let scheme = uri.scheme_str().unwrap_or("http");
+    let port = uri.port_u16().unwrap_or(if scheme == "https" { 443 } else { 80 });
+    format!("{}://{}:{}", scheme, uri.host().unwrap_or_default().to_ascii_lowercase(), port)
+}
+
+fn record_outcome(permit: Option<CircuitPermit<'_>>, result: Result<Response<Incoming>, client::legacy::Error>) -> Result<Response<Incoming>, client::legacy::Error> {
+    if let Some(permit) = permit {
+        permit.record(result.as_ref().map_or(true, |res| circuit_breaker::is_failure_status(res.status())));
+    }
+    result
+}
+
 #[cfg(feature = "rustls")]
 mod danger {
-    use std::num::NonZeroIsize;
+    use std::{num::NonZeroIsize, sync::Arc};
 
     use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified};
+    use rustls::client::WebPkiServerVerifier;
+    use rustls::crypto::WebPkiSupportedAlgorithms;
     use rustls::{DigitallySignedStruct, Error, SignatureScheme};
     use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
 
-    use super::OnServerCertificateVerificationHandler;
+    use super::{OnServerCertificateVerificationHandler, StringBuffer};
 
     #[derive(Debug)]
     pub struct NoCertificateVerification {}
 
+    /// Passes the presented chain, the stapled OCSP response and the result of the standard verification to the handler.
+    /// The handler decides whether to trust the server, and the handshake signatures are always verified.
     #[derive(Debug)]
     pub struct CustomCerficateVerification {
-        pub handler: (OnServerCertificateVerificationHandler, NonZeroIsize)
+        handler: (OnServerCertificateVerificationHandler, NonZeroIsize),
+        // The verifier fails to build if there are no valid root certificates.
+        default_verifier: Result<Arc<WebPkiServerVerifier>, String>,
+        supported_algorithms: WebPkiSupportedAlgorithms,
+    }
+
+    impl CustomCerficateVerification {
+        pub fn new(handler: (OnServerCertificateVerificationHandler, NonZeroIsize), default_verifier: Result<Arc<WebPkiServerVerifier>, String>) -> Self {
+            CustomCerficateVerification {
+                handler,
+                default_verifier,
+                supported_algorithms: rustls::crypto::ring::default_provider().signature_verification_algorithms,
+            }
+        }
     }
 
     const ALL_SCHEMES: [SignatureScheme; 12] = [
@@ -275,16 +862,38 @@ mod danger {
         fn verify_server_cert(
             &self,
             end_entity: &CertificateDer<'_>,
-            _intermediates: &[CertificateDer<'_>],
+            intermediates: &[CertificateDer<'_>],
             server_name: &ServerName<'_>,
-            _ocsp_response: &[u8],
+            ocsp_response: &[u8],
             now: UnixTime,
         ) -> Result<ServerCertVerified, Error> {
+            let default_verification_error = match &self.default_verifier {
+                Ok(verifier) => match verifier.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now) {
+                    Ok(_) => String::new(),
+                    Err(err) => err.to_string(),
+                },
+                Err(err) => err.clone(),
+            };
+
             let server_name = server_name.to_str();
             let server_name = server_name.as_bytes();
-            let cetificate_der = end_entity.as_ref();
-
-            if (self.handler.0)(self.handler.1, server_name.as_ptr(), server_name.len(), cetificate_der.as_ptr(), cetificate_der.len(), now.as_secs()) {
+            let certificates = std::iter::once(end_entity)
+                .chain(intermediates)
+                .map(|cert| StringBuffer::new(cert.as_ptr(), cert.len() as i32))
+                .collect::<Vec<_>>();
+
+            if (self.handler.0)(
+                self.handler.1,
+                server_name.as_ptr(),
+                server_name.len(),
+                certificates.as_ptr(),
+                certificates.len(),
+                ocsp_response.as_ptr(),
+                ocsp_response.len(),
+                default_verification_error.as_ptr(),
+                default_verification_error.len(),
+                now.as_secs(),
+            ) {
                 Ok(ServerCertVerified::assertion())
             } else {
                 Err(Error::InvalidCertificate(rustls::CertificateError::ApplicationVerificationFailure))
@@ -293,24 +902,24 @@ mod danger {
 
         fn verify_tls12_signature(
             &self,
-            _message: &[u8],
-            _cert: &CertificateDer<'_>,
-            _dss: &DigitallySignedStruct,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
         ) -> Result<HandshakeSignatureValid, Error> {
-            Ok(HandshakeSignatureValid::assertion())
+            rustls::crypto::verify_tls12_signature(message, cert, dss, &self.supported_algorithms)
         }
 
         fn verify_tls13_signature(
             &self,
-            _message: &[u8],
-            _cert: &CertificateDer<'_>,
-            _dss: &DigitallySignedStruct,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
         ) -> Result<HandshakeSignatureValid, Error> {
-            Ok(HandshakeSignatureValid::assertion())
+            rustls::crypto::verify_tls13_signature(message, cert, dss, &self.supported_algorithms)
         }
 
         fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
-            Vec::from(ALL_SCHEMES)
+            self.supported_algorithms.supported_schemes()
         }
     }
 
@@ -355,6 +964,8 @@ pub struct YahaNativeRequestContextInternal {
     pub builder: Option<hyper::http::request::Builder>,
     pub sender: Option<Sender<Bytes>>,
     pub has_body: bool,
+    pub body_compression: Option<YahaRequestBodyCompression>,
+    pub body_compression_level: Option<i32>,
     pub completed: bool,
     pub cancellation_token: CancellationToken,
     pub last_error: Option<String>,
@@ -362,6 +973,8 @@ pub struct YahaNativeRequestContextInternal {
     pub response_version: YahaHttpVersion,
     pub response_status: StatusCode,
     pub response_headers: Option<Vec<(String, String)>>,
+    // The URI of the response if the request was redirected.
+    pub response_uri: Option<String>,
     pub response_trailers: Option<Vec<(String, String)>>,
 }
 
@@ -386,6 +999,6 @@ impl Internalizable<Mutex<YahaNativeRequestContextInternal>> for YahaNativeReque
 pub fn to_internal<'a, T: Internalizable<U>, U>(v: *const T) -> &'a U {
     unsafe { &(*(v as *const U)) }
 }
-pub fn to_internal_arc<'a, T: Internalizable<U>, U>(v: *const T) -> Arc<U> {
+pub fn to_internal_arc<T: Internalizable<U>, U>(v: *const T) -> Arc<U> {
     unsafe { Arc::from_raw(v as *const U) }
 }
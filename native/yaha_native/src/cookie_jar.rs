@@ -0,0 +1,307 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use cookie::SameSite;
+use cookie_store::{CookieDomain, CookieExpiration, CookieStore, RawCookie};
+use hyper::{
+    header::{self, HeaderValue},
+    Method, Request, Response, Uri,
+};
+use publicsuffix::{List, Psl};
+use time::OffsetDateTime;
+use url::Url;
+
+use crate::primitives::YahaCookieFileFormat;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The URI of the request sent by the application. It is inserted into the extensions of the request, and kept while following the redirects.
+/// The `SameSite` cookies are sent according to whether the request is same-site with it.
+#[derive(Clone)]
+pub struct SiteForCookies(pub Uri);
+
+/// The file to persist the cookies.
+pub struct CookieFile {
+    path: PathBuf,
+    format: YahaCookieFileFormat,
+    // The generation of the last snapshot and the last written one. The older snapshot is not written over the newer one.
+    generation: AtomicU64,
+    written: Mutex<u64>,
+}
+
+impl CookieFile {
+    pub fn new(path: PathBuf, format: YahaCookieFileFormat) -> Self {
+        CookieFile {
+            path,
+            format,
+            generation: AtomicU64::new(0),
+            written: Mutex::new(0),
+        }
+    }
+
+    // Loads the cookies from the file. The empty store is returned if the file doesn't exist.
+    pub fn load(&self) -> Result<CookieStore, BoxError> {
+        let file = match File::open(&self.path) {
+            Ok(file) => BufReader::new(file),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(CookieStore::default()),
+            Err(err) => return Err(err.into()),
+        };
+        match self.format {
+            YahaCookieFileFormat::Json => cookie_store::serde::json::load(file),
+            YahaCookieFileFormat::Netscape => load_netscape(file),
+        }
+    }
+
+    // Serializes the persistent cookies in the store. The session cookies are not saved.
+    fn snapshot(&self, store: &CookieStore) -> Option<(u64, Vec<u8>)> {
+        let mut buf = Vec::new();
+        match self.format {
+            YahaCookieFileFormat::Json => cookie_store::serde::json::save(store, &mut buf).ok()?,
+            YahaCookieFileFormat::Netscape => save_netscape(store, &mut buf).ok()?,
+        }
+        Some((self.generation.fetch_add(1, Ordering::SeqCst) + 1, buf))
+    }
+
+    fn write(&self, generation: u64, content: &[u8]) -> io::Result<()> {
+        let mut written = self.written.lock().unwrap();
+        if *written >= generation {
+            return Ok(());
+        }
+        // Write to the temporary file and replace the file with it, so that the file is not left half-written.
+        let mut tmp = OsString::from(self.path.as_os_str());
+        tmp.push(".tmp");
+        fs::write(&tmp, content)?;
+        fs::rename(&tmp, &self.path)?;
+        *written = generation;
+        Ok(())
+    }
+}
+
+/// The cookie store shared by the requests of the client.
+///
+/// It follows the storage model and the domain and path matching of RFC 6265. `Secure` cookies are sent over HTTPS only.
+/// `SameSite=Strict` cookies are sent to the same site as the request sent by the application only,
+/// and `SameSite=Lax` cookies are also sent with the cross-site requests of the safe methods (e.g. redirected `GET` requests).
+/// Without a public suffix list, only the `Domain` attributes with a single label (e.g. `com`) are rejected as a public suffix.
+pub struct CookieJar {
+    store: Mutex<CookieStore>,
+    public_suffix_list: Option<List>,
+    file: Option<Arc<CookieFile>>,
+}
+
+impl CookieJar {
+    pub fn new(store: Option<CookieStore>, public_suffix_list: Option<List>, file: Option<CookieFile>) -> Self {
+        let store = match (store, &public_suffix_list) {
+            (Some(store), Some(list)) => store.with_suffix_list(list.clone()),
+            (Some(store), None) => store,
+            (None, list) => CookieStore::new(list.clone()),
+        };
+        CookieJar {
+            store: Mutex::new(store),
+            public_suffix_list,
+            file: file.map(Arc::new),
+        }
+    }
+
+    // Adds the cookies for the request to the `Cookie` header. The cookies are appended to the header set by the application.
+    pub fn add_request_cookies<B>(&self, req: &mut Request<B>) {
+        let url = match to_url(req.uri()) {
+            Some(url) => url,
+            None => return,
+        };
+        let same_site = match req.extensions().get::<SiteForCookies>().and_then(|site| to_url(&site.0)) {
+            Some(site) => self.is_same_site(&site, &url),
+            None => true,
+        };
+        let safe_method = matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE);
+
+        let cookies = {
+            let store = self.store.lock().unwrap();
+            let mut cookies = store.matches(&url);
+            // RFC 6265 5.4: The cookies with longer paths are listed first. The others are in the order of creation.
+            cookies.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+            cookies
+                .into_iter()
+                .filter(|cookie| match cookie.same_site() {
+                    Some(SameSite::Strict) => same_site,
+                    Some(SameSite::Lax) => same_site || safe_method,
+                    _ => true,
+                })
+                .map(|cookie| format!("{}={}", cookie.name(), cookie.value()))
+                .collect::<Vec<_>>()
+                .join("; ")
+        };
+        if cookies.is_empty() {
+            return;
+        }
+
+        let value = match req.headers().get(header::COOKIE).and_then(|value| value.to_str().ok()) {
+            Some(current) if !current.is_empty() => format!("{current}; {cookies}"),
+            _ => cookies,
+        };
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            req.headers_mut().insert(header::COOKIE, value);
+        }
+    }
+
+    // Stores the cookies of the `Set-Cookie` headers in the response, and saves them to the file if changed.
+    pub fn store_response_cookies<B>(&self, uri: &Uri, res: &Response<B>) {
+        let url = match to_url(uri) {
+            Some(url) => url,
+            None => return,
+        };
+        let cookies = res
+            .headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .filter_map(|value| std::str::from_utf8(value.as_bytes()).ok())
+            .filter_map(|value| RawCookie::parse(value.to_owned()).ok())
+            .filter(|cookie| self.is_acceptable(cookie, &url))
+            .collect::<Vec<_>>();
+        if cookies.is_empty() {
+            return;
+        }
+
+        let snapshot = {
+            let mut store = self.store.lock().unwrap();
+            let mut changed = false;
+            for cookie in &cookies {
+                changed |= store.insert_raw(cookie, &url).is_ok();
+            }
+            match &self.file {
+                Some(file) if changed => file.snapshot(&store).map(|snapshot| (file.clone(), snapshot)),
+                _ => None,
+            }
+        };
+        if let Some((file, (generation, content))) = snapshot {
+            tokio::task::spawn_blocking(move || {
+                // The failure to save is ignored. The cookies are kept in memory and saved on the next change.
+                let _ = file.write(generation, &content);
+            });
+        }
+    }
+
+    // The checks not covered by `CookieStore`. (the `Secure` attribute from HTTP, the cookie name prefixes, and the public suffixes without the list)
+    fn is_acceptable(&self, cookie: &RawCookie<'_>, url: &Url) -> bool {
+        let secure = cookie.secure().unwrap_or(false);
+        if secure && url.scheme() != "https" {
+            return false;
+        }
+        if cookie.name().starts_with("__Secure-") && !secure {
+            return false;
+        }
+        if cookie.name().starts_with("__Host-") && (!secure || cookie.domain().is_some() || cookie.path() != Some("/")) {
+            return false;
+        }
+        if self.public_suffix_list.is_none() {
+            if let (Some(domain), Some(host)) = (cookie.domain(), url.host_str()) {
+                let domain = domain.trim_start_matches('.');
+                if !domain.contains('.') && !domain.eq_ignore_ascii_case(host) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    // Schemeful same-site. The sites are compared by the registrable domains if the public suffix list is given, otherwise by the hosts.
+    fn is_same_site(&self, a: &Url, b: &Url) -> bool {
+        a.scheme() == b.scheme()
+            && match (a.host_str(), b.host_str()) {
+                (Some(a), Some(b)) => self.site(a) == self.site(b),
+                _ => false,
+            }
+    }
+
+    fn site(&self, host: &str) -> String {
+        let host = host.to_ascii_lowercase();
+        self.public_suffix_list
+            .as_ref()
+            .and_then(|list| list.domain(host.as_bytes()))
+            .and_then(|domain| std::str::from_utf8(domain.as_bytes()).ok().map(str::to_owned))
+            .unwrap_or(host)
+    }
+}
+
+fn to_url(uri: &Uri) -> Option<Url> {
+    Url::parse(&uri.to_string()).ok()
+}
+
+const NETSCAPE_HEADER: &str = "# Netscape HTTP Cookie File";
+const NETSCAPE_HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+
+// Loads the cookies from the Netscape cookie file used by curl and wget.
+// Each line is `domain`, `include subdomains`, `path`, `secure`, `expires`, `name` and `value` separated by tabs.
+fn load_netscape<R: BufRead>(reader: R) -> Result<CookieStore, BoxError> {
+    let mut store = CookieStore::default();
+    for line in reader.lines() {
+        let line = line?;
+        let (line, http_only) = match line.strip_prefix(NETSCAPE_HTTP_ONLY_PREFIX) {
+            Some(line) => (line, true),
+            None => (line.as_str(), false),
+        };
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields = line.split('\t').collect::<Vec<_>>();
+        let [domain, include_subdomains, path, secure, expires, name, value] = fields[..] else {
+            return Err(format!("Invalid line in the cookie file: {line}").into());
+        };
+        let host = domain.trim_start_matches('.');
+        let secure = secure.eq_ignore_ascii_case("TRUE");
+        let expires = expires.parse::<i64>()?;
+
+        let mut cookie = RawCookie::build((name.to_owned(), value.to_owned()))
+            .path(path.to_owned())
+            .secure(secure)
+            .http_only(http_only);
+        if include_subdomains.eq_ignore_ascii_case("TRUE") {
+            cookie = cookie.domain(host.to_owned());
+        }
+        // `0` is a session cookie.
+        if expires != 0 {
+            cookie = cookie.expires(OffsetDateTime::from_unix_timestamp(expires)?);
+        }
+        let url = Url::parse(&format!("{}://{}{}", if secure { "https" } else { "http" }, host, path))?;
+        // The expired cookies are skipped.
+        let _ = store.insert_raw(&cookie.build(), &url);
+    }
+    Ok(store)
+}
+
+fn save_netscape<W: Write>(store: &CookieStore, writer: &mut W) -> io::Result<()> {
+    writeln!(writer, "{NETSCAPE_HEADER}")?;
+    for cookie in store.iter_unexpired() {
+        let expires = match &cookie.expires {
+            CookieExpiration::AtUtc(expires) => expires.unix_timestamp(),
+            CookieExpiration::SessionEnd => continue,
+        };
+        let (domain, include_subdomains) = match &cookie.domain {
+            CookieDomain::HostOnly(host) => (host.clone(), "FALSE"),
+            CookieDomain::Suffix(suffix) => (format!(".{suffix}"), "TRUE"),
+            _ => continue,
+        };
+        writeln!(
+            writer,
+            "{}{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            if cookie.http_only().unwrap_or(false) { NETSCAPE_HTTP_ONLY_PREFIX } else { "" },
+            domain,
+            include_subdomains,
+            &*cookie.path,
+            if cookie.secure().unwrap_or(false) { "TRUE" } else { "FALSE" },
+            expires,
+            cookie.name(),
+            cookie.value(),
+        )?;
+    }
+    Ok(())
+}
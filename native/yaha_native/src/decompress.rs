@@ -0,0 +1,84 @@
+use std::io;
+
+use async_compression::tokio::bufread::{BrotliDecoder, GzipDecoder, ZlibDecoder, ZstdDecoder};
+use futures_util::{stream, TryStreamExt};
+use http_body_util::{combinators::BoxBody, BodyExt, StreamBody};
+use hyper::{
+    body::{Body, Frame},
+    header::{self, HeaderValue},
+    HeaderMap, Response,
+};
+use tokio::io::AsyncRead;
+use tokio_util::io::{ReaderStream, StreamReader};
+
+use crate::context::ResponseBody;
+
+// The content codings sent in `Accept-Encoding` header.
+const ACCEPT_ENCODING: &str = "gzip, deflate, br, zstd";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ContentCoding {
+    Gzip,
+    Deflate,
+    Brotli,
+    Zstd,
+}
+
+// Sets `Accept-Encoding` header if the application doesn't set it. Returns false if the application handles the encoding by itself.
+pub fn add_accept_encoding(headers: &mut HeaderMap) -> bool {
+    if headers.contains_key(header::ACCEPT_ENCODING) {
+        return false;
+    }
+    headers.insert(header::ACCEPT_ENCODING, HeaderValue::from_static(ACCEPT_ENCODING));
+    true
+}
+
+// Decompresses the response body with `Content-Encoding` header, and removes `Content-Encoding` and `Content-Length` headers.
+// The response is returned as is if the content coding is not supported (e.g. multiple codings) or the body is empty. (e.g. `HEAD`)
+pub fn decompress(res: Response<ResponseBody>) -> Response<ResponseBody> {
+    let coding = match res.headers().get(header::CONTENT_ENCODING).and_then(|value| value.to_str().ok()) {
+        Some(value) => match value.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => ContentCoding::Gzip,
+            "deflate" => ContentCoding::Deflate,
+            "br" => ContentCoding::Brotli,
+            "zstd" => ContentCoding::Zstd,
+            _ => return res,
+        },
+        None => return res,
+    };
+    if res.body().is_end_stream() {
+        return res;
+    }
+
+    let (mut parts, body) = res.into_parts();
+    parts.headers.remove(header::CONTENT_ENCODING);
+    parts.headers.remove(header::CONTENT_LENGTH);
+
+    // The trailers are dropped.
+    let data = stream::try_unfold(body, |mut body| async move {
+        loop {
+            match body.frame().await {
+                Some(Ok(frame)) => {
+                    if let Ok(data) = frame.into_data() {
+                        return Ok(Some((data, body)));
+                    }
+                }
+                Some(Err(err)) => return Err(io::Error::other(err)),
+                None => return Ok(None),
+            }
+        }
+    });
+    let reader = StreamReader::new(Box::pin(data));
+    let decoded = match coding {
+        ContentCoding::Gzip => to_body(GzipDecoder::new(reader)),
+        ContentCoding::Deflate => to_body(ZlibDecoder::new(reader)),
+        ContentCoding::Brotli => to_body(BrotliDecoder::new(reader)),
+        ContentCoding::Zstd => to_body(ZstdDecoder::new(reader)),
+    };
+    Response::from_parts(parts, decoded)
+}
+
+fn to_body<R: AsyncRead + Send + Sync + 'static>(reader: R) -> ResponseBody {
+    let frames = ReaderStream::new(reader).map_ok(Frame::data).map_err(Into::into);
+    BoxBody::new(StreamBody::new(frames))
+}
@@ -0,0 +1,391 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    io,
+    net::{IpAddr, SocketAddr},
+    num::NonZeroIsize,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use hickory_resolver::{
+    config::{LookupIpStrategy, NameServerConfig, Protocol, ResolverConfig, ResolverOpts},
+    error::ResolveErrorKind,
+    TokioAsyncResolver,
+};
+use hyper_util::client::legacy::connect::dns::{GaiResolver, Name};
+use tokio::sync::oneshot;
+use tower_service::Service;
+
+use crate::{context::OnResolveHandler, primitives::YahaAddressFamilyPreference};
+#[cfg(feature = "rustls")]
+use crate::doh::DohResolver;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+// The sender to complete the resolution started by the resolver callback. It is passed as `task_handle`.
+pub type ResolveSender = oneshot::Sender<Result<Vec<IpAddr>, String>>;
+
+// The maximum number of host names in the DNS cache.
+const MAX_DNS_CACHE_ENTRIES: usize = 1024;
+
+// How long to wait for the resolver callback if the connect timeout is not set.
+pub const DEFAULT_RESOLVE_CALLBACK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where the host names are resolved.
+#[derive(Clone)]
+pub enum ResolveSource {
+    // getaddrinfo on the blocking thread pool.
+    System(GaiResolver),
+    // The resolver callback provided by the managed side, and how long to wait for its completion.
+    Callback((OnResolveHandler, NonZeroIsize), Duration),
+    // The in-process async stub resolver. It provides TTL and negative answers for the cache.
+    BuiltIn(Box<TokioAsyncResolver>),
+    // DNS-over-HTTPS to the configured endpoint.
+    #[cfg(feature = "rustls")]
+    DnsOverHttps(Arc<DohResolver>),
+}
+
+impl Default for ResolveSource {
+    fn default() -> Self {
+        ResolveSource::System(GaiResolver::new())
+    }
+}
+
+// The result of a lookup. `ttl` is `None` if the source doesn't know it.
+pub struct Lookup {
+    pub addrs: Vec<IpAddr>,
+    pub ttl: Option<Duration>,
+}
+
+pub enum LookupError {
+    // The name doesn't exist or has no addresses. (NXDOMAIN or NODATA)
+    NotFound { message: String, negative_ttl: Option<Duration> },
+    Other(BoxError),
+}
+
+impl From<LookupError> for BoxError {
+    fn from(err: LookupError) -> Self {
+        match err {
+            LookupError::NotFound { message, .. } => not_found(&message),
+            LookupError::Other(err) => err,
+        }
+    }
+}
+
+impl ResolveSource {
+    // Creates the built-in resolver with `dns_servers`, or the system DNS configuration (e.g. `/etc/resolv.conf`) if it is empty.
+    // Returns `None` if the configuration is not available. (e.g. Android)
+    pub fn built_in(dns_servers: &[SocketAddr]) -> Option<ResolveSource> {
+        let (config, mut opts) = built_in_config(dns_servers)?;
+        Some(Self::built_in_with_config(config, &mut opts))
+    }
+
+    pub fn built_in_with_config(config: ResolverConfig, opts: &mut ResolverOpts) -> ResolveSource {
+        // Query both A and AAAA so that the connector can fall back to the other family.
+        opts.ip_strategy = LookupIpStrategy::Ipv4AndIpv6;
+        ResolveSource::BuiltIn(Box::new(TokioAsyncResolver::tokio(config, opts.clone())))
+    }
+
+    async fn lookup(&self, host: &str) -> Result<Lookup, LookupError> {
+        match self {
+            ResolveSource::System(gai) => {
+                let name = host.parse::<Name>().map_err(|e| LookupError::Other(e.into()))?;
+                let addrs = gai.clone().call(name).await.map_err(|e| LookupError::Other(e.into()))?;
+                Ok(Lookup { addrs: addrs.map(|addr| addr.ip()).collect(), ttl: None })
+            }
+            ResolveSource::Callback((handler, state), timeout) => {
+                let (tx, rx) = oneshot::channel::<Result<Vec<IpAddr>, String>>();
+                let tx = Box::into_raw(Box::new(tx)) as usize;
+
+                // The callback must complete the task with `yaha_complete_resolve`.
+                // If it completes after the timeout, the result is discarded and the sender is freed then.
+                handler(*state, host.as_ptr(), host.len(), tx);
+
+                let addrs = tokio::time::timeout(*timeout, rx)
+                    .await
+                    .map_err(|_| LookupError::Other(io::Error::new(io::ErrorKind::TimedOut, format!("the resolver callback didn't complete in {:?}", timeout)).into()))?
+                    .map_err(|_| LookupError::Other(io::Error::other("the resolver callback was dropped without completion").into()))?
+                    .map_err(|err| LookupError::Other(io::Error::other(format!("failed to resolve the host '{}': {}", host, err)).into()))?;
+                if addrs.is_empty() {
+                    return Err(LookupError::NotFound {
+                        message: format!("the resolver callback returned no addresses for the host '{}'", host),
+                        negative_ttl: None,
+                    });
+                }
+                Ok(Lookup { addrs, ttl: None })
+            }
+            ResolveSource::BuiltIn(resolver) => match resolver.lookup_ip(host).await {
+                Ok(lookup) => Ok(Lookup {
+                    addrs: lookup.iter().collect(),
+                    ttl: Some(lookup.valid_until().saturating_duration_since(Instant::now())),
+                }),
+                Err(err) => match err.kind() {
+                    ResolveErrorKind::NoRecordsFound { negative_ttl, .. } => Err(LookupError::NotFound {
+                        message: format!("failed to resolve the host '{}': {}", host, err),
+                        negative_ttl: negative_ttl.map(|ttl| Duration::from_secs(ttl as u64)),
+                    }),
+                    _ => Err(LookupError::Other(err.into())),
+                },
+            },
+            #[cfg(feature = "rustls")]
+            ResolveSource::DnsOverHttps(resolver) => resolver.lookup(host).await,
+        }
+    }
+
+    fn clear_cache(&self) {
+        if let ResolveSource::BuiltIn(resolver) = self {
+            resolver.clear_cache();
+        }
+    }
+}
+
+// Returns the configuration of the built-in resolver. The servers are queried over UDP, and over TCP for truncated responses.
+pub fn built_in_config(dns_servers: &[SocketAddr]) -> Option<(ResolverConfig, ResolverOpts)> {
+    if dns_servers.is_empty() {
+        return hickory_resolver::system_conf::read_system_conf().ok();
+    }
+    let name_servers = dns_servers
+        .iter()
+        .flat_map(|&address| [NameServerConfig::new(address, Protocol::Udp), NameServerConfig::new(address, Protocol::Tcp)])
+        .collect::<Vec<_>>();
+    Some((ResolverConfig::from_parts(None, vec![], name_servers), ResolverOpts::default()))
+}
+
+#[derive(Clone, Debug)]
+pub struct DnsCacheOptions {
+    // The TTL for the sources that don't provide TTL. (getaddrinfo and the resolver callback)
+    pub default_ttl: Duration,
+    // The upper bound of TTL.
+    pub max_ttl: Duration,
+    // The TTL of negative answers if the DNS server doesn't provide it.
+    pub negative_ttl: Duration,
+    // How long an expired entry can be served while it is refreshed in background.
+    pub stale_while_revalidate: Duration,
+}
+
+impl Default for DnsCacheOptions {
+    fn default() -> Self {
+        DnsCacheOptions {
+            default_ttl: Duration::from_secs(60),
+            max_ttl: Duration::from_secs(60 * 60),
+            negative_ttl: Duration::from_secs(10),
+            stale_while_revalidate: Duration::ZERO,
+        }
+    }
+}
+
+struct DnsCacheEntry {
+    // `Err` is a negative answer.
+    result: Result<Arc<Vec<IpAddr>>, String>,
+    expires_at: Instant,
+    refreshing: bool,
+}
+
+/// A cache of resolved addresses shared by the connections of the client.
+pub struct DnsCache {
+    options: DnsCacheOptions,
+    entries: Mutex<HashMap<String, DnsCacheEntry>>,
+}
+
+impl DnsCache {
+    pub fn new(options: DnsCacheOptions) -> Self {
+        DnsCache {
+            options,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn flush(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    async fn resolve(self: &Arc<Self>, host: &str, source: &ResolveSource) -> Result<Vec<IpAddr>, BoxError> {
+        let key = host.to_ascii_lowercase();
+        let now = Instant::now();
+        {
+            let mut entries = self.entries.lock().unwrap();
+            if let Some(entry) = entries.get_mut(&key) {
+                if now < entry.expires_at {
+                    return match &entry.result {
+                        Ok(addrs) => Ok(addrs.to_vec()),
+                        Err(message) => Err(not_found(message)),
+                    };
+                }
+                if let Ok(addrs) = &entry.result {
+                    if now < entry.expires_at + self.options.stale_while_revalidate {
+                        // Serve the stale addresses and refresh the entry in background.
+                        if !entry.refreshing {
+                            entry.refreshing = true;
+                            let cache = self.clone();
+                            let source = source.clone();
+                            let host = key.clone();
+                            tokio::spawn(async move {
+                                let result = source.lookup(&host).await;
+                                cache.store(&host, result);
+                            });
+                        }
+                        return Ok(addrs.to_vec());
+                    }
+                }
+            }
+        }
+
+        let result = source.lookup(&key).await;
+        let response = match &result {
+            Ok(lookup) => Ok(lookup.addrs.clone()),
+            Err(LookupError::NotFound { message, .. }) => Err(not_found(message)),
+            Err(LookupError::Other(err)) => Err(io::Error::other(err.to_string()).into()),
+        };
+        self.store(&key, result);
+        response
+    }
+
+    fn store(&self, key: &str, result: Result<Lookup, LookupError>) {
+        let now = Instant::now();
+        let (result, ttl) = match result {
+            Ok(lookup) => {
+                let ttl = lookup.ttl.unwrap_or(self.options.default_ttl).min(self.options.max_ttl);
+                (Ok(Arc::new(lookup.addrs)), ttl)
+            }
+            Err(LookupError::NotFound { message, negative_ttl }) => {
+                let ttl = negative_ttl.unwrap_or(self.options.negative_ttl).min(self.options.max_ttl);
+                (Err(message), ttl)
+            }
+            Err(LookupError::Other(_)) => {
+                // Transient failures (e.g. timeout) are not cached.
+                self.store_failure(key);
+                return;
+            }
+        };
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= MAX_DNS_CACHE_ENTRIES && !entries.contains_key(key) {
+            Self::evict(&mut entries, now);
+        }
+        entries.insert(key.to_string(), DnsCacheEntry { result, expires_at: now + ttl, refreshing: false });
+    }
+
+    // Allows the next request to refresh the stale entry again.
+    fn store_failure(&self, key: &str) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(key) {
+            entry.refreshing = false;
+        }
+    }
+
+    fn evict(entries: &mut HashMap<String, DnsCacheEntry>, now: Instant) {
+        entries.retain(|_, entry| now < entry.expires_at);
+        if entries.len() >= MAX_DNS_CACHE_ENTRIES {
+            // Remove the entry that expires first.
+            let oldest = entries.iter().min_by_key(|(_, entry)| entry.expires_at).map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+fn not_found(message: &str) -> BoxError {
+    io::Error::new(io::ErrorKind::NotFound, message.to_string()).into()
+}
+
+/// Resolves host names for the connector.
+#[derive(Clone, Default)]
+pub struct Resolver {
+    source: ResolveSource,
+    cache: Option<Arc<DnsCache>>,
+    family_preference: YahaAddressFamilyPreference,
+}
+
+impl Resolver {
+    pub fn new(source: ResolveSource, cache: Option<Arc<DnsCache>>) -> Self {
+        Resolver { source, cache, family_preference: YahaAddressFamilyPreference::Default }
+    }
+
+    pub fn with_family_preference(mut self, family_preference: YahaAddressFamilyPreference) -> Self {
+        self.family_preference = family_preference;
+        self
+    }
+
+    pub async fn resolve(&self, host: &str) -> Result<Vec<IpAddr>, BoxError> {
+        let addrs = match &self.cache {
+            Some(cache) => cache.resolve(host, &self.source).await?,
+            None => self.source.lookup(host).await?.addrs,
+        };
+        // The cache keeps all families, so that changing the preference doesn't require flushing it.
+        let addrs = sort_by_family_preference(addrs, self.family_preference);
+        if addrs.is_empty() {
+            return Err(not_found(&format!("no addresses of the preferred family for the host '{}'", host)));
+        }
+        Ok(addrs)
+    }
+
+    // Clears the DNS cache and the cache of the built-in resolver.
+    pub fn flush(&self) {
+        if let Some(cache) = &self.cache {
+            cache.flush();
+        }
+        self.source.clear_cache();
+    }
+}
+
+impl Service<Name> for Resolver {
+    type Response = std::vec::IntoIter<SocketAddr>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        let resolver = self.clone();
+        Box::pin(async move {
+            // The port is set by `HttpConnector`.
+            let addrs = resolver.resolve(name.as_str()).await?;
+            Ok(addrs
+                .into_iter()
+                .map(|ip| SocketAddr::new(ip, 0))
+                .collect::<Vec<_>>()
+                .into_iter())
+        })
+    }
+}
+
+// Filters or reorders the addresses by the family preference.
+// `HttpConnector` tries the family of the first address first, and the other family after the Happy Eyeballs delay.
+fn sort_by_family_preference(addrs: Vec<IpAddr>, family_preference: YahaAddressFamilyPreference) -> Vec<IpAddr> {
+    match family_preference {
+        YahaAddressFamilyPreference::Default => addrs,
+        YahaAddressFamilyPreference::Ipv4Only => addrs.into_iter().filter(IpAddr::is_ipv4).collect(),
+        YahaAddressFamilyPreference::Ipv6Only => addrs.into_iter().filter(IpAddr::is_ipv6).collect(),
+        YahaAddressFamilyPreference::PreferIpv4 => {
+            let (mut preferred, fallback): (Vec<_>, Vec<_>) = addrs.into_iter().partition(IpAddr::is_ipv4);
+            preferred.extend(fallback);
+            preferred
+        }
+        YahaAddressFamilyPreference::PreferIpv6 => {
+            let (mut preferred, fallback): (Vec<_>, Vec<_>) = addrs.into_iter().partition(IpAddr::is_ipv6);
+            preferred.extend(fallback);
+            preferred
+        }
+    }
+}
+
+// Parses the comma-separated addresses passed to `yaha_complete_resolve`.
+pub fn parse_addresses(addresses: &str) -> Result<Vec<IpAddr>, String> {
+    addresses
+        .split(',')
+        .map(str::trim)
+        .filter(|address| !address.is_empty())
+        .map(|address| {
+            address
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse::<IpAddr>()
+                .map_err(|_| format!("invalid IP address: {}", address))
+        })
+        .collect()
+}
@@ -0,0 +1,186 @@
+use std::{
+    io,
+    net::IpAddr,
+    sync::Arc,
+    time::Duration,
+};
+
+use hickory_resolver::proto::{
+    op::{Message, MessageType, OpCode, Query, ResponseCode},
+    rr::{Name, RData, RecordType},
+};
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    body::Bytes,
+    header::{ACCEPT, CONTENT_TYPE},
+    Method, Request, StatusCode, Uri,
+};
+use hyper_rustls::HttpsConnector;
+use hyper_util::{
+    client::legacy::{connect::HttpConnector, Client},
+    rt::{TokioExecutor, TokioTimer},
+};
+
+use crate::{
+    dns::{Lookup, LookupError},
+    overrides::{ConnectOverrides, OverrideConnector},
+};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+const DNS_MESSAGE: &str = "application/dns-message";
+
+// The same as the default timeout of the built-in resolver.
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Resolves host names with DNS-over-HTTPS (RFC 8484).
+pub struct DohResolver {
+    endpoint: Uri,
+    client: Client<HttpsConnector<OverrideConnector<HttpConnector>>, Full<Bytes>>,
+}
+
+impl DohResolver {
+    // Creates the resolver for `endpoint` (e.g. `https://dns.example/dns-query`).
+    // If `bootstrap_addresses` is not empty, the endpoint host is connected to these addresses instead of resolving it.
+    pub fn new(
+        endpoint: Uri,
+        bootstrap_addresses: &[IpAddr],
+        tls_config: rustls::ClientConfig,
+        connect_timeout: Option<Duration>,
+    ) -> Result<Self, String> {
+        let host = endpoint.host().ok_or_else(|| format!("Invalid DNS-over-HTTPS endpoint: {}", endpoint))?;
+        let port = endpoint.port_u16().unwrap_or(443);
+
+        let mut overrides = ConnectOverrides::default();
+        for address in bootstrap_addresses {
+            overrides.add_address(&format!("{}:{}", host, port), &address.to_string())?;
+        }
+
+        let mut http_conn = HttpConnector::new();
+        http_conn.set_nodelay(true);
+        http_conn.enforce_http(false);
+        http_conn.set_connect_timeout(connect_timeout);
+        let connector = hyper_rustls::HttpsConnectorBuilder::new()
+            .with_tls_config(tls_config)
+            .https_only()
+            .enable_all_versions()
+            .wrap_connector(OverrideConnector::new(http_conn, Arc::new(overrides)));
+
+        let mut builder = Client::builder(TokioExecutor::new());
+        builder.timer(TokioTimer::new());
+        Ok(DohResolver {
+            endpoint,
+            client: builder.build(connector),
+        })
+    }
+
+    pub async fn lookup(&self, host: &str) -> Result<Lookup, LookupError> {
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return Ok(Lookup { addrs: vec![ip], ttl: None });
+        }
+
+        let (v4, v6) = tokio::join!(self.query(host, RecordType::A), self.query(host, RecordType::AAAA));
+        match (v4, v6) {
+            (Ok(v4), Ok(v6)) => {
+                let mut addrs = v4.addrs;
+                addrs.extend(v6.addrs);
+                Ok(Lookup { addrs, ttl: min_ttl(v4.ttl, v6.ttl) })
+            }
+            (Ok(lookup), Err(LookupError::NotFound { .. })) | (Err(LookupError::NotFound { .. }), Ok(lookup)) => Ok(lookup),
+            (
+                Err(LookupError::NotFound { message, negative_ttl: ttl1 }),
+                Err(LookupError::NotFound { negative_ttl: ttl2, .. }),
+            ) => Err(LookupError::NotFound { message, negative_ttl: min_ttl(ttl1, ttl2) }),
+            // Use the addresses of the other family on a transient failure, but don't cache them.
+            (Ok(lookup), Err(LookupError::Other(_))) | (Err(LookupError::Other(_)), Ok(lookup)) => {
+                Ok(Lookup { addrs: lookup.addrs, ttl: Some(Duration::ZERO) })
+            }
+            (Err(LookupError::Other(err)), _) | (_, Err(LookupError::Other(err))) => Err(LookupError::Other(err)),
+        }
+    }
+
+    // Sends a query for the records of `record_type`. `NotFound` is returned if the response has no records. (NXDOMAIN or NODATA)
+    async fn query(&self, host: &str, record_type: RecordType) -> Result<Lookup, LookupError> {
+        let query = build_query(host, record_type).map_err(LookupError::Other)?;
+        let response = tokio::time::timeout(QUERY_TIMEOUT, self.send(query))
+            .await
+            .map_err(|_| LookupError::Other(io::Error::new(io::ErrorKind::TimedOut, "DNS-over-HTTPS query timed out").into()))?
+            .map_err(LookupError::Other)?;
+
+        let not_found = |response: &Message| LookupError::NotFound {
+            message: format!("failed to resolve the host '{}': {} ({})", host, response.response_code(), record_type),
+            negative_ttl: negative_ttl(response),
+        };
+        match response.response_code() {
+            ResponseCode::NoError => {}
+            ResponseCode::NXDomain => return Err(not_found(&response)),
+            code => {
+                return Err(LookupError::Other(
+                    io::Error::other(format!("failed to resolve the host '{}': {}", host, code)).into(),
+                ))
+            }
+        }
+
+        let mut addrs = Vec::new();
+        let mut ttl: Option<u32> = None;
+        for record in response.answers() {
+            let ip = match record.data() {
+                Some(RData::A(a)) if record_type == RecordType::A => IpAddr::V4(a.0),
+                Some(RData::AAAA(aaaa)) if record_type == RecordType::AAAA => IpAddr::V6(aaaa.0),
+                // e.g. CNAME. The server follows the chain and includes the final records.
+                _ => continue,
+            };
+            addrs.push(ip);
+            ttl = Some(ttl.map_or(record.ttl(), |ttl| ttl.min(record.ttl())));
+        }
+        if addrs.is_empty() {
+            return Err(not_found(&response));
+        }
+        Ok(Lookup { addrs, ttl: ttl.map(|ttl| Duration::from_secs(ttl as u64)) })
+    }
+
+    async fn send(&self, query: Vec<u8>) -> Result<Message, BoxError> {
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri(self.endpoint.clone())
+            .header(CONTENT_TYPE, DNS_MESSAGE)
+            .header(ACCEPT, DNS_MESSAGE)
+            .body(Full::new(Bytes::from(query)))?;
+        let res = self.client.request(req).await?;
+        if res.status() != StatusCode::OK {
+            return Err(io::Error::other(format!("DNS-over-HTTPS server responded with {}", res.status())).into());
+        }
+        let body = res.into_body().collect().await?.to_bytes();
+        Ok(Message::from_vec(&body)?)
+    }
+}
+
+fn build_query(host: &str, record_type: RecordType) -> Result<Vec<u8>, BoxError> {
+    let mut name = Name::from_ascii(host)?;
+    name.set_fqdn(true);
+
+    let mut message = Message::new();
+    // RFC 8484 recommends ID 0 to make the responses cache friendly.
+    message
+        .set_id(0)
+        .set_message_type(MessageType::Query)
+        .set_op_code(OpCode::Query)
+        .set_recursion_desired(true)
+        .add_query(Query::query(name, record_type));
+    Ok(message.to_vec()?)
+}
+
+// The negative TTL is the smaller of the TTL and the MINIMUM field of the SOA record in the authority section. (RFC 2308)
+fn negative_ttl(response: &Message) -> Option<Duration> {
+    response.name_servers().iter().find_map(|record| match record.data() {
+        Some(RData::SOA(soa)) => Some(Duration::from_secs(record.ttl().min(soa.minimum()) as u64)),
+        _ => None,
+    })
+}
+
+fn min_ttl(a: Option<Duration>, b: Option<Duration>) -> Option<Duration> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
@@ -0,0 +1,95 @@
+use std::{future::Future, time::Duration};
+
+use futures_util::{stream::FuturesUnordered, StreamExt};
+use http_body_util::{combinators::BoxBody, BodyExt, Empty};
+use hyper::{
+    body::{Body, Bytes, Incoming},
+    Request, Response,
+};
+use tokio::{select, time::Instant};
+
+use crate::{balancer::UsedEndpoints, context::{RequestBody, RequestError}, retry};
+
+#[derive(Clone, Debug)]
+pub struct HedgingOptions {
+    // The delay before sending the next duplicate request if no response headers arrive.
+    pub delay: Duration,
+    // The maximum number of requests including the first one.
+    pub max_attempts: u32,
+}
+
+/// Sends duplicate requests if the response headers don't arrive within the delay, and uses the first response.
+pub struct HedgingPolicy {
+    options: HedgingOptions,
+}
+
+impl HedgingPolicy {
+    pub fn new(options: HedgingOptions) -> Self {
+        HedgingPolicy { options }
+    }
+
+    // Sends the request with `send`. Only the idempotent requests without body are hedged.
+    pub async fn execute<F, Fut>(&self, req: Request<RequestBody>, send: F) -> Result<Response<Incoming>, RequestError>
+    where
+        F: Fn(Request<RequestBody>) -> Fut,
+        Fut: Future<Output = Result<Response<Incoming>, RequestError>>,
+    {
+        if self.options.max_attempts < 2 || !retry::is_idempotent(req.method()) || !req.body().is_end_stream() {
+            return send(req).await;
+        }
+
+        let (mut parts, _) = req.into_parts();
+        // The duplicate requests prefer the endpoints not used by the previous ones.
+        parts.extensions.insert(UsedEndpoints::default());
+
+        let new_attempt = || {
+            let mut req = Request::builder()
+                .method(parts.method.clone())
+                .uri(parts.uri.clone())
+                .version(parts.version)
+                .body(BoxBody::new(Empty::<Bytes>::new().map_err(|never| match never {})))
+                .unwrap();
+            *req.headers_mut() = parts.headers.clone();
+            *req.extensions_mut() = parts.extensions.clone();
+            send(req)
+        };
+
+        // The requests still in flight are cancelled when `attempts` is dropped.
+        let mut attempts = FuturesUnordered::new();
+        attempts.push(new_attempt());
+        let mut launched = 1;
+        // The delay is restarted only when a new request is sent.
+        let delay = tokio::time::sleep(self.options.delay);
+        tokio::pin!(delay);
+        let mut error: Option<RequestError> = None;
+        loop {
+            let hedge = launched < self.options.max_attempts;
+            select! {
+                result = attempts.next(), if !attempts.is_empty() => match result {
+                    Some(Ok(res)) => return Ok(res),
+                    Some(Err(err)) => {
+                        // Keep the first error of the client. The later requests may fail only because the circuit breaker is opened by it.
+                        if !matches!(error, Some(RequestError::Client(_))) {
+                            error = Some(err);
+                        }
+                        if hedge {
+                            // Send the next request right away instead of waiting for the delay.
+                            attempts.push(new_attempt());
+                            launched += 1;
+                            delay.as_mut().reset(Instant::now() + self.options.delay);
+                        } else if attempts.is_empty() {
+                            // The failure is reported if no other requests are in flight.
+                            return Err(error.unwrap());
+                        }
+                    }
+                    None => {}
+                },
+                _ = &mut delay, if hedge => {
+                    attempts.push(new_attempt());
+                    launched += 1;
+                    delay.as_mut().reset(Instant::now() + self.options.delay);
+                }
+            }
+        }
+    }
+}
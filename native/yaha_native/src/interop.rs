@@ -16,7 +16,7 @@ impl StringBuffer {
 
     pub fn new(ptr: *const u8, len: i32) -> Self {
         StringBuffer {
-            ptr: ptr,
+            ptr,
             length: len,
         }
     }
@@ -24,7 +24,7 @@ impl StringBuffer {
     pub fn to_str(&self) -> &str {
         unsafe {
             let bytes = std::slice::from_raw_parts(self.ptr, self.length as usize);
-            std::str::from_utf8_unchecked(&bytes)
+            std::str::from_utf8_unchecked(bytes)
         }
     }
     pub fn to_bytes(&self) -> &[u8] {
@@ -1,6 +1,26 @@
+mod balancer;
+mod cache;
+mod circuit_breaker;
+mod compress;
 mod context;
+mod cookie_jar;
+mod decompress;
+mod dns;
+#[cfg(feature = "rustls")]
+mod doh;
+mod hedge;
 mod interop;
+mod overrides;
+#[cfg(feature = "rustls")]
+mod pinning;
 mod primitives;
+mod proxy;
+mod redirect;
+mod retry;
+mod socks;
+mod srv;
+#[cfg(feature = "rustls")]
+mod verification;
 
-#[allow(unused_variables)]
+#[allow(unused_variables, clippy::missing_safety_doc, clippy::not_unsafe_ptr_arg_deref)]
 pub mod binding;
@@ -0,0 +1,177 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    io,
+    net::{IpAddr, SocketAddr},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use hyper::{http::uri::Scheme, Uri};
+use tower_service::Service;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+// Static overrides of the connection destination. (like curl's `--resolve` and `--connect-to`)
+#[derive(Clone, Debug, Default)]
+pub struct ConnectOverrides {
+    // `host:port` -> the addresses to connect to instead of resolving the host name.
+    addresses: HashMap<(String, u16), Vec<SocketAddr>>,
+    // `host:port` -> another `host:port` to connect to.
+    connect_to: HashMap<(String, u16), (String, u16)>,
+}
+
+impl ConnectOverrides {
+    // Adds an address for `host:port`. The address can omit the port (e.g. `192.0.2.1`) to use the original port.
+    pub fn add_address(&mut self, host_port: &str, address: &str) -> Result<(), String> {
+        let (host, port) = parse_host_port(host_port)?;
+        let address = match address.parse::<SocketAddr>() {
+            Ok(address) => address,
+            Err(_) => {
+                let ip = trim_brackets(address)
+                    .parse::<IpAddr>()
+                    .map_err(|_| format!("Invalid address: {}", address))?;
+                SocketAddr::new(ip, port)
+            }
+        };
+        self.addresses.entry((host, port)).or_default().push(address);
+        Ok(())
+    }
+
+    pub fn add_connect_to(&mut self, from: &str, to: &str) -> Result<(), String> {
+        let from = parse_host_port(from)?;
+        let to = parse_host_port(to)?;
+        self.connect_to.insert(from, to);
+        Ok(())
+    }
+
+    // Returns whether the destination of `host:port` is overridden.
+    pub fn contains(&self, host: &str, port: u16) -> bool {
+        self.resolve(host, port).is_some()
+    }
+
+    // Connects `host:port` to the address only.
+    pub fn pin_address(&mut self, host: &str, port: u16, address: SocketAddr) {
+        let key = (trim_brackets(host).to_ascii_lowercase(), port);
+        self.connect_to.remove(&key);
+        self.addresses.insert(key, vec![address]);
+    }
+
+    // Returns the destination to connect to. `connect_to` is applied first, then `addresses` for the new `host:port`.
+    fn resolve(&self, host: &str, port: u16) -> Option<Target> {
+        let key = (trim_brackets(host).to_ascii_lowercase(), port);
+        let key = match self.connect_to.get(&key) {
+            Some(to) => to.clone(),
+            None if self.addresses.contains_key(&key) => key,
+            None => return None,
+        };
+        match self.addresses.get(&key) {
+            Some(addresses) => Some(Target::Addresses(addresses.clone())),
+            None => Some(Target::HostPort(key.0, key.1)),
+        }
+    }
+}
+
+enum Target {
+    Addresses(Vec<SocketAddr>),
+    HostPort(String, u16),
+}
+
+fn trim_brackets(host: &str) -> &str {
+    // `Uri::host` returns IPv6 address with square brackets.
+    host.trim_start_matches('[').trim_end_matches(']')
+}
+
+fn parse_host_port(value: &str) -> Result<(String, u16), String> {
+    let (host, port) = value
+        .rsplit_once(':')
+        .ok_or_else(|| format!("Invalid host and port (expected `host:port`): {}", value))?;
+    let host = trim_brackets(host);
+    if host.is_empty() {
+        return Err(format!("Invalid host and port (missing host): {}", value));
+    }
+    let port = port
+        .parse::<u16>()
+        .map_err(|_| format!("Invalid host and port (invalid port): {}", value))?;
+    Ok((host.to_ascii_lowercase(), port))
+}
+
+pub fn to_uri(host: &str, port: u16) -> Result<Uri, BoxError> {
+    let authority = if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    };
+    Ok(Uri::builder()
+        .scheme(Scheme::HTTP)
+        .authority(authority)
+        .path_and_query("/")
+        .build()?)
+}
+
+/// A connector that applies `ConnectOverrides` before the inner connector resolves the host name.
+///
+/// The destination URI seen by the outer connectors is not changed, so `Host` header and SNI keep the original host name.
+#[derive(Clone)]
+pub struct OverrideConnector<C> {
+    inner: C,
+    overrides: Arc<ConnectOverrides>,
+}
+
+impl<C> OverrideConnector<C> {
+    pub fn new(inner: C, overrides: Arc<ConnectOverrides>) -> Self {
+        OverrideConnector { inner, overrides }
+    }
+}
+
+impl<C> Service<Uri> for OverrideConnector<C>
+where
+    C: Service<Uri> + Clone + Send + 'static,
+    C::Response: Send,
+    C::Future: Send + 'static,
+    C::Error: Into<BoxError>,
+{
+    type Response = C::Response;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, dst: Uri) -> Self::Future {
+        let port = dst.port_u16().unwrap_or(if dst.scheme() == Some(&Scheme::HTTPS) { 443 } else { 80 });
+        let target = dst.host().and_then(|host| self.overrides.resolve(host, port));
+
+        match target {
+            None => {
+                let connecting = self.inner.call(dst);
+                Box::pin(async move { connecting.await.map_err(Into::into) })
+            }
+            Some(Target::HostPort(host, port)) => {
+                let uri = match to_uri(&host, port) {
+                    Ok(uri) => uri,
+                    Err(err) => return Box::pin(async move { Err(err) }),
+                };
+                let connecting = self.inner.call(uri);
+                Box::pin(async move { connecting.await.map_err(Into::into) })
+            }
+            Some(Target::Addresses(addresses)) => {
+                let mut inner = self.inner.clone();
+                Box::pin(async move {
+                    // Try the addresses in order and return the first successful connection.
+                    let mut last_error: BoxError = io::Error::other("no addresses to connect").into();
+                    for address in addresses {
+                        let uri = to_uri(&address.ip().to_string(), address.port())?;
+                        match inner.call(uri).await {
+                            Ok(stream) => return Ok(stream),
+                            Err(err) => last_error = err.into(),
+                        }
+                    }
+                    Err(last_error)
+                })
+            }
+        }
+    }
+}
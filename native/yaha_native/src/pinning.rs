@@ -0,0 +1,252 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::Engine;
+use rustls::pki_types::{CertificateDer, TrustAnchor, UnixTime};
+
+// The length of SHA-256 hash.
+const PIN_LENGTH: usize = 32;
+
+/// The public keys pinned per host name pattern. A pin is the SHA-256 hash of the SubjectPublicKeyInfo.
+#[derive(Clone, Debug, Default)]
+pub struct PinStore {
+    pin_sets: Vec<PinSet>,
+}
+
+#[derive(Clone, Debug)]
+struct PinSet {
+    // The lower-cased host name, or `*.` followed by the domain to match its subdomains.
+    pattern: String,
+    pins: Vec<[u8; PIN_LENGTH]>,
+    // The pins are not enforced after this time, so that the clients don't stop working if the pins are not updated.
+    expires_at: Option<SystemTime>,
+}
+
+impl PinStore {
+    // Adds the pins for the host name pattern. The pins are separated by commas, and each pin is `sha256/<base64>` or `<base64>`.
+    // The backup pins are added with the primary pins, and the server is trusted if one of them matches.
+    pub fn add(&mut self, pattern: &str, pins: &str, expires_at: Option<SystemTime>) -> Result<(), String> {
+        let pattern = pattern.trim().trim_end_matches('.').to_ascii_lowercase();
+        let domain = pattern.strip_prefix("*.").unwrap_or(&pattern);
+        if domain.is_empty() || domain.contains('*') {
+            return Err(format!("invalid host name pattern: '{}'", pattern));
+        }
+
+        let pins = pins
+            .split(',')
+            .map(str::trim)
+            .filter(|pin| !pin.is_empty())
+            .map(parse_pin)
+            .collect::<Result<Vec<_>, _>>()?;
+        if pins.is_empty() {
+            return Err(format!("no pins are specified for '{}'", pattern));
+        }
+
+        self.pin_sets.push(PinSet { pattern, pins, expires_at });
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pin_sets.is_empty()
+    }
+
+    // Verifies that one of the certificates in the path from the server certificate to a trusted root has a pinned public key.
+    // The path is built and verified again here, so the pins are checked only against the certificates which actually chain to one of the trust anchors,
+    // including the trust anchor itself. The server is not trusted if no path is built, as the other certificates presented by the server can be anyone's.
+    // The pins of all the patterns matching the server name are accepted. The server is trusted if no pattern matches.
+    pub fn verify(&self, server_name: &str, certificates: &[CertificateDer<'_>], trust_anchors: &[TrustAnchor<'_>], now: SystemTime) -> Result<(), String> {
+        let server_name = server_name.trim_end_matches('.').to_ascii_lowercase();
+        let pins = self.pin_sets
+            .iter()
+            .filter(|pin_set| pin_set.expires_at.is_none_or(|expires_at| now < expires_at))
+            .filter(|pin_set| matches(&pin_set.pattern, &server_name))
+            .flat_map(|pin_set| pin_set.pins.iter())
+            .collect::<Vec<_>>();
+        if pins.is_empty() {
+            return Ok(());
+        }
+
+        let path = verified_path_sha256(certificates, trust_anchors, now)
+            .map_err(|err| format!("the public key pins for '{}' can't be checked as the server certificates don't chain to a trusted root: {}", server_name, err))?;
+        if path.iter().any(|hash| pins.contains(&hash)) {
+            return Ok(());
+        }
+
+        let path = path.iter().map(format_pin).collect::<Vec<_>>();
+        Err(format!(
+            "the public key pins for '{}' don't match the server certificates (certificate path: {})",
+            server_name,
+            path.join(", ")
+        ))
+    }
+}
+
+fn matches(pattern: &str, server_name: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(domain) => {
+            server_name.len() > domain.len()
+                && server_name.ends_with(domain)
+                && server_name.as_bytes()[server_name.len() - domain.len() - 1] == b'.'
+        }
+        None => pattern == server_name,
+    }
+}
+
+fn parse_pin(pin: &str) -> Result<[u8; PIN_LENGTH], String> {
+    let encoded = pin.strip_prefix("sha256/").unwrap_or(pin);
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .ok()
+        .and_then(|hash| <[u8; PIN_LENGTH]>::try_from(hash).ok())
+        .ok_or_else(|| format!("invalid pin: '{}'", pin))
+}
+
+fn format_pin(hash: &[u8; PIN_LENGTH]) -> String {
+    format!("sha256/{}", base64::engine::general_purpose::STANDARD.encode(hash))
+}
+
+// Builds the path from the end-entity certificate to one of the trust anchors, and returns the hashes of the public keys from the end-entity to the trust anchor.
+fn verified_path_sha256(certificates: &[CertificateDer<'_>], trust_anchors: &[TrustAnchor<'_>], now: SystemTime) -> Result<Vec<[u8; PIN_LENGTH]>, String> {
+    let (end_entity, intermediates) = certificates.split_first().ok_or("the server presented no certificates")?;
+    let end_entity = webpki::EndEntityCert::try_from(end_entity).map_err(|err| format!("{:?}", err))?;
+    let now = UnixTime::since_unix_epoch(now.duration_since(UNIX_EPOCH).unwrap_or_default());
+    let path = end_entity
+        .verify_for_usage(
+            rustls::crypto::ring::default_provider().signature_verification_algorithms.all,
+            trust_anchors,
+            intermediates,
+            now,
+            webpki::KeyUsage::server_auth(),
+            None,
+            None,
+        )
+        .map_err(|err| format!("{:?}", err))?;
+
+    let mut hashes = vec![sha256(path.end_entity().subject_public_key_info().as_ref())];
+    hashes.extend(path.intermediate_certificates().map(|cert| sha256(cert.subject_public_key_info().as_ref())));
+    // The trust anchor holds the content of the SubjectPublicKeyInfo without the SEQUENCE header.
+    hashes.push(sha256(&der_sequence(path.anchor().subject_public_key_info.as_ref())));
+    Ok(hashes)
+}
+
+fn sha256(data: &[u8]) -> [u8; PIN_LENGTH] {
+    let digest = ring::digest::digest(&ring::digest::SHA256, data);
+    digest.as_ref().try_into().unwrap()
+}
+
+// Wraps the content into a DER encoded SEQUENCE.
+fn der_sequence(content: &[u8]) -> Vec<u8> {
+    let length = content.len().to_be_bytes();
+    let length = &length[length.iter().position(|byte| *byte != 0).unwrap_or(length.len() - 1)..];
+    let mut der = vec![0x30];
+    if content.len() >= 0x80 {
+        der.push(0x80 | length.len() as u8);
+    }
+    der.extend_from_slice(length);
+    der.extend_from_slice(content);
+    der
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A test root CA, an intermediate CA issued by it, the localhost certificate issued by the intermediate,
+    // and a localhost certificate which claims to be issued by the intermediate but is signed by another key.
+    const ROOT_CA: &str = "\
+-----BEGIN CERTIFICATE-----
+MIIBoTCCAUagAwIBAgIUO0hPpZXXn6DR4S3FDZiVVASMh40wCgYIKoZIzj0EAwIw
+LTErMCkGA1UEAwwiWWV0QW5vdGhlckh0dHBIYW5kbGVyIFRlc3QgUm9vdCBDQTAg
+Fw0yNjEwMTgwNzQ2MDJaGA8yMTI2MDkyNDA3NDYwMlowLTErMCkGA1UEAwwiWWV0
+QW5vdGhlckh0dHBIYW5kbGVyIFRlc3QgUm9vdCBDQTBZMBMGByqGSM49AgEGCCqG
+SM49AwEHA0IABKiJp/5gqhbeJMv25u58CeM4o1Dvl45t2IbW0Ir7CIr9aFSnfKUM
+ZMZSATAtq0BemOnqCNvLt85XtapAAmAfbqajQjBAMA8GA1UdEwEB/wQFMAMBAf8w
+DgYDVR0PAQH/BAQDAgEGMB0GA1UdDgQWBBSuKDiMpSi1XDSv8mF8C7yZ3WQsCTAK
+BggqhkjOPQQDAgNJADBGAiEAmBqUM1UTkDZ3qf9qpLrYko1BI2erWeR6l8E2ehGl
+PIACIQDxdp7xoIJ6gUt7WZ9rknfLk3vBTE9b+Z6/eBcYvnZbWQ==
+-----END CERTIFICATE-----";
+    const INTERMEDIATE_CA: &str = "\
+-----BEGIN CERTIFICATE-----
+MIIByjCCAW+gAwIBAgIUKRirhntv32B7u1xtDdXB9k5wCjwwCgYIKoZIzj0EAwIw
+LTErMCkGA1UEAwwiWWV0QW5vdGhlckh0dHBIYW5kbGVyIFRlc3QgUm9vdCBDQTAg
+Fw0yNjEwMTgwNzQ2MDJaGA8yMTI2MDkyNDA3NDYwMlowNTEzMDEGA1UEAwwqWWV0
+QW5vdGhlckh0dHBIYW5kbGVyIFRlc3QgSW50ZXJtZWRpYXRlIENBMFkwEwYHKoZI
+zj0CAQYIKoZIzj0DAQcDQgAEEqBcyMxHIU4c+wPKQUtFfwc2CcsNgtHGL9ChgCju
+o0m6elGgeqWnN3slzE/U733gfjoWZk6MMUZYiFYT50IeKKNjMGEwDwYDVR0TAQH/
+BAUwAwEB/zAOBgNVHQ8BAf8EBAMCAQYwHQYDVR0OBBYEFF9thMeIdOrfyE4JEp2U
+jNRgR8URMB8GA1UdIwQYMBaAFK4oOIylKLVcNK/yYXwLvJndZCwJMAoGCCqGSM49
+BAMCA0kAMEYCIQDGbngvH6m3Lm9RoWje8iUidYbKNHstCz7J/Ih8fSHMdwIhANat
+aB5s4Agv7E4pUzazFX80H9QMbb3VwMlGr9o2Mson
+-----END CERTIFICATE-----";
+    const LOCALHOST: &str = "\
+-----BEGIN CERTIFICATE-----
+MIIB4DCCAYagAwIBAgIUWAN+40ed4IxHwc/Xl2/pI54OlUowCgYIKoZIzj0EAwIw
+NTEzMDEGA1UEAwwqWWV0QW5vdGhlckh0dHBIYW5kbGVyIFRlc3QgSW50ZXJtZWRp
+YXRlIENBMCAXDTI2MTAxODA3NDYwMloYDzIxMjYwOTI0MDc0NjAyWjAUMRIwEAYD
+VQQDDAlsb2NhbGhvc3QwWTATBgcqhkjOPQIBBggqhkjOPQMBBwNCAASMd1XWYUai
+jsJYVUqVGjLRzzN1kvnPvz/xX+cnv6nfdMSNXz+4AdLFa0MUVKXzTYBvbz1Ny0zj
+dQoNI113oaTso4GSMIGPMAwGA1UdEwEB/wQCMAAwDgYDVR0PAQH/BAQDAgeAMBMG
+A1UdJQQMMAoGCCsGAQUFBwMBMBoGA1UdEQQTMBGCCWxvY2FsaG9zdIcEfwAAATAd
+BgNVHQ4EFgQUY3znI9AOLzItVdw3LyCaSIJdVUkwHwYDVR0jBBgwFoAUX22Ex4h0
+6t/ITgkSnZSM1GBHxREwCgYIKoZIzj0EAwIDSAAwRQIgMEhIPAWOOmORi0wZwwGe
+lwoTP0bSX27KpuPA+kzSyXMCIQDgEb4PWP4MloJ4rujqdMEf4KhlkHUh12GkFSkD
+9J8zuw==
+-----END CERTIFICATE-----";
+    const FORGED_LOCALHOST: &str = "\
+-----BEGIN CERTIFICATE-----
+MIIBwDCCAWWgAwIBAgIUKyRHjusRK8UsS4L7wLFnBvfQ5aowCgYIKoZIzj0EAwIw
+NTEzMDEGA1UEAwwqWWV0QW5vdGhlckh0dHBIYW5kbGVyIFRlc3QgSW50ZXJtZWRp
+YXRlIENBMCAXDTI2MTAxODAwMDAwMFoYDzIxMjYwOTI0MDAwMDAwWjAUMRIwEAYD
+VQQDDAlsb2NhbGhvc3QwWTATBgcqhkjOPQIBBggqhkjOPQMBBwNCAAT7nfDhj2hN
+Tuhqly/1C4W+gWC1ZXySejH12gauf2z05qFB+MU6HHqpUWbNp09TNFJ4KO/FOuKp
+FR7uMNGIHmkHo3IwcDAMBgNVHRMBAf8EAjAAMA4GA1UdDwEB/wQEAwIHgDATBgNV
+HSUEDDAKBggrBgEFBQcDATAaBgNVHREEEzARgglsb2NhbGhvc3SHBH8AAAEwHwYD
+VR0jBBgwFoAUX22Ex4h06t/ITgkSnZSM1GBHxREwCgYIKoZIzj0EAwIDSQAwRgIh
+AMl7LqdUeFpo9QOPDe7dikj4Uh1NaZitYUhwEzoaJzFJAiEA8CbSddS8Gd9h2OZV
++3QQjWBjlS0eozMu+4394yMVBS0=
+-----END CERTIFICATE-----";
+
+    const ROOT_CA_PIN: &str = "sha256/2Yvkc5466SNIRo8zRyQMQgKjwlH9fXmHc/l2WC6yH+I=";
+    const INTERMEDIATE_CA_PIN: &str = "sha256/k/k+FRHdCPx5c2xBv4kJz5lNS5r9TcHhB6/Q49k0+0Y=";
+    const LOCALHOST_PIN: &str = "sha256/1mM4JrojsW0ly5NTWQ9u0s+OklgCi9pj1c+XMV7GcbY=";
+    const UNKNOWN_PIN: &str = "sha256/AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=";
+
+    fn certificates(pems: &[&str]) -> Vec<CertificateDer<'static>> {
+        pems.iter().map(|pem| rustls_pemfile::certs(&mut pem.as_bytes()).next().unwrap().unwrap()).collect()
+    }
+
+    fn verify(pins: &str, chain: &[&str]) -> Result<(), String> {
+        let mut pin_store = PinStore::default();
+        pin_store.add("localhost", pins, None).unwrap();
+        let mut root_store = rustls::RootCertStore::empty();
+        root_store.add_parsable_certificates(certificates(&[ROOT_CA]));
+        pin_store.verify("localhost", &certificates(chain), &root_store.roots, SystemTime::now())
+    }
+
+    #[test]
+    fn matches_any_certificate_in_verified_path() {
+        assert!(verify(LOCALHOST_PIN, &[LOCALHOST, INTERMEDIATE_CA]).is_ok());
+        assert!(verify(INTERMEDIATE_CA_PIN, &[LOCALHOST, INTERMEDIATE_CA]).is_ok());
+        // The trust anchor is in the path even if the server doesn't present it.
+        assert!(verify(ROOT_CA_PIN, &[LOCALHOST, INTERMEDIATE_CA]).is_ok());
+    }
+
+    #[test]
+    fn rejects_mismatched_pins() {
+        let err = verify(UNKNOWN_PIN, &[LOCALHOST, INTERMEDIATE_CA]).unwrap_err();
+        assert!(err.contains("don't match the server certificates"));
+        assert!(err.contains(&format!("{}, {}, {}", LOCALHOST_PIN, INTERMEDIATE_CA_PIN, ROOT_CA_PIN)));
+    }
+
+    #[test]
+    fn rejects_pinned_intermediate_presented_with_forged_certificate() {
+        let err = verify(INTERMEDIATE_CA_PIN, &[FORGED_LOCALHOST, INTERMEDIATE_CA]).unwrap_err();
+        assert!(err.contains("don't chain to a trusted root"));
+    }
+
+    #[test]
+    fn ignores_other_hosts() {
+        let mut pin_store = PinStore::default();
+        pin_store.add("*.localhost", UNKNOWN_PIN, None).unwrap();
+        assert!(pin_store.verify("localhost", &certificates(&[FORGED_LOCALHOST]), &[], SystemTime::now()).is_ok());
+    }
+}
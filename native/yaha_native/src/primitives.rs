@@ -29,6 +29,141 @@ pub enum CompletionReason {
     Success,
     Error,
     Aborted,
+    CircuitBreakerOpen,
+}
+
+// The order of address families to connect to. The values must be kept in sync with `AddressFamilyPreference` in C#.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum YahaAddressFamilyPreference {
+    // Keep the order returned by the resolver.
+    #[default]
+    Default = 0,
+    Ipv4Only = 1,
+    Ipv6Only = 2,
+    PreferIpv4 = 3,
+    PreferIpv6 = 4,
+}
+
+impl TryFrom<i32> for YahaAddressFamilyPreference {
+    type Error = ();
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(YahaAddressFamilyPreference::Default),
+            1 => Ok(YahaAddressFamilyPreference::Ipv4Only),
+            2 => Ok(YahaAddressFamilyPreference::Ipv6Only),
+            3 => Ok(YahaAddressFamilyPreference::PreferIpv4),
+            4 => Ok(YahaAddressFamilyPreference::PreferIpv6),
+            _ => Err(()),
+        }
+    }
+}
+
+// The values must be kept in sync with `LoadBalancingPolicy` in C#.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum YahaLoadBalancingPolicy {
+    RoundRobin = 0,
+    LeastOutstandingRequests = 1,
+    PowerOfTwoChoices = 2,
+}
+
+impl TryFrom<i32> for YahaLoadBalancingPolicy {
+    type Error = ();
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(YahaLoadBalancingPolicy::RoundRobin),
+            1 => Ok(YahaLoadBalancingPolicy::LeastOutstandingRequests),
+            2 => Ok(YahaLoadBalancingPolicy::PowerOfTwoChoices),
+            _ => Err(()),
+        }
+    }
+}
+
+// The values must be kept in sync with `CookieFileFormat` in C#.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum YahaCookieFileFormat {
+    Json = 0,
+    Netscape = 1,
+}
+
+impl TryFrom<i32> for YahaCookieFileFormat {
+    type Error = ();
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(YahaCookieFileFormat::Json),
+            1 => Ok(YahaCookieFileFormat::Netscape),
+            _ => Err(()),
+        }
+    }
+}
+
+// The values must be kept in sync with `RequestBodyCompression` in C#.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum YahaRequestBodyCompression {
+    Gzip = 0,
+    Brotli = 1,
+    Zstd = 2,
+}
+
+impl TryFrom<i32> for YahaRequestBodyCompression {
+    type Error = ();
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(YahaRequestBodyCompression::Gzip),
+            1 => Ok(YahaRequestBodyCompression::Brotli),
+            2 => Ok(YahaRequestBodyCompression::Zstd),
+            _ => Err(()),
+        }
+    }
+}
+
+// The values must be kept in sync with `RevocationCheckDepth` in C#.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum YahaRevocationCheckDepth {
+    // Check the revocation status of the end-entity and intermediate certificates.
+    Chain = 0,
+    EndEntity = 1,
+}
+
+impl TryFrom<i32> for YahaRevocationCheckDepth {
+    type Error = ();
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(YahaRevocationCheckDepth::Chain),
+            1 => Ok(YahaRevocationCheckDepth::EndEntity),
+            _ => Err(()),
+        }
+    }
+}
+
+// The values must be kept in sync with `UnknownRevocationStatusPolicy` in C#.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum YahaUnknownRevocationStatusPolicy {
+    // Untrust the certificate if no CRL is provided by its issuer.
+    Deny = 0,
+    Allow = 1,
+}
+
+impl TryFrom<i32> for YahaUnknownRevocationStatusPolicy {
+    type Error = ();
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(YahaUnknownRevocationStatusPolicy::Deny),
+            1 => Ok(YahaUnknownRevocationStatusPolicy::Allow),
+            _ => Err(()),
+        }
+    }
 }
 
 #[repr(i32)]
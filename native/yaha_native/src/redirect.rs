@@ -0,0 +1,148 @@
+use std::future::Future;
+
+use http_body_util::{combinators::BoxBody, BodyExt, Empty};
+use hyper::{
+    body::{Body, Bytes},
+    header::{self, HeaderMap},
+    Method, Request, Response, StatusCode, Uri,
+};
+
+use crate::{context::{RequestBody, RequestError}, retry::ReplayableBody};
+
+#[derive(Clone, Debug)]
+pub struct RedirectOptions {
+    pub max_redirects: u32,
+    // Follows the redirects from HTTPS to HTTP. The redirect response is returned as is if this is false.
+    pub allow_https_to_http: bool,
+}
+
+impl Default for RedirectOptions {
+    fn default() -> Self {
+        RedirectOptions {
+            max_redirects: 50,
+            allow_https_to_http: false,
+        }
+    }
+}
+
+/// The URI of the response after following the redirects. It is inserted into the extensions of the response if the request was redirected.
+#[derive(Clone)]
+pub struct RedirectedUri(pub Uri);
+
+/// Follows the redirect responses.
+pub struct RedirectPolicy {
+    options: RedirectOptions,
+}
+
+impl RedirectPolicy {
+    pub fn new(options: RedirectOptions) -> Self {
+        RedirectPolicy { options }
+    }
+
+    // Sends the request with `send`, and sends the request to the location while the response is a redirect.
+    // The last redirect response is returned if it can't be followed. (the limit of redirects, downgrade to HTTP, body not replayable)
+    pub async fn execute<F, Fut, B>(&self, req: Request<RequestBody>, send: F) -> Result<Response<B>, RequestError>
+    where
+        F: Fn(Request<RequestBody>) -> Fut,
+        Fut: Future<Output = Result<Response<B>, RequestError>>,
+    {
+        let (mut parts, body) = req.into_parts();
+        let mut body = (!body.is_end_stream()).then(|| ReplayableBody::new(body));
+
+        let mut redirects = 0;
+        loop {
+            let mut req = Request::builder()
+                .method(parts.method.clone())
+                .uri(parts.uri.clone())
+                .version(parts.version)
+                .body(match &body {
+                    Some(body) => BoxBody::new(body.replay()),
+                    None => BoxBody::new(Empty::<Bytes>::new().map_err(|never| match never {})),
+                })
+                .unwrap();
+            *req.headers_mut() = parts.headers.clone();
+            *req.extensions_mut() = parts.extensions.clone();
+
+            let mut res = send(req).await?;
+            if redirects > 0 {
+                res.extensions_mut().insert(RedirectedUri(parts.uri.clone()));
+            }
+
+            if redirects >= self.options.max_redirects {
+                return Ok(res);
+            }
+            let location = match location(&parts.uri, &res) {
+                Some(location) => location,
+                None => return Ok(res),
+            };
+            if parts.uri.scheme_str() == Some("https") && location.scheme_str() == Some("http") && !self.options.allow_https_to_http {
+                return Ok(res);
+            }
+
+            // RFC 9110 15.4: 303 changes the method to GET (except HEAD). 301 and 302 change POST to GET for compatibility.
+            let status = res.status();
+            let change_to_get = (status == StatusCode::SEE_OTHER && parts.method != Method::HEAD)
+                || ((status == StatusCode::MOVED_PERMANENTLY || status == StatusCode::FOUND) && parts.method == Method::POST);
+            if change_to_get {
+                parts.method = Method::GET;
+                body = None;
+                remove_content_headers(&mut parts.headers);
+            } else if body.as_ref().is_some_and(|body| !body.can_replay()) {
+                return Ok(res);
+            }
+
+            // The credentials must not be sent to another origin.
+            if !is_same_origin(&parts.uri, &location) {
+                parts.headers.remove(header::AUTHORIZATION);
+                parts.headers.remove(header::COOKIE);
+            }
+            parts.headers.remove(header::HOST);
+
+            drop(res);
+            parts.uri = location;
+            redirects += 1;
+        }
+    }
+}
+
+// Returns the URI to follow if the response is a redirect.
+fn location<B>(base: &Uri, res: &Response<B>) -> Option<Uri> {
+    if !matches!(
+        res.status(),
+        StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND | StatusCode::SEE_OTHER | StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT
+    ) {
+        return None;
+    }
+    let location = res.headers().get(header::LOCATION)?.to_str().ok()?;
+    let mut location = url::Url::parse(&base.to_string()).ok()?.join(location).ok()?;
+    if location.scheme() != "http" && location.scheme() != "https" {
+        return None;
+    }
+    location.set_fragment(None);
+    location.as_str().parse().ok()
+}
+
+fn is_same_origin(a: &Uri, b: &Uri) -> bool {
+    fn origin(uri: &Uri) -> (Option<&str>, Option<String>, Option<u16>) {
+        let port = uri.port_u16().or(match uri.scheme_str() {
+            Some("https") => Some(443),
+            Some("http") => Some(80),
+            _ => None,
+        });
+        (uri.scheme_str(), uri.host().map(|host| host.to_ascii_lowercase()), port)
+    }
+    origin(a) == origin(b)
+}
+
+fn remove_content_headers(headers: &mut HeaderMap) {
+    for name in [
+        header::CONTENT_LENGTH,
+        header::CONTENT_TYPE,
+        header::CONTENT_ENCODING,
+        header::CONTENT_LANGUAGE,
+        header::CONTENT_LOCATION,
+        header::TRANSFER_ENCODING,
+    ] {
+        headers.remove(name);
+    }
+}
@@ -0,0 +1,304 @@
+use std::{
+    error::Error as StdError,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use http_body_util::{combinators::BoxBody, BodyExt, Empty};
+use hyper::{
+    body::{Body, Bytes, Frame, Incoming, SizeHint},
+    Method, Request, Response,
+};
+use hyper_util::client;
+use rand::Rng;
+
+use crate::context::{RequestBody, RequestError};
+
+// The request body is kept for replay up to this size. The larger bodies are streamed without buffering, and the requests are not retried.
+const MAX_REPLAY_BODY_SIZE: usize = 64 * 1024;
+
+// The number of retries allowed before the budget is earned by the requests.
+const RETRY_BUDGET_RESERVE: f64 = 10.0;
+
+#[derive(Clone, Debug)]
+pub struct RetryOptions {
+    // The maximum number of attempts including the first one.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    // Retries the requests with non-idempotent methods even if the server may have processed them.
+    pub retry_non_idempotent: bool,
+    // The ratio of retries to requests. Each request earns this amount of the budget, and each retry spends 1.
+    pub budget_ratio: f64,
+}
+
+impl Default for RetryOptions {
+    fn default() -> Self {
+        RetryOptions {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(2),
+            retry_non_idempotent: false,
+            budget_ratio: 0.2,
+        }
+    }
+}
+
+// Whether the server may have processed the failed request.
+#[derive(Debug, PartialEq, Eq)]
+enum FailureKind {
+    // The request was not sent or was rejected before processing. (connection failure, REFUSED_STREAM)
+    Unprocessed,
+    // The connection was lost while the request was in flight. (connection reset, GOAWAY)
+    MaybeProcessed,
+}
+
+/// Retries the requests failed with the transient errors.
+pub struct RetryPolicy {
+    options: RetryOptions,
+    budget: Mutex<f64>,
+}
+
+impl RetryPolicy {
+    pub fn new(options: RetryOptions) -> Self {
+        RetryPolicy {
+            options,
+            budget: Mutex::new(RETRY_BUDGET_RESERVE),
+        }
+    }
+
+    // Sends the request with `send`, and sends it again while it fails with a retryable error.
+    pub async fn execute<F, Fut>(&self, req: Request<RequestBody>, send: F) -> Result<Response<Incoming>, RequestError>
+    where
+        F: Fn(Request<RequestBody>) -> Fut,
+        Fut: Future<Output = Result<Response<Incoming>, RequestError>>,
+    {
+        let (parts, body) = req.into_parts();
+        let body = (!body.is_end_stream()).then(|| ReplayableBody::new(body));
+        self.deposit();
+
+        let mut attempt = 1;
+        loop {
+            let mut req = Request::builder()
+                .method(parts.method.clone())
+                .uri(parts.uri.clone())
+                .version(parts.version)
+                .body(match &body {
+                    Some(body) => BoxBody::new(body.replay()),
+                    None => BoxBody::new(Empty::<Bytes>::new().map_err(|never| match never {})),
+                })
+                .unwrap();
+            *req.headers_mut() = parts.headers.clone();
+            *req.extensions_mut() = parts.extensions.clone();
+
+            let err = match send(req).await {
+                Err(RequestError::Client(err)) => err,
+                result => return result,
+            };
+            let retryable = attempt < self.options.max_attempts
+                && body.as_ref().map(ReplayableBody::can_replay).unwrap_or(true)
+                && self.is_retryable(&parts.method, &err)
+                && self.withdraw();
+            if !retryable {
+                return Err(RequestError::Client(err));
+            }
+
+            tokio::time::sleep(self.backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    fn is_retryable(&self, method: &Method, err: &client::legacy::Error) -> bool {
+        match classify(err) {
+            Some(FailureKind::Unprocessed) => true,
+            Some(FailureKind::MaybeProcessed) => self.options.retry_non_idempotent || is_idempotent(method),
+            None => false,
+        }
+    }
+
+    // Exponential backoff with full jitter.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let max = self.options.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.options.max_backoff);
+        if max.is_zero() {
+            return max;
+        }
+        rand::thread_rng().gen_range(Duration::ZERO..=max)
+    }
+
+    fn deposit(&self) {
+        let mut budget = self.budget.lock().unwrap();
+        *budget = (*budget + self.options.budget_ratio).min(RETRY_BUDGET_RESERVE);
+    }
+
+    fn withdraw(&self) -> bool {
+        let mut budget = self.budget.lock().unwrap();
+        if *budget < 1.0 {
+            return false;
+        }
+        *budget -= 1.0;
+        true
+    }
+}
+
+pub fn is_idempotent(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE | Method::PUT | Method::DELETE)
+}
+
+fn classify(err: &client::legacy::Error) -> Option<FailureKind> {
+    if err.is_connect() {
+        // The permanent failures (e.g. the untrusted certificate, the name not found, the proxy authentication) fail again on retry.
+        return is_transient_connect_error(err).then_some(FailureKind::Unprocessed);
+    }
+
+    let mut source = err.source();
+    while let Some(err) = source {
+        if let Some(err) = err.downcast_ref::<h2::Error>() {
+            if err.reason() == Some(h2::Reason::REFUSED_STREAM) {
+                return Some(FailureKind::Unprocessed);
+            }
+            if err.is_go_away() || err.is_io() {
+                return Some(FailureKind::MaybeProcessed);
+            }
+            return None;
+        }
+        if let Some(err) = err.downcast_ref::<hyper::Error>() {
+            // The pooled connection was closed before the request was written.
+            if err.is_canceled() {
+                return Some(FailureKind::Unprocessed);
+            }
+            if err.is_incomplete_message() {
+                return Some(FailureKind::MaybeProcessed);
+            }
+        }
+        if let Some(err) = err.downcast_ref::<std::io::Error>() {
+            use std::io::ErrorKind;
+            if matches!(err.kind(), ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted | ErrorKind::BrokenPipe | ErrorKind::UnexpectedEof) {
+                return Some(FailureKind::MaybeProcessed);
+            }
+        }
+        source = err.source();
+    }
+    None
+}
+
+// Returns `true` if the connection failed with a transient network error. The first I/O error in the chain is the cause.
+fn is_transient_connect_error(err: &client::legacy::Error) -> bool {
+    let mut source = err.source();
+    while let Some(err) = source {
+        if let Some(err) = err.downcast_ref::<std::io::Error>() {
+            use std::io::ErrorKind;
+            return matches!(err.kind(), ErrorKind::ConnectionRefused | ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted | ErrorKind::TimedOut);
+        }
+        source = err.source();
+    }
+    false
+}
+
+struct BodySource {
+    body: RequestBody,
+    // The chunks read from the body so far. Cleared if the body is larger than `MAX_REPLAY_BODY_SIZE`.
+    chunks: Vec<Bytes>,
+    buffered_size: usize,
+    replayable: bool,
+    ended: bool,
+    // Only the body of the latest attempt reads from the source.
+    generation: usize,
+}
+
+/// Shares the request body between the attempts. The chunks read by the previous attempts are replayed first,
+/// and then the rest of the body is read from the source.
+pub struct ReplayableBody {
+    source: Arc<Mutex<BodySource>>,
+}
+
+impl ReplayableBody {
+    pub fn new(body: RequestBody) -> Self {
+        ReplayableBody {
+            source: Arc::new(Mutex::new(BodySource {
+                body,
+                chunks: Vec::new(),
+                buffered_size: 0,
+                replayable: true,
+                ended: false,
+                generation: 0,
+            })),
+        }
+    }
+
+    pub fn can_replay(&self) -> bool {
+        self.source.lock().unwrap().replayable
+    }
+
+    pub fn replay(&self) -> ReplayBody {
+        let mut source = self.source.lock().unwrap();
+        source.generation += 1;
+        ReplayBody {
+            source: self.source.clone(),
+            generation: source.generation,
+            position: 0,
+        }
+    }
+}
+
+pub struct ReplayBody {
+    source: Arc<Mutex<BodySource>>,
+    generation: usize,
+    position: usize,
+}
+
+impl Body for ReplayBody {
+    type Data = Bytes;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn poll_frame(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let source = self.source.clone();
+        let mut source = source.lock().unwrap();
+        if source.generation != self.generation {
+            // The attempt has been abandoned.
+            return Poll::Ready(None);
+        }
+        if let Some(chunk) = source.chunks.get(self.position) {
+            let chunk = chunk.clone();
+            self.position += 1;
+            return Poll::Ready(Some(Ok(Frame::data(chunk))));
+        }
+        if source.ended {
+            return Poll::Ready(None);
+        }
+
+        let frame = match Pin::new(&mut source.body).poll_frame(cx) {
+            Poll::Ready(Some(Ok(frame))) => frame,
+            Poll::Ready(None) => {
+                source.ended = true;
+                return Poll::Ready(None);
+            }
+            other => return other,
+        };
+        if let (Some(chunk), true) = (frame.data_ref(), source.replayable) {
+            source.buffered_size += chunk.len();
+            if source.buffered_size > MAX_REPLAY_BODY_SIZE {
+                source.replayable = false;
+                source.chunks = Vec::new();
+            } else {
+                let chunk = chunk.clone();
+                source.chunks.push(chunk);
+                self.position += 1;
+            }
+        }
+        Poll::Ready(Some(Ok(frame)))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        let source = self.source.lock().unwrap();
+        source.ended && self.position >= source.chunks.len()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::default()
+    }
+}
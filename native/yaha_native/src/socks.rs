@@ -0,0 +1,201 @@
+use std::{
+    io,
+    net::{IpAddr, SocketAddr},
+};
+
+use hyper::rt;
+use hyper_util::rt::TokioIo;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::proxy::ProxyCredentials;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+const SOCKS5_VERSION: u8 = 0x05;
+const SOCKS5_AUTH_NONE: u8 = 0x00;
+const SOCKS5_AUTH_USERNAME_PASSWORD: u8 = 0x02;
+const SOCKS5_AUTH_NO_ACCEPTABLE_METHODS: u8 = 0xff;
+const SOCKS5_CMD_CONNECT: u8 = 0x01;
+const SOCKS5_ATYP_IPV4: u8 = 0x01;
+const SOCKS5_ATYP_DOMAIN: u8 = 0x03;
+const SOCKS5_ATYP_IPV6: u8 = 0x04;
+
+const SOCKS4_VERSION: u8 = 0x04;
+const SOCKS4_CMD_CONNECT: u8 = 0x01;
+const SOCKS4_REPLY_GRANTED: u8 = 0x5a;
+
+// The destination of the connection through SOCKS proxy.
+pub enum SocksTarget<'a> {
+    Addr(SocketAddr),
+    Domain(&'a str, u16),
+}
+
+impl<'a> SocksTarget<'a> {
+    pub fn new(host: &'a str, port: u16) -> Self {
+        // `Uri::host` returns IPv6 address with square brackets.
+        let trimmed = host.trim_start_matches('[').trim_end_matches(']');
+        match trimmed.parse::<IpAddr>() {
+            Ok(ip) => SocksTarget::Addr(SocketAddr::new(ip, port)),
+            Err(_) => SocksTarget::Domain(host, port),
+        }
+    }
+}
+
+fn socks_error(message: String) -> BoxError {
+    io::Error::other(message).into()
+}
+
+pub async fn connect_socks5<T>(stream: T, target: SocksTarget<'_>, credentials: Option<&ProxyCredentials>) -> Result<T, BoxError>
+where
+    T: rt::Read + rt::Write + Unpin,
+{
+    let mut io = TokioIo::new(stream);
+
+    // Negotiate the authentication method. If the credentials are set, the proxy may still choose no authentication.
+    if credentials.is_some() {
+        io.write_all(&[SOCKS5_VERSION, 2, SOCKS5_AUTH_NONE, SOCKS5_AUTH_USERNAME_PASSWORD]).await?;
+    } else {
+        io.write_all(&[SOCKS5_VERSION, 1, SOCKS5_AUTH_NONE]).await?;
+    }
+
+    let mut reply = [0u8; 2];
+    io.read_exact(&mut reply).await?;
+    if reply[0] != SOCKS5_VERSION {
+        return Err(socks_error(format!("the SOCKS5 proxy returned an unexpected version: {}", reply[0])));
+    }
+    match (reply[1], credentials) {
+        (SOCKS5_AUTH_NONE, _) => {}
+        (SOCKS5_AUTH_USERNAME_PASSWORD, Some(credentials)) => {
+            // RFC 1929: Username/Password Authentication for SOCKS V5
+            let username = credentials.username.as_bytes();
+            let password = credentials.password.as_bytes();
+            if username.len() > 255 || password.len() > 255 {
+                return Err(socks_error("the SOCKS5 username or password is too long".to_string()));
+            }
+
+            let mut request = Vec::with_capacity(3 + username.len() + password.len());
+            request.push(0x01);
+            request.push(username.len() as u8);
+            request.extend_from_slice(username);
+            request.push(password.len() as u8);
+            request.extend_from_slice(password);
+            io.write_all(&request).await?;
+
+            let mut reply = [0u8; 2];
+            io.read_exact(&mut reply).await?;
+            if reply[1] != 0x00 {
+                return Err(socks_error("the SOCKS5 proxy rejected the username and password".to_string()));
+            }
+        }
+        (SOCKS5_AUTH_NO_ACCEPTABLE_METHODS, _) => {
+            return Err(socks_error("the SOCKS5 proxy has no acceptable authentication methods".to_string()));
+        }
+        (method, _) => {
+            return Err(socks_error(format!("the SOCKS5 proxy selected an unsupported authentication method: {}", method)));
+        }
+    }
+
+    // Send CONNECT command.
+    let mut request = vec![SOCKS5_VERSION, SOCKS5_CMD_CONNECT, 0x00];
+    let port = match target {
+        SocksTarget::Addr(SocketAddr::V4(addr)) => {
+            request.push(SOCKS5_ATYP_IPV4);
+            request.extend_from_slice(&addr.ip().octets());
+            addr.port()
+        }
+        SocksTarget::Addr(SocketAddr::V6(addr)) => {
+            request.push(SOCKS5_ATYP_IPV6);
+            request.extend_from_slice(&addr.ip().octets());
+            addr.port()
+        }
+        SocksTarget::Domain(domain, port) => {
+            if domain.len() > 255 {
+                return Err(socks_error("the destination host name is too long for SOCKS5".to_string()));
+            }
+            request.push(SOCKS5_ATYP_DOMAIN);
+            request.push(domain.len() as u8);
+            request.extend_from_slice(domain.as_bytes());
+            port
+        }
+    };
+    request.extend_from_slice(&port.to_be_bytes());
+    io.write_all(&request).await?;
+
+    let mut reply = [0u8; 4];
+    io.read_exact(&mut reply).await?;
+    if reply[1] != 0x00 {
+        let reason = match reply[1] {
+            0x01 => "general SOCKS server failure",
+            0x02 => "connection not allowed by ruleset",
+            0x03 => "network unreachable",
+            0x04 => "host unreachable",
+            0x05 => "connection refused",
+            0x06 => "TTL expired",
+            0x07 => "command not supported",
+            0x08 => "address type not supported",
+            _ => "unknown error",
+        };
+        return Err(socks_error(format!("the SOCKS5 proxy failed to connect: {} ({})", reason, reply[1])));
+    }
+
+    // Discard the bound address.
+    let addr_len = match reply[3] {
+        SOCKS5_ATYP_IPV4 => 4,
+        SOCKS5_ATYP_IPV6 => 16,
+        SOCKS5_ATYP_DOMAIN => io.read_u8().await? as usize,
+        atyp => return Err(socks_error(format!("the SOCKS5 proxy returned an unknown address type: {}", atyp))),
+    };
+    let mut bound = vec![0u8; addr_len + 2];
+    io.read_exact(&mut bound).await?;
+
+    Ok(io.into_inner())
+}
+
+pub async fn connect_socks4a<T>(stream: T, target: SocksTarget<'_>, credentials: Option<&ProxyCredentials>) -> Result<T, BoxError>
+where
+    T: rt::Read + rt::Write + Unpin,
+{
+    let mut io = TokioIo::new(stream);
+
+    let mut request = vec![SOCKS4_VERSION, SOCKS4_CMD_CONNECT];
+    match target {
+        SocksTarget::Addr(SocketAddr::V4(addr)) => {
+            request.extend_from_slice(&addr.port().to_be_bytes());
+            request.extend_from_slice(&addr.ip().octets());
+            push_socks4_user_id(&mut request, credentials);
+        }
+        SocksTarget::Addr(SocketAddr::V6(_)) => {
+            return Err(socks_error("SOCKS4 does not support IPv6 destinations".to_string()));
+        }
+        SocksTarget::Domain(domain, port) => {
+            // SOCKS4a: Set the IP address to 0.0.0.x (x != 0) and append the domain name after the user ID.
+            request.extend_from_slice(&port.to_be_bytes());
+            request.extend_from_slice(&[0, 0, 0, 1]);
+            push_socks4_user_id(&mut request, credentials);
+            request.extend_from_slice(domain.as_bytes());
+            request.push(0x00);
+        }
+    }
+    io.write_all(&request).await?;
+
+    let mut reply = [0u8; 8];
+    io.read_exact(&mut reply).await?;
+    if reply[1] != SOCKS4_REPLY_GRANTED {
+        let reason = match reply[1] {
+            0x5b => "request rejected or failed",
+            0x5c => "the proxy cannot connect to identd on the client",
+            0x5d => "the user ID is not confirmed by identd",
+            _ => "unknown error",
+        };
+        return Err(socks_error(format!("the SOCKS4 proxy failed to connect: {} ({})", reason, reply[1])));
+    }
+
+    Ok(io.into_inner())
+}
+
+fn push_socks4_user_id(request: &mut Vec<u8>, credentials: Option<&ProxyCredentials>) {
+    if let Some(credentials) = credentials {
+        request.extend_from_slice(credentials.username.as_bytes());
+    }
+    request.push(0x00);
+}
@@ -0,0 +1,149 @@
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use hickory_resolver::{config::{ResolverConfig, ResolverOpts}, TokioAsyncResolver};
+use hyper::Uri;
+use rand::Rng;
+use tower_service::Service;
+
+use crate::overrides::to_uri;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Discovers the endpoints of a logical service name (e.g. `_grpc._tcp.svc.internal`) with DNS SRV records (RFC 2782).
+pub struct SrvResolver {
+    resolver: TokioAsyncResolver,
+}
+
+impl SrvResolver {
+    pub fn new(config: ResolverConfig, opts: ResolverOpts) -> Self {
+        SrvResolver {
+            resolver: TokioAsyncResolver::tokio(config, opts),
+        }
+    }
+
+    // Returns whether the host is a service name. The service and protocol labels start with underscore.
+    pub fn is_service_name(host: &str) -> bool {
+        host.starts_with('_')
+    }
+
+    // Returns the targets in the order to try. The targets are ordered by priority, and weighted randomly within the same priority.
+    pub async fn lookup(&self, name: &str) -> Result<Vec<(String, u16)>, BoxError> {
+        let lookup = self.resolver.srv_lookup(name).await?;
+        let records = lookup
+            .iter()
+            // A target of "." means that the service is decidedly not available.
+            .filter(|srv| !srv.target().is_root())
+            .map(|srv| SrvTarget {
+                priority: srv.priority(),
+                weight: srv.weight(),
+                host: srv.target().to_ascii().trim_end_matches('.').to_string(),
+                port: srv.port(),
+            })
+            .collect::<Vec<_>>();
+        if records.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, format!("the service '{}' is not available", name)).into());
+        }
+        Ok(order_targets(records, &mut rand::thread_rng()))
+    }
+}
+
+struct SrvTarget {
+    priority: u16,
+    weight: u16,
+    host: String,
+    port: u16,
+}
+
+fn order_targets(mut records: Vec<SrvTarget>, rng: &mut impl Rng) -> Vec<(String, u16)> {
+    records.sort_by_key(|record| record.priority);
+
+    let mut ordered = Vec::with_capacity(records.len());
+    let mut records = records.into_iter().peekable();
+    while let Some(first) = records.next() {
+        let mut group = vec![first];
+        while let Some(record) = records.next_if(|record| record.priority == group[0].priority) {
+            group.push(record);
+        }
+
+        // The weighted selection of RFC 2782. The records with weight 0 have a very small chance to be selected first.
+        group.sort_by_key(|record| record.weight);
+        while !group.is_empty() {
+            let total: u32 = group.iter().map(|record| record.weight as u32).sum();
+            let selected = rng.gen_range(0..=total);
+            let mut running_sum = 0;
+            let index = group
+                .iter()
+                .position(|record| {
+                    running_sum += record.weight as u32;
+                    running_sum >= selected
+                })
+                .unwrap_or(0);
+            let record = group.remove(index);
+            ordered.push((record.host, record.port));
+        }
+    }
+    ordered
+}
+
+/// A connector that dials the targets of SRV records for the service names.
+///
+/// The destination URI seen by the outer connectors is not changed, so `Host` header and SNI keep the logical service name.
+#[derive(Clone)]
+pub struct SrvConnector<C> {
+    inner: C,
+    resolver: Option<Arc<SrvResolver>>,
+}
+
+impl<C> SrvConnector<C> {
+    pub fn new(inner: C, resolver: Option<Arc<SrvResolver>>) -> Self {
+        SrvConnector { inner, resolver }
+    }
+}
+
+impl<C> Service<Uri> for SrvConnector<C>
+where
+    C: Service<Uri> + Clone + Send + 'static,
+    C::Response: Send,
+    C::Future: Send + 'static,
+    C::Error: Into<BoxError>,
+{
+    type Response = C::Response;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, dst: Uri) -> Self::Future {
+        let resolver = match (&self.resolver, dst.host()) {
+            (Some(resolver), Some(host)) if SrvResolver::is_service_name(host) => resolver.clone(),
+            _ => {
+                let connecting = self.inner.call(dst);
+                return Box::pin(async move { connecting.await.map_err(Into::into) });
+            }
+        };
+
+        let mut inner = self.inner.clone();
+        Box::pin(async move {
+            let name = dst.host().unwrap_or_default();
+            let targets = resolver.lookup(name).await?;
+
+            // Try the targets in order and return the first successful connection.
+            let mut last_error: BoxError = io::Error::other("no targets to connect").into();
+            for (host, port) in targets {
+                match inner.call(to_uri(&host, port)?).await {
+                    Ok(stream) => return Ok(stream),
+                    Err(err) => last_error = err.into(),
+                }
+            }
+            Err(last_error)
+        })
+    }
+}
@@ -0,0 +1,244 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    io,
+    num::NonZeroIsize,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant, SystemTime},
+};
+
+use hyper::Uri;
+use hyper_rustls::MaybeHttpsStream;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::crypto::WebPkiSupportedAlgorithms;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, Error, RootCertStore, SignatureScheme};
+use tokio::sync::oneshot;
+use tower_service::Service;
+
+use crate::interop::StringBuffer;
+use crate::pinning::PinStore;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+// The handler must complete the task with `yaha_complete_server_certificate_verification`.
+pub type OnServerCertificateVerificationAsyncHandler = extern "C" fn(callback_state: NonZeroIsize, server_name: *const u8, server_name_len: usize, certificates: *const StringBuffer, certificates_len: usize, ocsp_response: *const u8, ocsp_response_len: usize, default_verification_error: *const u8, default_verification_error_len: usize, now: u64, task_handle: usize);
+
+// The sender to complete the verification started by the handler. It is passed as `task_handle`.
+pub type VerificationSender = oneshot::Sender<Result<(), String>>;
+
+// The stapled OCSP responses are dropped after this time if the connection doesn't reach the handler, e.g. the handshake failed after the certificates were received.
+const PENDING_OCSP_RESPONSE_LIFETIME: Duration = Duration::from_secs(60);
+
+/// Verifies the server certificates with the asynchronous handler after the TLS handshake.
+///
+/// rustls requires the verifier to return the result synchronously, so the handshake is completed by `DeferredVerifier`
+/// which verifies only the handshake signatures, and `VerifyingConnector` hands the connection to hyper after the handler trusts the server.
+/// No request is sent on the connection until then.
+///
+/// This is fail-open inside rustls by design: the TLS configuration with `DeferredVerifier` establishes a connection to any server,
+/// and only `VerifyingConnector` keeps the untrusted connections from hyper. The configuration must not be used without it,
+/// so the connections to the proxy and the DNS-over-HTTPS server use the standard verification instead.
+/// The early data (0-RTT) is not enabled, so nothing is written to the connection before the handler completes.
+#[derive(Debug)]
+pub struct AsyncCertificateVerification {
+    handler: (OnServerCertificateVerificationAsyncHandler, NonZeroIsize),
+    // The verifier fails to build if there are no valid root certificates.
+    default_verifier: Result<Arc<WebPkiServerVerifier>, String>,
+    supported_algorithms: WebPkiSupportedAlgorithms,
+    timeout: Duration,
+    // The stapled OCSP responses and the time they are received by the end-entity certificate. They can't be retrieved from the connection after the handshake.
+    // Each entry is removed when the connection of the handshake is verified.
+    ocsp_responses: Mutex<HashMap<Vec<u8>, PendingOcspResponse>>,
+}
+
+impl AsyncCertificateVerification {
+    pub fn new(handler: (OnServerCertificateVerificationAsyncHandler, NonZeroIsize), default_verifier: Result<Arc<WebPkiServerVerifier>, String>, timeout: Duration) -> Self {
+        AsyncCertificateVerification {
+            handler,
+            default_verifier,
+            supported_algorithms: rustls::crypto::ring::default_provider().signature_verification_algorithms,
+            timeout,
+            ocsp_responses: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // The verifier for the TLS configuration.
+    pub fn verifier(self: &Arc<Self>) -> Arc<dyn ServerCertVerifier> {
+        Arc::new(DeferredVerifier(self.clone()))
+    }
+
+    async fn verify(&self, server_name: &str, certificates: &[CertificateDer<'static>]) -> Result<(), String> {
+        let (end_entity, intermediates) = certificates.split_first().ok_or("the server presented no certificates")?;
+        let ocsp_response = self.ocsp_responses.lock().unwrap().remove(end_entity.as_ref()).map(|pending| pending.response).unwrap_or_default();
+        let now = UnixTime::now();
+
+        let default_verification_error = match (&self.default_verifier, ServerName::try_from(server_name)) {
+            (Ok(verifier), Ok(name)) => match verifier.verify_server_cert(end_entity, intermediates, &name, &ocsp_response, now) {
+                Ok(_) => String::new(),
+                Err(err) => err.to_string(),
+            },
+            (Err(err), _) => err.clone(),
+            (_, Err(err)) => err.to_string(),
+        };
+
+        let (tx, rx) = oneshot::channel::<Result<(), String>>();
+        {
+            let certificates = certificates
+                .iter()
+                .map(|cert| StringBuffer::new(cert.as_ptr(), cert.len() as i32))
+                .collect::<Vec<_>>();
+            let tx = Box::into_raw(Box::new(tx)) as usize;
+
+            (self.handler.0)(
+                self.handler.1,
+                server_name.as_ptr(),
+                server_name.len(),
+                certificates.as_ptr(),
+                certificates.len(),
+                ocsp_response.as_ptr(),
+                ocsp_response.len(),
+                default_verification_error.as_ptr(),
+                default_verification_error.len(),
+                now.as_secs(),
+                tx,
+            );
+        }
+
+        match tokio::time::timeout(self.timeout, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err("the verification handler was dropped without completion".to_string()),
+            Err(_) => Err(format!("the verification handler didn't complete in {:?}", self.timeout)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PendingOcspResponse {
+    response: Vec<u8>,
+    received_at: Instant,
+}
+
+/// Completes the handshake without trusting the server. The handshake signatures are verified.
+/// It accepts any certificate, so the connections must be verified by `VerifyingConnector` before they are used.
+#[derive(Debug)]
+struct DeferredVerifier(Arc<AsyncCertificateVerification>);
+
+impl ServerCertVerifier for DeferredVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, Error> {
+        let mut ocsp_responses = self.0.ocsp_responses.lock().unwrap();
+        ocsp_responses.retain(|_, pending| pending.received_at.elapsed() < PENDING_OCSP_RESPONSE_LIFETIME);
+        if ocsp_response.is_empty() {
+            ocsp_responses.remove(end_entity.as_ref());
+        } else {
+            ocsp_responses.insert(end_entity.to_vec(), PendingOcspResponse { response: ocsp_response.to_vec(), received_at: Instant::now() });
+        }
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.0.supported_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.0.supported_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.supported_algorithms.supported_schemes()
+    }
+}
+
+/// A connector that verifies the TLS connection after the handshake before returning it.
+/// It waits for `AsyncCertificateVerification` and then checks the public key pins against the certificate path to one of the trusted roots.
+/// The connections are returned as is if neither is configured.
+#[derive(Clone)]
+pub struct VerifyingConnector<C> {
+    inner: C,
+    verification: Option<Arc<AsyncCertificateVerification>>,
+    pin_store: Option<Arc<PinStore>>,
+    // The trust anchors to build the certificate path which the public key pins are checked against.
+    root_store: Arc<RootCertStore>,
+    // The server name used for SNI instead of the host of the URI.
+    override_server_name: Option<String>,
+}
+
+impl<C> VerifyingConnector<C> {
+    pub fn new(inner: C, verification: Option<Arc<AsyncCertificateVerification>>, override_server_name: Option<String>) -> Self {
+        VerifyingConnector { inner, verification, pin_store: None, root_store: Arc::new(RootCertStore::empty()), override_server_name }
+    }
+
+    pub fn with_pin_store(mut self, pin_store: Option<Arc<PinStore>>, root_store: Arc<RootCertStore>) -> Self {
+        self.pin_store = pin_store;
+        self.root_store = root_store;
+        self
+    }
+}
+
+impl<C, T> Service<Uri> for VerifyingConnector<C>
+where
+    C: Service<Uri, Response = MaybeHttpsStream<T>>,
+    C::Future: Send + 'static,
+    C::Error: Into<BoxError>,
+    T: Send + 'static,
+{
+    type Response = MaybeHttpsStream<T>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, dst: Uri) -> Self::Future {
+        let server_name = self.override_server_name
+            .clone()
+            .unwrap_or_else(|| dst.host().unwrap_or_default().trim_start_matches('[').trim_end_matches(']').to_string());
+        let verification = self.verification.clone();
+        let pin_store = self.pin_store.clone();
+        let root_store = self.root_store.clone();
+        let connecting = self.inner.call(dst);
+        Box::pin(async move {
+            let stream = connecting.await.map_err(Into::into)?;
+            if verification.is_none() && pin_store.is_none() {
+                return Ok(stream);
+            }
+            let certificates = match &stream {
+                MaybeHttpsStream::Https(tls) => tls.inner().get_ref().1.peer_certificates().unwrap_or_default().iter().map(|cert| cert.clone().into_owned()).collect::<Vec<_>>(),
+                MaybeHttpsStream::Http(_) => return Ok(stream),
+            };
+            if let Some(verification) = verification {
+                verification
+                    .verify(&server_name, &certificates)
+                    .await
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("the server certificate is not trusted: {}", err)))?;
+            }
+            if let Some(pin_store) = pin_store {
+                pin_store
+                    .verify(&server_name, &certificates, &root_store.roots, SystemTime::now())
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("the server certificate is not trusted: {}", err)))?;
+            }
+            Ok(stream)
+        })
+    }
+}
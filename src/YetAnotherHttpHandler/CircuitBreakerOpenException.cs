@@ -0,0 +1,13 @@
+using System;
+using System.IO;
+
+namespace Cysharp.Net.Http
+{
+    /// <summary>
+    /// The exception that is thrown when a request is not sent because the circuit breaker for the destination is open.
+    /// </summary>
+    public class CircuitBreakerOpenException : IOException
+    {
+        public CircuitBreakerOpenException(string message) : base(message) { }
+    }
+}
@@ -0,0 +1,56 @@
+using System.Net.Http;
+
+namespace Cysharp.Net.Http
+{
+    /// <summary>
+    /// Provides the per-request options of <see cref="YetAnotherHttpHandler"/>.
+    /// </summary>
+    public static class HttpRequestMessageExtensions
+    {
+        private const string BodyCompressionKey = "Cysharp.Net.Http.YetAnotherHttpHandler.BodyCompression";
+#if NET5_0_OR_GREATER
+        private static readonly HttpRequestOptionsKey<BodyCompressionOptions> BodyCompressionOptionsKey = new(BodyCompressionKey);
+#endif
+
+        /// <summary>
+        /// Compresses the request body in the native layer while it is sent.
+        /// </summary>
+        /// <remarks>
+        /// <c>Content-Encoding</c> header is set (or the coding is appended to it), and <c>Content-Length</c> header is removed to send the body in chunks.
+        /// The server must support the content coding of the request body.
+        /// </remarks>
+        /// <param name="request">The request to compress the body.</param>
+        /// <param name="compression">The compression algorithm.</param>
+        /// <param name="level">The compression level. It is clamped to the range of the algorithm. If it is <c>null</c>, the default level of the algorithm is used.</param>
+        public static void SetBodyCompression(this HttpRequestMessage request, RequestBodyCompression compression, int? level = null)
+        {
+            var options = new BodyCompressionOptions(compression, level);
+#if NET5_0_OR_GREATER
+            request.Options.Set(BodyCompressionOptionsKey, options);
+#else
+            request.Properties[BodyCompressionKey] = options;
+#endif
+        }
+
+        internal static BodyCompressionOptions? GetBodyCompression(this HttpRequestMessage request)
+        {
+#if NET5_0_OR_GREATER
+            return request.Options.TryGetValue(BodyCompressionOptionsKey, out var options) ? options : null;
+#else
+            return request.Properties.TryGetValue(BodyCompressionKey, out var options) ? options as BodyCompressionOptions : null;
+#endif
+        }
+
+        internal sealed class BodyCompressionOptions
+        {
+            public RequestBodyCompression Compression { get; }
+            public int? Level { get; }
+
+            public BodyCompressionOptions(RequestBodyCompression compression, int? level)
+            {
+                Compression = compression;
+                Level = level;
+            }
+        }
+    }
+}
@@ -1,7 +1,9 @@
 using System;
 using System.Diagnostics;
+using System.IO;
 using System.Text;
 using System.IO.Pipelines;
+using System.Net;
 using System.Net.Http;
 using System.Net.Http.Headers;
 using System.Runtime.InteropServices;
@@ -22,6 +24,8 @@ namespace Cysharp.Net.Http
         //private unsafe YahaNativeContext* _ctx;
         private readonly YahaContextSafeHandle _handle;
         private GCHandle? _onVerifyServerCertificateHandle; // The handle must be released in Dispose if it is allocated.
+        private GCHandle? _onSelectProxyHandle; // The handle must be released in Dispose if it is allocated.
+        private GCHandle? _onResolveHostNameHandle; // The handle must be released in Dispose if it is allocated.
         private bool _disposed = false;
         private PipeOptions? _responsePipeOptions;
 
@@ -31,6 +35,9 @@ namespace Cysharp.Net.Http
         private static readonly unsafe NativeMethods.yaha_init_context_on_receive_delegate OnReceiveCallback = OnReceive;
         private static readonly unsafe NativeMethods.yaha_init_context_on_complete_delegate OnCompleteCallback = OnComplete;
         private static readonly unsafe NativeMethods.yaha_client_config_set_server_certificate_verification_handler_handler_delegate OnServerCertificateVerificationCallback = OnServerCertificateVerification;
+        private static readonly unsafe NativeMethods.yaha_client_config_set_server_certificate_verification_async_handler_handler_delegate OnServerCertificateVerificationAsyncCallback = OnServerCertificateVerificationAsync;
+        private static readonly unsafe NativeMethods.yaha_client_config_set_proxy_selection_handler_handler_delegate OnProxySelectionCallback = OnProxySelection;
+        private static readonly unsafe NativeMethods.yaha_client_config_set_resolve_handler_handler_delegate OnResolveHostNameCallback = OnResolveHostName;
 
         public unsafe NativeHttpHandlerCore(NativeClientSettings settings)
         {
@@ -80,7 +87,27 @@ namespace Cysharp.Net.Http
                 if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.SkipCertificateVerification)}' = {skipCertificateVerification}");
                 NativeMethods.yaha_client_config_skip_certificate_verification(ctx, skipCertificateVerification);
             }
-            if (settings.OnVerifyServerCertificate is { } onVerifyServerCertificate)
+            if (settings.OnVerifyServerCertificateChainAsync is { } onVerifyServerCertificateChainAsync)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.OnVerifyServerCertificateChainAsync)}' = {onVerifyServerCertificateChainAsync}");
+
+                // NOTE: We need to keep the handle to call in the static callback method.
+                //       The handle must be released in Dispose if it is allocated.
+                _onVerifyServerCertificateHandle = GCHandle.Alloc(onVerifyServerCertificateChainAsync);
+
+                NativeMethods.yaha_client_config_set_server_certificate_verification_async_handler(ctx, OnServerCertificateVerificationAsyncCallback, GCHandle.ToIntPtr(_onVerifyServerCertificateHandle.Value));
+            }
+            else if (settings.OnVerifyServerCertificateChain is { } onVerifyServerCertificateChain)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.OnVerifyServerCertificateChain)}' = {onVerifyServerCertificateChain}");
+
+                // NOTE: We need to keep the handle to call in the static callback method.
+                //       The handle must be released in Dispose if it is allocated.
+                _onVerifyServerCertificateHandle = GCHandle.Alloc(onVerifyServerCertificateChain);
+
+                NativeMethods.yaha_client_config_set_server_certificate_verification_handler(ctx, OnServerCertificateVerificationCallback, GCHandle.ToIntPtr(_onVerifyServerCertificateHandle.Value));
+            }
+            else if (settings.OnVerifyServerCertificate is { } onVerifyServerCertificate)
             {
                 if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.OnVerifyServerCertificate)}' = {onVerifyServerCertificate}");
 
@@ -90,6 +117,11 @@ namespace Cysharp.Net.Http
 
                 NativeMethods.yaha_client_config_set_server_certificate_verification_handler(ctx, OnServerCertificateVerificationCallback, GCHandle.ToIntPtr(_onVerifyServerCertificateHandle.Value));
             }
+            if (settings.ServerCertificateVerificationTimeout is { } serverCertificateVerificationTimeout)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.ServerCertificateVerificationTimeout)}' = {serverCertificateVerificationTimeout}");
+                NativeMethods.yaha_client_config_server_certificate_verification_timeout(ctx, (ulong)serverCertificateVerificationTimeout.TotalMilliseconds);
+            }
             if (settings.RootCertificates is { } rootCertificates)
             {
                 if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.RootCertificates)}' = Length:{rootCertificates.Length}");
@@ -101,6 +133,69 @@ namespace Cysharp.Net.Http
                     if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"yaha_client_config_add_root_certificates: ValidCertificatesCount={validCertificatesCount}");
                 }
             }
+            if (settings.UseSystemRootCertificates is { } useSystemRootCertificates)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.UseSystemRootCertificates)}' = {useSystemRootCertificates}");
+                NativeMethods.yaha_client_config_use_system_root_certificates(ctx, useSystemRootCertificates);
+            }
+            if (settings.UseBuiltInRootCertificates is { } useBuiltInRootCertificates)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.UseBuiltInRootCertificates)}' = {useBuiltInRootCertificates}");
+                NativeMethods.yaha_client_config_use_built_in_root_certificates(ctx, useBuiltInRootCertificates);
+            }
+            if (settings.CertificateRevocationLists is { } certificateRevocationLists)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.CertificateRevocationLists)}' = Count:{certificateRevocationLists.Count}");
+                for (var i = 0; i < certificateRevocationLists.Count; i++)
+                {
+                    AddCertificateRevocationLists(ctx, certificateRevocationLists[i], $"{nameof(settings.CertificateRevocationLists)}[{i}]");
+                }
+            }
+            if (settings.CertificateRevocationListFiles is { } certificateRevocationListFiles)
+            {
+                foreach (var path in certificateRevocationListFiles)
+                {
+                    if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.CertificateRevocationListFiles)}' = {path}");
+                    AddCertificateRevocationLists(ctx, File.ReadAllBytes(path), path);
+                }
+            }
+            if (settings.RevocationCheckDepth is { } revocationCheckDepth)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.RevocationCheckDepth)}' = {revocationCheckDepth}");
+                if (!NativeMethods.yaha_client_config_revocation_check_depth(ctx, (int)revocationCheckDepth))
+                {
+                    throw new ArgumentOutOfRangeException(nameof(settings.RevocationCheckDepth), revocationCheckDepth, "The revocation check depth is not supported.");
+                }
+            }
+            if (settings.UnknownRevocationStatusPolicy is { } unknownRevocationStatusPolicy)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.UnknownRevocationStatusPolicy)}' = {unknownRevocationStatusPolicy}");
+                if (!NativeMethods.yaha_client_config_unknown_revocation_status_policy(ctx, (int)unknownRevocationStatusPolicy))
+                {
+                    throw new ArgumentOutOfRangeException(nameof(settings.UnknownRevocationStatusPolicy), unknownRevocationStatusPolicy, "The unknown revocation status policy is not supported.");
+                }
+            }
+            if (settings.PublicKeyPins is { } publicKeyPins)
+            {
+                foreach (var publicKeyPinSet in publicKeyPins)
+                {
+                    if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.PublicKeyPins)}' = {publicKeyPinSet}");
+                    var hostPatternBytes = Encoding.UTF8.GetBytes(publicKeyPinSet.HostPattern);
+                    var pinsBytes = Encoding.UTF8.GetBytes(string.Join(",", publicKeyPinSet.Pins));
+                    fixed (byte* hostPatternBuffer = hostPatternBytes)
+                    fixed (byte* pinsBuffer = pinsBytes)
+                    {
+                        var hostPatternSb = new StringBuffer(hostPatternBuffer, hostPatternBytes.Length);
+                        var pinsSb = new StringBuffer(pinsBuffer, pinsBytes.Length);
+                        // NOTE: 0 means the pins never expire.
+                        var expiresAt = publicKeyPinSet.ExpiresAt is { } expiresAtValue ? (ulong)Math.Max(1, expiresAtValue.ToUnixTimeSeconds()) : 0;
+                        if (!NativeMethods.yaha_client_config_add_public_key_pins(ctx, &hostPatternSb, &pinsSb, expiresAt))
+                        {
+                            throw new ArgumentException($"The public key pins '{publicKeyPinSet}' are invalid.", nameof(settings.PublicKeyPins));
+                        }
+                    }
+                }
+            }
             if (settings.OverrideServerName is { } overrideServerName)
             {
                 if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.OverrideServerName)}' = {overrideServerName}");
@@ -161,6 +256,365 @@ namespace Cysharp.Net.Http
                 if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.ConnectTimeout)}' = {connectTimeout}");
                 NativeMethods.yaha_client_config_connect_timeout(ctx, (ulong)connectTimeout.TotalMilliseconds);
             }
+            if (settings.AddressFamilyPreference is { } addressFamilyPreference)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.AddressFamilyPreference)}' = {addressFamilyPreference}");
+                if (!NativeMethods.yaha_client_config_address_family_preference(ctx, (int)addressFamilyPreference))
+                {
+                    throw new ArgumentOutOfRangeException(nameof(settings.AddressFamilyPreference), addressFamilyPreference, "The address family preference is not supported.");
+                }
+            }
+            if (settings.HappyEyeballsFallbackDelay is { } happyEyeballsFallbackDelay)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.HappyEyeballsFallbackDelay)}' = {happyEyeballsFallbackDelay}");
+                NativeMethods.yaha_client_config_happy_eyeballs_fallback_delay(ctx, (ulong)happyEyeballsFallbackDelay.TotalMilliseconds);
+            }
+            if (settings.LoadBalancingPolicy is { } loadBalancingPolicy)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.LoadBalancingPolicy)}' = {loadBalancingPolicy}");
+                if (!NativeMethods.yaha_client_config_load_balancing_policy(ctx, (int)loadBalancingPolicy))
+                {
+                    throw new ArgumentOutOfRangeException(nameof(settings.LoadBalancingPolicy), loadBalancingPolicy, "The load balancing policy is not supported.");
+                }
+            }
+            if (settings.LoadBalancingReResolveInterval is { } loadBalancingReResolveInterval)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.LoadBalancingReResolveInterval)}' = {loadBalancingReResolveInterval}");
+                NativeMethods.yaha_client_config_load_balancing_re_resolve_interval(ctx, (ulong)loadBalancingReResolveInterval.TotalMilliseconds);
+            }
+            if (settings.EnableCircuitBreaker is { } enableCircuitBreaker)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.EnableCircuitBreaker)}' = {enableCircuitBreaker}");
+                NativeMethods.yaha_client_config_circuit_breaker(ctx, enableCircuitBreaker);
+            }
+            if (settings.CircuitBreakerConsecutiveFailures is { } circuitBreakerConsecutiveFailures)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.CircuitBreakerConsecutiveFailures)}' = {circuitBreakerConsecutiveFailures}");
+                NativeMethods.yaha_client_config_circuit_breaker_consecutive_failures(ctx, circuitBreakerConsecutiveFailures);
+            }
+            if (settings.CircuitBreakerFailureRate is { } circuitBreakerFailureRate)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.CircuitBreakerFailureRate)}' = {circuitBreakerFailureRate}");
+                if (circuitBreakerFailureRate < 0 || circuitBreakerFailureRate > 1)
+                {
+                    throw new ArgumentException("The failure rate must be between 0 and 1.", nameof(settings.CircuitBreakerFailureRate));
+                }
+                NativeMethods.yaha_client_config_circuit_breaker_failure_rate(ctx, circuitBreakerFailureRate);
+            }
+            if (settings.CircuitBreakerSamplingRequests is { } circuitBreakerSamplingRequests)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.CircuitBreakerSamplingRequests)}' = {circuitBreakerSamplingRequests}");
+                NativeMethods.yaha_client_config_circuit_breaker_sampling_requests(ctx, (nuint)circuitBreakerSamplingRequests);
+            }
+            if (settings.CircuitBreakerCooldown is { } circuitBreakerCooldown)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.CircuitBreakerCooldown)}' = {circuitBreakerCooldown}");
+                NativeMethods.yaha_client_config_circuit_breaker_cooldown(ctx, (ulong)circuitBreakerCooldown.TotalMilliseconds);
+            }
+            if (settings.EnableRetry is { } enableRetry)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.EnableRetry)}' = {enableRetry}");
+                NativeMethods.yaha_client_config_retry(ctx, enableRetry);
+            }
+            if (settings.RetryMaxAttempts is { } retryMaxAttempts)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.RetryMaxAttempts)}' = {retryMaxAttempts}");
+                NativeMethods.yaha_client_config_retry_max_attempts(ctx, retryMaxAttempts);
+            }
+            if (settings.RetryInitialBackoff is { } retryInitialBackoff)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.RetryInitialBackoff)}' = {retryInitialBackoff}");
+                NativeMethods.yaha_client_config_retry_initial_backoff(ctx, (ulong)retryInitialBackoff.TotalMilliseconds);
+            }
+            if (settings.RetryMaxBackoff is { } retryMaxBackoff)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.RetryMaxBackoff)}' = {retryMaxBackoff}");
+                NativeMethods.yaha_client_config_retry_max_backoff(ctx, (ulong)retryMaxBackoff.TotalMilliseconds);
+            }
+            if (settings.RetryNonIdempotentRequests is { } retryNonIdempotentRequests)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.RetryNonIdempotentRequests)}' = {retryNonIdempotentRequests}");
+                NativeMethods.yaha_client_config_retry_non_idempotent_requests(ctx, retryNonIdempotentRequests);
+            }
+            if (settings.RetryBudgetRatio is { } retryBudgetRatio)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.RetryBudgetRatio)}' = {retryBudgetRatio}");
+                if (retryBudgetRatio < 0)
+                {
+                    throw new ArgumentException("The retry budget ratio must not be negative.", nameof(settings.RetryBudgetRatio));
+                }
+                NativeMethods.yaha_client_config_retry_budget_ratio(ctx, retryBudgetRatio);
+            }
+            if (settings.HedgingDelay is { } hedgingDelay)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.HedgingDelay)}' = {hedgingDelay}");
+                NativeMethods.yaha_client_config_hedging_delay(ctx, (ulong)hedgingDelay.TotalMilliseconds);
+            }
+            if (settings.HedgingMaxAttempts is { } hedgingMaxAttempts)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.HedgingMaxAttempts)}' = {hedgingMaxAttempts}");
+                NativeMethods.yaha_client_config_hedging_max_attempts(ctx, hedgingMaxAttempts);
+            }
+            if (settings.AllowAutoRedirect is { } allowAutoRedirect)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.AllowAutoRedirect)}' = {allowAutoRedirect}");
+                NativeMethods.yaha_client_config_redirect(ctx, allowAutoRedirect);
+            }
+            if (settings.MaxAutomaticRedirections is { } maxAutomaticRedirections)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.MaxAutomaticRedirections)}' = {maxAutomaticRedirections}");
+                if (maxAutomaticRedirections <= 0)
+                {
+                    throw new ArgumentException("The maximum number of redirects must be greater than zero.", nameof(settings.MaxAutomaticRedirections));
+                }
+                NativeMethods.yaha_client_config_max_redirects(ctx, (uint)maxAutomaticRedirections);
+            }
+            if (settings.AllowHttpsToHttpRedirect is { } allowHttpsToHttpRedirect)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.AllowHttpsToHttpRedirect)}' = {allowHttpsToHttpRedirect}");
+                NativeMethods.yaha_client_config_allow_https_to_http_redirect(ctx, allowHttpsToHttpRedirect);
+            }
+            if (settings.UseCookies is { } useCookies)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.UseCookies)}' = {useCookies}");
+                NativeMethods.yaha_client_config_cookie_jar(ctx, useCookies);
+            }
+            if (settings.CookieFilePath is { } cookieFilePath)
+            {
+                var cookieFileFormat = settings.CookieFileFormat ?? CookieFileFormat.Json;
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.CookieFilePath)}' = {cookieFilePath} ({cookieFileFormat})");
+                var strBytes = Encoding.UTF8.GetBytes(cookieFilePath);
+                fixed (byte* buffer = strBytes)
+                {
+                    var sb = new StringBuffer(buffer, strBytes.Length);
+                    if (!NativeMethods.yaha_client_config_cookie_file(ctx, &sb, (int)cookieFileFormat))
+                    {
+                        throw new ArgumentException($"The cookie file '{cookieFilePath}' can't be loaded as {cookieFileFormat}.", nameof(settings.CookieFilePath));
+                    }
+                }
+            }
+            if (settings.CookiePublicSuffixListPath is { } cookiePublicSuffixListPath)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.CookiePublicSuffixListPath)}' = {cookiePublicSuffixListPath}");
+                var strBytes = Encoding.UTF8.GetBytes(cookiePublicSuffixListPath);
+                fixed (byte* buffer = strBytes)
+                {
+                    var sb = new StringBuffer(buffer, strBytes.Length);
+                    if (!NativeMethods.yaha_client_config_cookie_public_suffix_list(ctx, &sb))
+                    {
+                        throw new ArgumentException($"The public suffix list '{cookiePublicSuffixListPath}' can't be loaded.", nameof(settings.CookiePublicSuffixListPath));
+                    }
+                }
+            }
+            if (settings.EnableHttpCache is { } enableHttpCache)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.EnableHttpCache)}' = {enableHttpCache}");
+                NativeMethods.yaha_client_config_http_cache(ctx, enableHttpCache);
+            }
+            if (settings.HttpCacheDirectory is { } httpCacheDirectory)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.HttpCacheDirectory)}' = {httpCacheDirectory}");
+                var strBytes = Encoding.UTF8.GetBytes(httpCacheDirectory);
+                fixed (byte* buffer = strBytes)
+                {
+                    var sb = new StringBuffer(buffer, strBytes.Length);
+                    if (!NativeMethods.yaha_client_config_http_cache_directory(ctx, &sb))
+                    {
+                        throw new ArgumentException($"The cache directory '{httpCacheDirectory}' can't be created.", nameof(settings.HttpCacheDirectory));
+                    }
+                }
+            }
+            if (settings.HttpCacheMaxSize is { } httpCacheMaxSize)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.HttpCacheMaxSize)}' = {httpCacheMaxSize}");
+                NativeMethods.yaha_client_config_http_cache_max_size(ctx, httpCacheMaxSize);
+            }
+            if (settings.HttpCacheMaxEntrySize is { } httpCacheMaxEntrySize)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.HttpCacheMaxEntrySize)}' = {httpCacheMaxEntrySize}");
+                NativeMethods.yaha_client_config_http_cache_max_entry_size(ctx, httpCacheMaxEntrySize);
+            }
+            if (settings.EnableAutomaticDecompression is { } enableAutomaticDecompression)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.EnableAutomaticDecompression)}' = {enableAutomaticDecompression}");
+                NativeMethods.yaha_client_config_decompression(ctx, enableAutomaticDecompression);
+            }
+            if (settings.Proxy is { } proxy)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.Proxy)}' = {proxy}");
+                var strBytes = Encoding.UTF8.GetBytes(proxy);
+                fixed (byte* buffer = strBytes)
+                {
+                    var sb = new StringBuffer(buffer, strBytes.Length);
+                    if (!NativeMethods.yaha_client_config_proxy(ctx, &sb))
+                    {
+                        throw new ArgumentException($"The proxy URI '{proxy}' is invalid or not supported.", nameof(settings.Proxy));
+                    }
+                }
+            }
+            if (settings.UseEnvironmentProxy is { } useEnvironmentProxy)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.UseEnvironmentProxy)}' = {useEnvironmentProxy}");
+                NativeMethods.yaha_client_config_use_environment_proxy(ctx, useEnvironmentProxy);
+            }
+            if (settings.OnSelectProxy is { } onSelectProxy)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.OnSelectProxy)}' = {onSelectProxy}");
+
+                // NOTE: We need to keep the handle to call in the static callback method.
+                //       The handle must be released in Dispose if it is allocated.
+                _onSelectProxyHandle = GCHandle.Alloc(onSelectProxy);
+
+                NativeMethods.yaha_client_config_set_proxy_selection_handler(ctx, OnProxySelectionCallback, GCHandle.ToIntPtr(_onSelectProxyHandle.Value));
+            }
+            if (settings.ProxyCredentials is { } proxyCredentials)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.ProxyCredentials)}' = UserName:{proxyCredentials.UserName}");
+                var userNameBytes = Encoding.UTF8.GetBytes(proxyCredentials.UserName);
+                var passwordBytes = Encoding.UTF8.GetBytes(proxyCredentials.Password);
+                fixed (byte* userNameBuffer = userNameBytes)
+                fixed (byte* passwordBuffer = passwordBytes)
+                {
+                    var userNameSb = new StringBuffer(userNameBuffer, userNameBytes.Length);
+                    var passwordSb = new StringBuffer(passwordBuffer, passwordBytes.Length);
+                    NativeMethods.yaha_client_config_add_proxy_basic_credentials(ctx, &userNameSb, &passwordSb);
+                }
+            }
+            if (settings.ProxyBearerToken is { } proxyBearerToken)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.ProxyBearerToken)}' = Length:{proxyBearerToken.Length}");
+                var strBytes = Encoding.UTF8.GetBytes(proxyBearerToken);
+                fixed (byte* buffer = strBytes)
+                {
+                    var sb = new StringBuffer(buffer, strBytes.Length);
+                    NativeMethods.yaha_client_config_add_proxy_bearer_token(ctx, &sb);
+                }
+            }
+            if (settings.ResolveOverrides is { } resolveOverrides)
+            {
+                foreach (var resolveOverride in resolveOverrides)
+                {
+                    if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.ResolveOverrides)}' = {resolveOverride.Key}: {string.Join(", ", resolveOverride.Value)}");
+                    var hostPortBytes = Encoding.UTF8.GetBytes(resolveOverride.Key);
+                    foreach (var address in resolveOverride.Value)
+                    {
+                        var addressBytes = Encoding.UTF8.GetBytes(address);
+                        fixed (byte* hostPortBuffer = hostPortBytes)
+                        fixed (byte* addressBuffer = addressBytes)
+                        {
+                            var hostPortSb = new StringBuffer(hostPortBuffer, hostPortBytes.Length);
+                            var addressSb = new StringBuffer(addressBuffer, addressBytes.Length);
+                            if (!NativeMethods.yaha_client_config_add_resolve_override(ctx, &hostPortSb, &addressSb))
+                            {
+                                throw new ArgumentException($"The resolve override '{resolveOverride.Key}' = '{address}' is invalid.", nameof(settings.ResolveOverrides));
+                            }
+                        }
+                    }
+                }
+            }
+            if (settings.ConnectTo is { } connectTo)
+            {
+                foreach (var connectToEntry in connectTo)
+                {
+                    if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.ConnectTo)}' = {connectToEntry.Key}: {connectToEntry.Value}");
+                    var fromBytes = Encoding.UTF8.GetBytes(connectToEntry.Key);
+                    var toBytes = Encoding.UTF8.GetBytes(connectToEntry.Value);
+                    fixed (byte* fromBuffer = fromBytes)
+                    fixed (byte* toBuffer = toBytes)
+                    {
+                        var fromSb = new StringBuffer(fromBuffer, fromBytes.Length);
+                        var toSb = new StringBuffer(toBuffer, toBytes.Length);
+                        if (!NativeMethods.yaha_client_config_add_connect_to(ctx, &fromSb, &toSb))
+                        {
+                            throw new ArgumentException($"The connect-to '{connectToEntry.Key}' = '{connectToEntry.Value}' is invalid.", nameof(settings.ConnectTo));
+                        }
+                    }
+                }
+            }
+            if (settings.OnResolveHostName is { } onResolveHostName)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.OnResolveHostName)}' = {onResolveHostName}");
+
+                // NOTE: We need to keep the handle to call in the static callback method.
+                //       The handle must be released in Dispose if it is allocated.
+                _onResolveHostNameHandle = GCHandle.Alloc(onResolveHostName);
+
+                NativeMethods.yaha_client_config_set_resolve_handler(ctx, OnResolveHostNameCallback, GCHandle.ToIntPtr(_onResolveHostNameHandle.Value));
+            }
+            if (settings.UseBuiltInDnsResolver is { } useBuiltInDnsResolver)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.UseBuiltInDnsResolver)}' = {useBuiltInDnsResolver}");
+                NativeMethods.yaha_client_config_use_built_in_dns_resolver(ctx, useBuiltInDnsResolver);
+            }
+            if (settings.DnsServers is { } dnsServers)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.DnsServers)}' = {string.Join<IPEndPoint>(", ", dnsServers)}");
+                foreach (var dnsServer in dnsServers)
+                {
+                    var addressBytes = Encoding.UTF8.GetBytes(new IPEndPoint(dnsServer.Address, dnsServer.Port == 0 ? 53 : dnsServer.Port).ToString());
+                    fixed (byte* addressBuffer = addressBytes)
+                    {
+                        var addressSb = new StringBuffer(addressBuffer, addressBytes.Length);
+                        NativeMethods.yaha_client_config_add_dns_server(ctx, &addressSb);
+                    }
+                }
+            }
+            if (settings.EnableSrvDiscovery is { } enableSrvDiscovery)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.EnableSrvDiscovery)}' = {enableSrvDiscovery}");
+                NativeMethods.yaha_client_config_srv_discovery(ctx, enableSrvDiscovery);
+            }
+            if (settings.DnsOverHttpsEndpoint is { } dnsOverHttpsEndpoint)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.DnsOverHttpsEndpoint)}' = {dnsOverHttpsEndpoint}");
+                var endpointBytes = Encoding.UTF8.GetBytes(dnsOverHttpsEndpoint.AbsoluteUri);
+                fixed (byte* endpointBuffer = endpointBytes)
+                {
+                    var endpointSb = new StringBuffer(endpointBuffer, endpointBytes.Length);
+                    if (!NativeMethods.yaha_client_config_dns_over_https_endpoint(ctx, &endpointSb))
+                    {
+                        throw new ArgumentException($"The DNS-over-HTTPS endpoint '{dnsOverHttpsEndpoint}' is invalid. It must be an absolute HTTPS URI, and DNS-over-HTTPS requires the rustls build of the native library.", nameof(settings.DnsOverHttpsEndpoint));
+                    }
+                }
+            }
+            if (settings.DnsOverHttpsBootstrapAddresses is { } dnsOverHttpsBootstrapAddresses)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.DnsOverHttpsBootstrapAddresses)}' = {string.Join<IPAddress>(", ", dnsOverHttpsBootstrapAddresses)}");
+                foreach (var address in dnsOverHttpsBootstrapAddresses)
+                {
+                    var addressBytes = Encoding.UTF8.GetBytes(address.ToString());
+                    fixed (byte* addressBuffer = addressBytes)
+                    {
+                        var addressSb = new StringBuffer(addressBuffer, addressBytes.Length);
+                        NativeMethods.yaha_client_config_add_dns_over_https_bootstrap_address(ctx, &addressSb);
+                    }
+                }
+            }
+            if (settings.EnableDnsCache is { } enableDnsCache)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.EnableDnsCache)}' = {enableDnsCache}");
+                NativeMethods.yaha_client_config_dns_cache(ctx, enableDnsCache);
+            }
+            if (settings.DnsCacheDefaultTtl is { } dnsCacheDefaultTtl)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.DnsCacheDefaultTtl)}' = {dnsCacheDefaultTtl}");
+                NativeMethods.yaha_client_config_dns_cache_default_ttl(ctx, (ulong)dnsCacheDefaultTtl.TotalMilliseconds);
+            }
+            if (settings.DnsCacheMaxTtl is { } dnsCacheMaxTtl)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.DnsCacheMaxTtl)}' = {dnsCacheMaxTtl}");
+                NativeMethods.yaha_client_config_dns_cache_max_ttl(ctx, (ulong)dnsCacheMaxTtl.TotalMilliseconds);
+            }
+            if (settings.DnsCacheNegativeTtl is { } dnsCacheNegativeTtl)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.DnsCacheNegativeTtl)}' = {dnsCacheNegativeTtl}");
+                NativeMethods.yaha_client_config_dns_cache_negative_ttl(ctx, (ulong)dnsCacheNegativeTtl.TotalMilliseconds);
+            }
+            if (settings.DnsCacheStaleWhileRevalidate is { } dnsCacheStaleWhileRevalidate)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.DnsCacheStaleWhileRevalidate)}' = {dnsCacheStaleWhileRevalidate}");
+                NativeMethods.yaha_client_config_dns_cache_stale_while_revalidate(ctx, (ulong)dnsCacheStaleWhileRevalidate.TotalMilliseconds);
+            }
             if (settings.Http2KeepAliveInterval is { } http2KeepAliveInterval)
             {
                 if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Option '{nameof(settings.Http2KeepAliveInterval)}' = {http2KeepAliveInterval}");
@@ -217,6 +671,44 @@ namespace Cysharp.Net.Http
             if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"{nameof(NativeHttpHandlerCore)} created");
         }
 
+        private static unsafe void AddCertificateRevocationLists(YahaNativeContext* ctx, byte[] crls, string source)
+        {
+            // NOTE: The revocation status must not be skipped silently, so the invalid CRLs are rejected.
+            var validCrlsCount = (nuint)0;
+            if (crls.Length > 0)
+            {
+                fixed (byte* buffer = crls)
+                {
+                    var sb = new StringBuffer(buffer, crls.Length);
+                    validCrlsCount = NativeMethods.yaha_client_config_add_certificate_revocation_lists(ctx, &sb);
+                    if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"yaha_client_config_add_certificate_revocation_lists: Source={source}; ValidCrlsCount={validCrlsCount}");
+                }
+            }
+            if (validCrlsCount == 0)
+            {
+                throw new ArgumentException($"The certificate revocation list '{source}' is invalid.", nameof(NativeClientSettings.CertificateRevocationLists));
+            }
+        }
+
+        public unsafe void FlushDnsCache()
+        {
+            if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"yaha_client_flush_dns_cache");
+
+            var addRefContext = false;
+            try
+            {
+                _handle.DangerousAddRef(ref addRefContext);
+                NativeMethods.yaha_client_flush_dns_cache(_handle.DangerousGet());
+            }
+            finally
+            {
+                if (addRefContext)
+                {
+                    _handle.DangerousRelease();
+                }
+            }
+        }
+
         public Task<HttpResponseMessage> SendAsync(HttpRequestMessage request, CancellationToken cancellationToken)
         {
             if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"HttpMessageHandler.SendAsync: {request.RequestUri}; Method={request.Method}; Version={request.Version}");
@@ -339,6 +831,17 @@ namespace Cysharp.Net.Http
             // Prepare body channel
             NativeMethods.yaha_request_set_has_body(ctx, reqCtx, request.Content != null);
 
+            // Compress the request body
+            if (request.Content != null && request.GetBodyCompression() is { } bodyCompression)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"[ReqSeq:{requestSequence}] Compress the request body: Compression={bodyCompression.Compression}; Level={bodyCompression.Level}");
+                ThrowHelper.ThrowIfFailed(ctx, reqCtx, NativeMethods.yaha_request_set_body_compression(ctx, reqCtx, (int)bodyCompression.Compression));
+                if (bodyCompression.Level is { } level)
+                {
+                    ThrowHelper.ThrowIfFailed(ctx, reqCtx, NativeMethods.yaha_request_set_body_compression_level(ctx, reqCtx, level));
+                }
+            }
+
             // Prepare a request context
             var requestContextManaged = new RequestContext(_handle, reqCtxHandle, request, requestSequence, _responsePipeOptions, cancellationToken);
             requestContextManaged.Allocate();
@@ -419,6 +922,20 @@ namespace Cysharp.Net.Http
                         }
                     }
                 }
+
+                // The request has been redirected to the URI.
+                var bufUri = NativeMethods.yaha_request_response_get_uri(ctx, reqCtx);
+                if (bufUri != null)
+                {
+                    try
+                    {
+                        requestContext.Response.SetRequestUri(UnsafeUtilities.GetStringFromUtf8Bytes(bufUri->AsSpan()));
+                    }
+                    finally
+                    {
+                        NativeMethods.yaha_free_byte_buffer(bufUri);
+                    }
+                }
             }
             finally
             {
@@ -436,22 +953,36 @@ namespace Cysharp.Net.Http
         }
 
         [MonoPInvokeCallback(typeof(NativeMethods.yaha_client_config_set_server_certificate_verification_handler_handler_delegate))]
-        private static unsafe bool OnServerCertificateVerification(IntPtr callbackState, byte* serverNamePtr, UIntPtr /*nuint*/ serverNameLength, byte* certificateDerPtr, UIntPtr /*nuint*/ certificateDerLength, ulong now)
+        private static unsafe bool OnServerCertificateVerification(IntPtr callbackState, byte* serverNamePtr, UIntPtr /*nuint*/ serverNameLength, StringBuffer* certificatesPtr, UIntPtr /*nuint*/ certificatesLength, byte* ocspResponsePtr, UIntPtr /*nuint*/ ocspResponseLength, byte* defaultVerificationErrorPtr, UIntPtr /*nuint*/ defaultVerificationErrorLength, ulong now)
         {
             var serverName = UnsafeUtilities.GetStringFromUtf8Bytes(new ReadOnlySpan<byte>(serverNamePtr, (int)serverNameLength));
-            var certificateDer = new ReadOnlySpan<byte>(certificateDerPtr, (int)certificateDerLength);
-            if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Trace($"OnServerCertificateVerification: State=0x{callbackState:X}; ServerName={serverName}; CertificateDer.Length={certificateDer.Length}; Now={now}");
+            var certificateDer = new ReadOnlySpan<byte>(certificatesPtr[0].ptr, certificatesPtr[0].length);
+            var defaultVerificationError = (int)defaultVerificationErrorLength == 0 ? null : UnsafeUtilities.GetStringFromUtf8Bytes(new ReadOnlySpan<byte>(defaultVerificationErrorPtr, (int)defaultVerificationErrorLength));
+            if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Trace($"OnServerCertificateVerification: State=0x{callbackState:X}; ServerName={serverName}; CertificateDer.Length={certificateDer.Length}; Certificates={certificatesLength}; OcspResponse.Length={ocspResponseLength}; DefaultVerificationError={defaultVerificationError}; Now={now}");
 
-            var onServerCertificateVerification = (ServerCertificateVerificationHandler?)GCHandle.FromIntPtr(callbackState).Target;
-            Debug.Assert(onServerCertificateVerification != null);
-            if (onServerCertificateVerification == null)
-            {
-                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Warning($"OnServerVerification: The verification callback was called, but onServerCertificateVerification is null.");
-                return false;
-            }
+            var target = GCHandle.FromIntPtr(callbackState).Target;
+            Debug.Assert(target != null);
             try
             {
-                var success = onServerCertificateVerification(serverName, certificateDer, DateTimeOffset.FromUnixTimeSeconds((long)now));
+                bool success;
+                switch (target)
+                {
+                    case ServerCertificateChainVerificationHandler onServerCertificateChainVerification:
+                        var certificates = new byte[(int)certificatesLength][];
+                        for (var i = 0; i < certificates.Length; i++)
+                        {
+                            certificates[i] = new ReadOnlySpan<byte>(certificatesPtr[i].ptr, certificatesPtr[i].length).ToArray();
+                        }
+                        var ocspResponse = new ReadOnlySpan<byte>(ocspResponsePtr, (int)ocspResponseLength).ToArray();
+                        success = onServerCertificateChainVerification(new ServerCertificateVerificationContext(serverName, certificates, ocspResponse, DateTimeOffset.FromUnixTimeSeconds((long)now), defaultVerificationError));
+                        break;
+                    case ServerCertificateVerificationHandler onServerCertificateVerification:
+                        success = onServerCertificateVerification(serverName, certificateDer, DateTimeOffset.FromUnixTimeSeconds((long)now));
+                        break;
+                    default:
+                        if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Warning($"OnServerVerification: The verification callback was called, but onServerCertificateVerification is null.");
+                        return false;
+                }
                 if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Trace($"OnServerVerification: Success = {success}");
                 return success;
             }
@@ -462,6 +993,157 @@ namespace Cysharp.Net.Http
             }
         }
 
+        [MonoPInvokeCallback(typeof(NativeMethods.yaha_client_config_set_server_certificate_verification_async_handler_handler_delegate))]
+        private static unsafe void OnServerCertificateVerificationAsync(IntPtr callbackState, byte* serverNamePtr, UIntPtr /*nuint*/ serverNameLength, StringBuffer* certificatesPtr, UIntPtr /*nuint*/ certificatesLength, byte* ocspResponsePtr, UIntPtr /*nuint*/ ocspResponseLength, byte* defaultVerificationErrorPtr, UIntPtr /*nuint*/ defaultVerificationErrorLength, ulong now, nuint taskHandle)
+        {
+            var serverName = UnsafeUtilities.GetStringFromUtf8Bytes(new ReadOnlySpan<byte>(serverNamePtr, (int)serverNameLength));
+            var defaultVerificationError = (int)defaultVerificationErrorLength == 0 ? null : UnsafeUtilities.GetStringFromUtf8Bytes(new ReadOnlySpan<byte>(defaultVerificationErrorPtr, (int)defaultVerificationErrorLength));
+            if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Trace($"OnServerCertificateVerificationAsync: State=0x{callbackState:X}; ServerName={serverName}; Certificates={certificatesLength}; OcspResponse.Length={ocspResponseLength}; DefaultVerificationError={defaultVerificationError}; Now={now}");
+
+            // NOTE: The buffers are valid only during the callback, so they are copied before the verification is started.
+            var certificates = new byte[(int)certificatesLength][];
+            for (var i = 0; i < certificates.Length; i++)
+            {
+                certificates[i] = new ReadOnlySpan<byte>(certificatesPtr[i].ptr, certificatesPtr[i].length).ToArray();
+            }
+            var ocspResponse = new ReadOnlySpan<byte>(ocspResponsePtr, (int)ocspResponseLength).ToArray();
+            var context = new ServerCertificateVerificationContext(serverName, certificates, ocspResponse, DateTimeOffset.FromUnixTimeSeconds((long)now), defaultVerificationError);
+
+            var onVerifyServerCertificateChainAsync = (ServerCertificateChainVerificationAsyncHandler?)GCHandle.FromIntPtr(callbackState).Target;
+            Debug.Assert(onVerifyServerCertificateChainAsync != null);
+
+            // NOTE: The native side waits for the completion without blocking the worker thread.
+            //       The task must always be completed by calling CompleteServerCertificateVerification.
+            _ = VerifyServerCertificateAsync(onVerifyServerCertificateChainAsync, context, taskHandle);
+        }
+
+        private static async Task VerifyServerCertificateAsync(ServerCertificateChainVerificationAsyncHandler? onVerifyServerCertificateChainAsync, ServerCertificateVerificationContext context, nuint taskHandle)
+        {
+            try
+            {
+                if (onVerifyServerCertificateChainAsync == null)
+                {
+                    throw new InvalidOperationException("The verification callback was called, but onVerifyServerCertificateChainAsync is null.");
+                }
+
+                var success = await onVerifyServerCertificateChainAsync(context).ConfigureAwait(false);
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Trace($"OnServerCertificateVerificationAsync: ServerName={context.ServerName}; Success = {success}");
+                CompleteServerCertificateVerification(taskHandle, success ? null : "the verification handler rejected the certificate");
+            }
+            catch (Exception e)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Error($"OnServerCertificateVerificationAsync: The verification callback thrown an exception: {e.ToString()}");
+                CompleteServerCertificateVerification(taskHandle, e.Message);
+            }
+        }
+
+        private static unsafe void CompleteServerCertificateVerification(nuint taskHandle, string? error)
+        {
+            if (error is null)
+            {
+                NativeMethods.yaha_complete_server_certificate_verification(taskHandle, (StringBuffer*)0);
+                return;
+            }
+
+            using var messageUtf8 = new TempUtf8String(error);
+            fixed (byte* messagePtr = messageUtf8.Span)
+            {
+                var sb = new StringBuffer(messagePtr, messageUtf8.Span.Length);
+                NativeMethods.yaha_complete_server_certificate_verification(taskHandle, &sb);
+            }
+        }
+
+        [MonoPInvokeCallback(typeof(NativeMethods.yaha_client_config_set_proxy_selection_handler_handler_delegate))]
+        private static unsafe bool OnProxySelection(IntPtr callbackState, byte* uriPtr, UIntPtr /*nuint*/ uriLength, YahaProxySelection* selection)
+        {
+            var uri = UnsafeUtilities.GetStringFromUtf8Bytes(new ReadOnlySpan<byte>(uriPtr, (int)uriLength));
+            if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Trace($"OnProxySelection: State=0x{callbackState:X}; Uri={uri}");
+
+            var onSelectProxy = (ProxySelectionHandler?)GCHandle.FromIntPtr(callbackState).Target;
+            Debug.Assert(onSelectProxy != null);
+            if (onSelectProxy == null)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Warning($"OnProxySelection: The proxy selection callback was called, but onSelectProxy is null.");
+                return false;
+            }
+            try
+            {
+                var proxy = onSelectProxy(new Uri(uri));
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Trace($"OnProxySelection: Proxy = {proxy ?? "(direct)"}");
+                if (proxy is null)
+                {
+                    return true;
+                }
+
+                var strBytes = Encoding.UTF8.GetBytes(proxy);
+                fixed (byte* buffer = strBytes)
+                {
+                    var sb = new StringBuffer(buffer, strBytes.Length);
+                    return NativeMethods.yaha_proxy_selection_set_proxy(selection, &sb);
+                }
+            }
+            catch (Exception e)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Error($"OnProxySelection: The proxy selection callback thrown an exception: {e.ToString()}");
+                return false;
+            }
+        }
+
+        [MonoPInvokeCallback(typeof(NativeMethods.yaha_client_config_set_resolve_handler_handler_delegate))]
+        private static unsafe void OnResolveHostName(IntPtr callbackState, byte* hostPtr, UIntPtr /*nuint*/ hostLength, nuint taskHandle)
+        {
+            var host = UnsafeUtilities.GetStringFromUtf8Bytes(new ReadOnlySpan<byte>(hostPtr, (int)hostLength));
+            if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Trace($"OnResolveHostName: State=0x{callbackState:X}; Host={host}");
+
+            var onResolveHostName = (HostNameResolutionHandler?)GCHandle.FromIntPtr(callbackState).Target;
+            Debug.Assert(onResolveHostName != null);
+
+            // NOTE: The native side waits for the completion without blocking the worker thread.
+            //       The task must always be completed by calling CompleteResolve.
+            _ = ResolveHostNameAsync(onResolveHostName, host, taskHandle);
+        }
+
+        private static async Task ResolveHostNameAsync(HostNameResolutionHandler? onResolveHostName, string host, nuint taskHandle)
+        {
+            try
+            {
+                if (onResolveHostName == null)
+                {
+                    throw new InvalidOperationException("The resolver callback was called, but onResolveHostName is null.");
+                }
+
+                var addresses = await onResolveHostName(host).ConfigureAwait(false);
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Trace($"OnResolveHostName: Host={host}; Addresses={string.Join<IPAddress>(", ", addresses)}");
+                CompleteResolve(taskHandle, string.Join<IPAddress>(",", addresses), null);
+            }
+            catch (Exception e)
+            {
+                if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Error($"OnResolveHostName: The resolver callback thrown an exception: {e.ToString()}");
+                CompleteResolve(taskHandle, null, e.Message);
+            }
+        }
+
+        private static unsafe void CompleteResolve(nuint taskHandle, string? addresses, string? error)
+        {
+            if (error is null)
+            {
+                using var addressesUtf8 = new TempUtf8String(addresses ?? string.Empty);
+                fixed (byte* addressesPtr = addressesUtf8.Span)
+                {
+                    var sb = new StringBuffer(addressesPtr, addressesUtf8.Span.Length);
+                    NativeMethods.yaha_complete_resolve(taskHandle, &sb, (StringBuffer*)0);
+                }
+                return;
+            }
+
+            using var messageUtf8 = new TempUtf8String(error);
+            fixed (byte* messagePtr = messageUtf8.Span)
+            {
+                var sb = new StringBuffer(messagePtr, messageUtf8.Span.Length);
+                NativeMethods.yaha_complete_resolve(taskHandle, (StringBuffer*)0, &sb);
+            }
+        }
+
         [MonoPInvokeCallback(typeof(NativeMethods.yaha_init_context_on_receive_delegate))]
         private static unsafe void OnReceive(int reqSeq, IntPtr state, UIntPtr length, byte* buf, nuint taskHandle)
         {
@@ -575,7 +1257,7 @@ namespace Cysharp.Net.Http
 
                     requestContext.Complete();
                 }
-                else if (reason == CompletionReason.Error)
+                else if (reason == CompletionReason.Error || reason == CompletionReason.CircuitBreakerOpen)
                 {
                     var addRefContext = false;
                     var addRefRequestContext = false;
@@ -590,7 +1272,15 @@ namespace Cysharp.Net.Http
                         var buf = NativeMethods.yaha_get_last_error(ctx, reqCtx);
                         try
                         {
-                            requestContext.CompleteAsFailed(UnsafeUtilities.GetStringFromUtf8Bytes(buf->AsSpan()), h2ErrorCode);
+                            var errorMessage = UnsafeUtilities.GetStringFromUtf8Bytes(buf->AsSpan());
+                            if (reason == CompletionReason.CircuitBreakerOpen)
+                            {
+                                requestContext.CompleteAsFailed(new CircuitBreakerOpenException(errorMessage));
+                            }
+                            else
+                            {
+                                requestContext.CompleteAsFailed(errorMessage, h2ErrorCode);
+                            }
                         }
                         finally
                         {
@@ -640,6 +1330,8 @@ namespace Cysharp.Net.Http
             if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Info($"Dispose {nameof(NativeHttpHandlerCore)}; disposing={disposing}");
 
             _onVerifyServerCertificateHandle?.Free();
+            _onSelectProxyHandle?.Free();
+            _onResolveHostNameHandle?.Free();
 
             NativeRuntime.Instance.Release(); // We always need to release runtime.
 
@@ -52,9 +52,96 @@ namespace Cysharp.Net.Http
         [DllImport(__DllName, EntryPoint = "yaha_client_config_add_root_certificates", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
         public static extern nuint yaha_client_config_add_root_certificates(YahaNativeContext* ctx, StringBuffer* root_certs);
 
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_use_system_root_certificates", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_use_system_root_certificates(YahaNativeContext* ctx, [MarshalAs(UnmanagedType.U1)] bool val);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_use_built_in_root_certificates", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_use_built_in_root_certificates(YahaNativeContext* ctx, [MarshalAs(UnmanagedType.U1)] bool val);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_add_certificate_revocation_lists", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern nuint yaha_client_config_add_certificate_revocation_lists(YahaNativeContext* ctx, StringBuffer* crls);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_revocation_check_depth", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool yaha_client_config_revocation_check_depth(YahaNativeContext* ctx, int value);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_unknown_revocation_status_policy", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool yaha_client_config_unknown_revocation_status_policy(YahaNativeContext* ctx, int value);
+
         [DllImport(__DllName, EntryPoint = "yaha_client_config_add_override_server_name", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
         public static extern void yaha_client_config_add_override_server_name(YahaNativeContext* ctx, StringBuffer* override_server_name);
 
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_proxy", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool yaha_client_config_proxy(YahaNativeContext* ctx, StringBuffer* proxy_uri);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_use_environment_proxy", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_use_environment_proxy(YahaNativeContext* ctx, [MarshalAs(UnmanagedType.U1)] bool val);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_add_resolve_override", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool yaha_client_config_add_resolve_override(YahaNativeContext* ctx, StringBuffer* host_port, StringBuffer* address);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_add_connect_to", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool yaha_client_config_add_connect_to(YahaNativeContext* ctx, StringBuffer* from, StringBuffer* to);
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void yaha_client_config_set_resolve_handler_handler_delegate(nint state, byte* host, nuint host_len, nuint task_handle);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_set_resolve_handler", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_set_resolve_handler(YahaNativeContext* ctx, yaha_client_config_set_resolve_handler_handler_delegate handler, nint callback_state);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_use_built_in_dns_resolver", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_use_built_in_dns_resolver(YahaNativeContext* ctx, [MarshalAs(UnmanagedType.U1)] bool val);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_add_dns_server", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool yaha_client_config_add_dns_server(YahaNativeContext* ctx, StringBuffer* address);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_srv_discovery", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_srv_discovery(YahaNativeContext* ctx, [MarshalAs(UnmanagedType.U1)] bool val);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_dns_over_https_endpoint", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool yaha_client_config_dns_over_https_endpoint(YahaNativeContext* ctx, StringBuffer* endpoint);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_add_dns_over_https_bootstrap_address", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool yaha_client_config_add_dns_over_https_bootstrap_address(YahaNativeContext* ctx, StringBuffer* address);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_dns_cache", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_dns_cache(YahaNativeContext* ctx, [MarshalAs(UnmanagedType.U1)] bool val);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_dns_cache_default_ttl", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_dns_cache_default_ttl(YahaNativeContext* ctx, ulong val_milliseconds);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_dns_cache_max_ttl", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_dns_cache_max_ttl(YahaNativeContext* ctx, ulong val_milliseconds);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_dns_cache_negative_ttl", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_dns_cache_negative_ttl(YahaNativeContext* ctx, ulong val_milliseconds);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_dns_cache_stale_while_revalidate", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_dns_cache_stale_while_revalidate(YahaNativeContext* ctx, ulong val_milliseconds);
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate bool yaha_client_config_set_proxy_selection_handler_handler_delegate(nint state, byte* uri, nuint uri_len, YahaProxySelection* selection);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_set_proxy_selection_handler", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_set_proxy_selection_handler(YahaNativeContext* ctx, yaha_client_config_set_proxy_selection_handler_handler_delegate handler, nint callback_state);
+
+        [DllImport(__DllName, EntryPoint = "yaha_proxy_selection_set_proxy", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool yaha_proxy_selection_set_proxy(YahaProxySelection* selection, StringBuffer* proxy_uri);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_add_proxy_basic_credentials", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_add_proxy_basic_credentials(YahaNativeContext* ctx, StringBuffer* username, StringBuffer* password);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_add_proxy_bearer_token", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_add_proxy_bearer_token(YahaNativeContext* ctx, StringBuffer* token);
+
         [DllImport(__DllName, EntryPoint = "yaha_client_config_add_client_auth_certificates", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
         public static extern nuint yaha_client_config_add_client_auth_certificates(YahaNativeContext* ctx, StringBuffer* auth_certs);
 
@@ -65,11 +152,24 @@ namespace Cysharp.Net.Http
         public static extern void yaha_client_config_skip_certificate_verification(YahaNativeContext* ctx, [MarshalAs(UnmanagedType.U1)] bool val);
 
         [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
-        public delegate bool yaha_client_config_set_server_certificate_verification_handler_handler_delegate(nint state, byte* server_name, nuint server_name_len, byte* certificate_der, nuint certificate_der_len, ulong now);
+        public delegate bool yaha_client_config_set_server_certificate_verification_handler_handler_delegate(nint state, byte* server_name, nuint server_name_len, StringBuffer* certificates, nuint certificates_len, byte* ocsp_response, nuint ocsp_response_len, byte* default_verification_error, nuint default_verification_error_len, ulong now);
 
         [DllImport(__DllName, EntryPoint = "yaha_client_config_set_server_certificate_verification_handler", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
         public static extern void yaha_client_config_set_server_certificate_verification_handler(YahaNativeContext* ctx, yaha_client_config_set_server_certificate_verification_handler_handler_delegate handler, nint callback_state);
 
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void yaha_client_config_set_server_certificate_verification_async_handler_handler_delegate(nint state, byte* server_name, nuint server_name_len, StringBuffer* certificates, nuint certificates_len, byte* ocsp_response, nuint ocsp_response_len, byte* default_verification_error, nuint default_verification_error_len, ulong now, nuint task_handle);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_set_server_certificate_verification_async_handler", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_set_server_certificate_verification_async_handler(YahaNativeContext* ctx, yaha_client_config_set_server_certificate_verification_async_handler_handler_delegate handler, nint callback_state);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_server_certificate_verification_timeout", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_server_certificate_verification_timeout(YahaNativeContext* ctx, ulong val_milliseconds);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_add_public_key_pins", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool yaha_client_config_add_public_key_pins(YahaNativeContext* ctx, StringBuffer* host_pattern, StringBuffer* pins, ulong expires_at_unix_seconds);
+
         [DllImport(__DllName, EntryPoint = "yaha_client_config_pool_idle_timeout", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
         public static extern void yaha_client_config_pool_idle_timeout(YahaNativeContext* ctx, ulong val_milliseconds);
 
@@ -103,6 +203,95 @@ namespace Cysharp.Net.Http
         [DllImport(__DllName, EntryPoint = "yaha_client_config_connect_timeout", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
         public static extern void yaha_client_config_connect_timeout(YahaNativeContext* ctx, ulong timeout_milliseconds);
 
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_address_family_preference", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool yaha_client_config_address_family_preference(YahaNativeContext* ctx, int value);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_happy_eyeballs_fallback_delay", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_happy_eyeballs_fallback_delay(YahaNativeContext* ctx, ulong delay_milliseconds);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_load_balancing_policy", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool yaha_client_config_load_balancing_policy(YahaNativeContext* ctx, int value);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_load_balancing_re_resolve_interval", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_load_balancing_re_resolve_interval(YahaNativeContext* ctx, ulong interval_milliseconds);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_circuit_breaker", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_circuit_breaker(YahaNativeContext* ctx, [MarshalAs(UnmanagedType.U1)] bool value);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_circuit_breaker_consecutive_failures", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_circuit_breaker_consecutive_failures(YahaNativeContext* ctx, uint value);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_circuit_breaker_failure_rate", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_circuit_breaker_failure_rate(YahaNativeContext* ctx, double value);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_circuit_breaker_sampling_requests", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_circuit_breaker_sampling_requests(YahaNativeContext* ctx, nuint value);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_circuit_breaker_cooldown", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_circuit_breaker_cooldown(YahaNativeContext* ctx, ulong cooldown_milliseconds);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_retry", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_retry(YahaNativeContext* ctx, [MarshalAs(UnmanagedType.U1)] bool value);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_retry_max_attempts", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_retry_max_attempts(YahaNativeContext* ctx, uint value);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_retry_initial_backoff", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_retry_initial_backoff(YahaNativeContext* ctx, ulong backoff_milliseconds);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_retry_max_backoff", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_retry_max_backoff(YahaNativeContext* ctx, ulong backoff_milliseconds);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_retry_non_idempotent_requests", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_retry_non_idempotent_requests(YahaNativeContext* ctx, [MarshalAs(UnmanagedType.U1)] bool value);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_retry_budget_ratio", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_retry_budget_ratio(YahaNativeContext* ctx, double value);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_hedging_delay", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_hedging_delay(YahaNativeContext* ctx, ulong delay_milliseconds);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_hedging_max_attempts", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_hedging_max_attempts(YahaNativeContext* ctx, uint value);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_redirect", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_redirect(YahaNativeContext* ctx, [MarshalAs(UnmanagedType.U1)] bool value);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_max_redirects", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_max_redirects(YahaNativeContext* ctx, uint value);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_allow_https_to_http_redirect", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_allow_https_to_http_redirect(YahaNativeContext* ctx, [MarshalAs(UnmanagedType.U1)] bool value);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_cookie_jar", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_cookie_jar(YahaNativeContext* ctx, [MarshalAs(UnmanagedType.U1)] bool val);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_cookie_file", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool yaha_client_config_cookie_file(YahaNativeContext* ctx, StringBuffer* path, int format);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_cookie_public_suffix_list", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool yaha_client_config_cookie_public_suffix_list(YahaNativeContext* ctx, StringBuffer* path);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_decompression", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_decompression(YahaNativeContext* ctx, [MarshalAs(UnmanagedType.U1)] bool val);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_http_cache", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_http_cache(YahaNativeContext* ctx, [MarshalAs(UnmanagedType.U1)] bool val);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_http_cache_directory", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool yaha_client_config_http_cache_directory(YahaNativeContext* ctx, StringBuffer* path);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_http_cache_max_size", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_http_cache_max_size(YahaNativeContext* ctx, ulong value);
+
+        [DllImport(__DllName, EntryPoint = "yaha_client_config_http_cache_max_entry_size", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_config_http_cache_max_entry_size(YahaNativeContext* ctx, ulong value);
+
         [DllImport(__DllName, EntryPoint = "yaha_client_config_http2_max_concurrent_reset_streams", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
         public static extern void yaha_client_config_http2_max_concurrent_reset_streams(YahaNativeContext* ctx, nuint max);
 
@@ -115,6 +304,9 @@ namespace Cysharp.Net.Http
         [DllImport(__DllName, EntryPoint = "yaha_client_config_unix_domain_socket_path", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
         public static extern void yaha_client_config_unix_domain_socket_path(YahaNativeContext* ctx, StringBuffer* uds_path);
 
+        [DllImport(__DllName, EntryPoint = "yaha_client_flush_dns_cache", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_client_flush_dns_cache(YahaNativeContext* ctx);
+
         [DllImport(__DllName, EntryPoint = "yaha_build_client", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
         public static extern void yaha_build_client(YahaNativeContext* ctx);
 
@@ -129,6 +321,14 @@ namespace Cysharp.Net.Http
         [return: MarshalAs(UnmanagedType.U1)]
         public static extern bool yaha_request_set_has_body(YahaNativeContext* ctx, YahaNativeRequestContext* req_ctx, [MarshalAs(UnmanagedType.U1)] bool value);
 
+        [DllImport(__DllName, EntryPoint = "yaha_request_set_body_compression", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool yaha_request_set_body_compression(YahaNativeContext* ctx, YahaNativeRequestContext* req_ctx, int value);
+
+        [DllImport(__DllName, EntryPoint = "yaha_request_set_body_compression_level", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool yaha_request_set_body_compression_level(YahaNativeContext* ctx, YahaNativeRequestContext* req_ctx, int value);
+
         [DllImport(__DllName, EntryPoint = "yaha_request_set_uri", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
         [return: MarshalAs(UnmanagedType.U1)]
         public static extern bool yaha_request_set_uri(YahaNativeContext* ctx, YahaNativeRequestContext* req_ctx, StringBuffer* value);
@@ -164,6 +364,9 @@ namespace Cysharp.Net.Http
         [DllImport(__DllName, EntryPoint = "yaha_request_response_get_header_value", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
         public static extern ByteBuffer* yaha_request_response_get_header_value(YahaNativeContext* ctx, YahaNativeRequestContext* req_ctx, int index);
 
+        [DllImport(__DllName, EntryPoint = "yaha_request_response_get_uri", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern ByteBuffer* yaha_request_response_get_uri(YahaNativeContext* ctx, YahaNativeRequestContext* req_ctx);
+
         [DllImport(__DllName, EntryPoint = "yaha_request_response_get_trailers_count", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
         public static extern int yaha_request_response_get_trailers_count(YahaNativeContext* ctx, YahaNativeRequestContext* req_ctx);
 
@@ -177,6 +380,12 @@ namespace Cysharp.Net.Http
         [return: MarshalAs(UnmanagedType.U1)]
         public static extern bool yaha_request_destroy(YahaNativeContext* ctx, YahaNativeRequestContext* req_ctx);
 
+        [DllImport(__DllName, EntryPoint = "yaha_complete_resolve", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_complete_resolve(nuint task_handle, StringBuffer* addresses, StringBuffer* error);
+
+        [DllImport(__DllName, EntryPoint = "yaha_complete_server_certificate_verification", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void yaha_complete_server_certificate_verification(nuint task_handle, StringBuffer* error);
+
         [DllImport(__DllName, EntryPoint = "yaha_complete_task", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
         public static extern void yaha_complete_task(nuint task_handle, StringBuffer* error);
 
@@ -203,6 +412,11 @@ namespace Cysharp.Net.Http
     {
     }
 
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct YahaProxySelection
+    {
+    }
+
     [StructLayout(LayoutKind.Sequential)]
     internal unsafe partial struct YahaNativeContext
     {
@@ -228,6 +442,7 @@ namespace Cysharp.Net.Http
         Success,
         Error,
         Aborted,
+        CircuitBreakerOpen,
     }
 
     internal enum WriteResult : int
@@ -0,0 +1,44 @@
+using System;
+using System.Collections.Generic;
+using System.Linq;
+
+namespace Cysharp.Net.Http
+{
+    /// <summary>
+    /// Represents the public keys pinned for the host names for <see cref="YetAnotherHttpHandler.PublicKeyPins"/>.
+    /// </summary>
+    public sealed class PublicKeyPinSet
+    {
+        /// <summary>
+        /// Gets the host name, or the pattern that starts with <c>*.</c> to match the subdomains (e.g. <c>*.example.com</c> matches <c>api.example.com</c> but not <c>example.com</c>).
+        /// </summary>
+        public string HostPattern { get; }
+
+        /// <summary>
+        /// Gets the Base64-encoded SHA-256 hashes of the SubjectPublicKeyInfo, in the form of <c>sha256/&lt;base64&gt;</c>. The backup pins are included.
+        /// </summary>
+        public IReadOnlyList<string> Pins { get; }
+
+        /// <summary>
+        /// Gets the time when the pins expire, or <c>null</c> if they never expire. The pins are not enforced after this time.
+        /// </summary>
+        public DateTimeOffset? ExpiresAt { get; }
+
+        /// <summary>
+        /// Initializes a new instance of the <see cref="PublicKeyPinSet"/> class.
+        /// </summary>
+        /// <param name="hostPattern">The host name, or the pattern that starts with <c>*.</c> to match the subdomains.</param>
+        /// <param name="pins">The Base64-encoded SHA-256 hashes of the SubjectPublicKeyInfo, in the form of <c>sha256/&lt;base64&gt;</c>.</param>
+        /// <param name="expiresAt">The time when the pins expire, or <c>null</c> if they never expire.</param>
+        public PublicKeyPinSet(string hostPattern, IEnumerable<string> pins, DateTimeOffset? expiresAt = null)
+        {
+            HostPattern = hostPattern ?? throw new ArgumentNullException(nameof(hostPattern));
+            Pins = pins?.ToArray() ?? throw new ArgumentNullException(nameof(pins));
+            ExpiresAt = expiresAt;
+        }
+
+        /// <inheritdoc />
+        public override string ToString()
+            => $"{HostPattern}: {string.Join(", ", Pins)}{(ExpiresAt is { } expiresAt ? $" (Expires: {expiresAt:o})" : "")}";
+    }
+}
@@ -313,6 +313,12 @@ namespace Cysharp.Net.Http
             _cancellationTokenSource.Cancel(); // Stop reading the request body.
         }
 
+        public void CompleteAsFailed(Exception ex)
+        {
+            Response.CompleteAsFailed(ex);
+            _cancellationTokenSource.Cancel(); // Stop reading the request body.
+        }
+
         private void TryReleaseNativeHandles()
         {
             Debug.Assert(!_handle.IsAllocated);
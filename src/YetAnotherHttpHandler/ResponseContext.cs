@@ -82,6 +82,14 @@ namespace Cysharp.Net.Http
             }
         }
 
+        internal void SetRequestUri(string uri)
+        {
+            if (_message.RequestMessage is { } requestMessage)
+            {
+                requestMessage.RequestUri = new Uri(uri);
+            }
+        }
+
         internal void SetVersion(YahaHttpVersion version)
         {
             _message.Version = version switch
@@ -125,19 +133,24 @@ namespace Cysharp.Net.Http
         {
             if (YahaEventSource.Log.IsEnabled()) YahaEventSource.Log.Trace($"[ReqSeq:{_requestContext.RequestSequence}] Response completed with failure ({errorMessage}) (0x{h2ErrorCode:x})");
 
-            lock (_writeLock)
+            Exception ex = new IOException(errorMessage);
+            if (h2ErrorCode != 0)
             {
-                if (_completed) return;
-
-                Exception ex = new IOException(errorMessage);
-                if (h2ErrorCode != 0)
-                {
 #if NET7_0_OR_GREATER
-                    ex = new HttpProtocolException(h2ErrorCode, $"The HTTP/2 server closed the connection or reset the stream. HTTP/2 error code '{Http2ErrorCode.ToName(h2ErrorCode)}' (0x{h2ErrorCode:x}).", ex);
+                ex = new HttpProtocolException(h2ErrorCode, $"The HTTP/2 server closed the connection or reset the stream. HTTP/2 error code '{Http2ErrorCode.ToName(h2ErrorCode)}' (0x{h2ErrorCode:x}).", ex);
 #else
-                    ex = new Http2StreamException($"The HTTP/2 server closed the connection or reset the stream. HTTP/2 error code '{Http2ErrorCode.ToName(h2ErrorCode)}' (0x{h2ErrorCode:x}).", ex);
+                ex = new Http2StreamException($"The HTTP/2 server closed the connection or reset the stream. HTTP/2 error code '{Http2ErrorCode.ToName(h2ErrorCode)}' (0x{h2ErrorCode:x}).", ex);
 #endif
-                }
+            }
+
+            CompleteAsFailed(ex);
+        }
+
+        public void CompleteAsFailed(Exception ex)
+        {
+            lock (_writeLock)
+            {
+                if (_completed) return;
 
 #if NET5_0_OR_GREATER
                 ExceptionDispatchInfo.SetCurrentStackTrace(ex);
@@ -0,0 +1,58 @@
+using System;
+using System.Collections.Generic;
+
+namespace Cysharp.Net.Http
+{
+    /// <summary>
+    /// Provides the information of the server certificate verification for <see cref="YetAnotherHttpHandler.OnVerifyServerCertificateChain"/>.
+    /// </summary>
+    public sealed class ServerCertificateVerificationContext
+    {
+        /// <summary>
+        /// Gets the server host name.
+        /// </summary>
+        public string ServerName { get; }
+
+        /// <summary>
+        /// Gets the certificates presented by the server in DER format. The first one is the end-entity certificate, and the intermediates follow.
+        /// </summary>
+        public IReadOnlyList<byte[]> Certificates { get; }
+
+        /// <summary>
+        /// Gets the end-entity certificate in DER format.
+        /// </summary>
+        public byte[] Certificate => Certificates[0];
+
+        /// <summary>
+        /// Gets the OCSP response stapled by the server. It is empty if the server doesn't staple the response.
+        /// </summary>
+        /// <remarks>
+        /// The response is not validated by the standard verification.
+        /// </remarks>
+        public byte[] OcspResponse { get; }
+
+        /// <summary>
+        /// Gets the current time.
+        /// </summary>
+        public DateTimeOffset Now { get; }
+
+        /// <summary>
+        /// Gets a value that indicates whether the standard verification succeeded. The chain, validity period and server name are verified against the root certificates.
+        /// </summary>
+        public bool IsDefaultVerificationSucceeded => DefaultVerificationError is null;
+
+        /// <summary>
+        /// Gets the error of the standard verification (e.g. <c>invalid peer certificate: UnknownIssuer</c>), or <c>null</c> if it succeeded.
+        /// </summary>
+        public string? DefaultVerificationError { get; }
+
+        internal ServerCertificateVerificationContext(string serverName, IReadOnlyList<byte[]> certificates, byte[] ocspResponse, DateTimeOffset now, string? defaultVerificationError)
+        {
+            ServerName = serverName;
+            Certificates = certificates;
+            OcspResponse = ocspResponse;
+            Now = now;
+            DefaultVerificationError = defaultVerificationError;
+        }
+    }
+}
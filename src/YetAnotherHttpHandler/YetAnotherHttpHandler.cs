@@ -1,6 +1,8 @@
 using System;
+using System.Collections.Generic;
 using System.Diagnostics;
 using System.IO.Pipelines;
+using System.Net;
 using System.Net.Http;
 using System.Threading;
 using System.Threading.Tasks;
@@ -50,11 +52,87 @@ namespace Cysharp.Net.Http
         /// </summary>
         public ServerCertificateVerificationHandler? OnVerifyServerCertificate { get => _settings.OnVerifyServerCertificate; set => _settings.OnVerifyServerCertificate = value; }
 
+        /// <summary>
+        /// Gets or sets a custom handler that validates server certificates with the full chain, the stapled OCSP response and the result of the standard verification.
+        /// </summary>
+        /// <remarks>
+        /// The standard verification against <see cref="RootCertificates"/> (or the built-in root CA) is performed first, and the handler decides whether to trust the server.
+        /// If both <see cref="OnVerifyServerCertificate"/> and this handler are set, this handler is used.
+        /// </remarks>
+        public ServerCertificateChainVerificationHandler? OnVerifyServerCertificateChain { get => _settings.OnVerifyServerCertificateChain; set => _settings.OnVerifyServerCertificateChain = value; }
+
+        /// <summary>
+        /// Gets or sets a custom handler that validates server certificates asynchronously, such as querying an OCSP responder or a certificate transparency log.
+        /// </summary>
+        /// <remarks>
+        /// The connection is not used for requests until the returned task completes, and the worker threads are not blocked while waiting.
+        /// The connection fails if the task doesn't complete within <see cref="ServerCertificateVerificationTimeout"/>.
+        /// If this handler is set, <see cref="OnVerifyServerCertificate"/> and <see cref="OnVerifyServerCertificateChain"/> are not used.
+        /// The connections to the proxy and the DNS-over-HTTPS server are verified with the standard verification.
+        /// </remarks>
+        public ServerCertificateChainVerificationAsyncHandler? OnVerifyServerCertificateChainAsync { get => _settings.OnVerifyServerCertificateChainAsync; set => _settings.OnVerifyServerCertificateChainAsync = value; }
+
+        /// <summary>
+        /// Gets or sets the timeout to wait for <see cref="OnVerifyServerCertificateChainAsync"/>. The default value is 30 seconds.
+        /// </summary>
+        public TimeSpan? ServerCertificateVerificationTimeout { get => _settings.ServerCertificateVerificationTimeout; set => _settings.ServerCertificateVerificationTimeout = value; }
+
         /// <summary>
         /// Gets or sets a custom root CA. By default, the built-in root CA (Mozilla's root certificates) is used. See also <seealso href="https://github.com/rustls/webpki-roots" />.
         /// </summary>
         public string? RootCertificates { get => _settings.RootCertificates; set => _settings.RootCertificates = value; }
 
+        /// <summary>
+        /// Gets or sets a value that indicates whether to trust the root certificates of the operating system's trust store. Default is <c>false</c>.
+        /// </summary>
+        /// <remarks>
+        /// If <c>SSL_CERT_FILE</c> or <c>SSL_CERT_DIR</c> environment variables are set, the certificates are loaded from them instead of the platform store.
+        /// The system roots are combined with <see cref="RootCertificates"/> and the built-in root CA if they are enabled.
+        /// </remarks>
+        public bool? UseSystemRootCertificates { get => _settings.UseSystemRootCertificates; set => _settings.UseSystemRootCertificates = value; }
+
+        /// <summary>
+        /// Gets or sets a value that indicates whether to trust the built-in root CA (Mozilla's root certificates). Default is <c>true</c> if <see cref="RootCertificates"/> is not set; otherwise <c>false</c>.
+        /// </summary>
+        /// <remarks>
+        /// Set this to <c>true</c> to trust both the public CAs and the private CA specified by <see cref="RootCertificates"/>.
+        /// </remarks>
+        public bool? UseBuiltInRootCertificates { get => _settings.UseBuiltInRootCertificates; set => _settings.UseBuiltInRootCertificates = value; }
+
+        /// <summary>
+        /// Gets or sets the certificate revocation lists (CRLs) to check the revocation status of the server certificates. Each item is PEM (which may contain multiple CRLs) or DER.
+        /// </summary>
+        /// <remarks>
+        /// The revocation status is checked in the standard verification only if CRLs are provided.
+        /// By default, the certificates whose issuer has no CRL are untrusted. See <see cref="UnknownRevocationStatusPolicy"/>.
+        /// </remarks>
+        public IList<byte[]>? CertificateRevocationLists { get => _settings.CertificateRevocationLists; set => _settings.CertificateRevocationLists = value; }
+
+        /// <summary>
+        /// Gets or sets the paths of the files that contain the certificate revocation lists (CRLs) in PEM or DER. The files are read when the handler is initialized.
+        /// </summary>
+        public IList<string>? CertificateRevocationListFiles { get => _settings.CertificateRevocationListFiles; set => _settings.CertificateRevocationListFiles = value; }
+
+        /// <summary>
+        /// Gets or sets the certificates to check the revocation status with the certificate revocation lists. Default is <see cref="Cysharp.Net.Http.RevocationCheckDepth.Chain"/>.
+        /// </summary>
+        public RevocationCheckDepth? RevocationCheckDepth { get => _settings.RevocationCheckDepth; set => _settings.RevocationCheckDepth = value; }
+
+        /// <summary>
+        /// Gets or sets whether to trust the certificates whose revocation status is unknown because no CRL is provided for the issuer. Default is <see cref="Cysharp.Net.Http.UnknownRevocationStatusPolicy.Deny"/>.
+        /// </summary>
+        public UnknownRevocationStatusPolicy? UnknownRevocationStatusPolicy { get => _settings.UnknownRevocationStatusPolicy; set => _settings.UnknownRevocationStatusPolicy = value; }
+
+        /// <summary>
+        /// Gets or sets the public keys pinned for the host names. The server is trusted only if one of the certificates in the path from the server certificate to a trusted root certificate has a pinned public key.
+        /// </summary>
+        /// <remarks>
+        /// The pins are checked after the standard verification (or the custom verification handler) trusts the server. The path is built with the root certificates of the standard verification (including the root certificate itself),
+        /// so the server is not trusted if its certificate doesn't chain to one of them even if the custom verification handler trusts it. The hosts that don't match any pattern are not affected.
+        /// The host name is matched against <see cref="OverrideServerName"/> if it is set. The connections to the proxy and the DNS-over-HTTPS server are not pinned.
+        /// </remarks>
+        public IList<PublicKeyPinSet>? PublicKeyPins { get => _settings.PublicKeyPins; set => _settings.PublicKeyPins = value; }
+
         /// <summary>
         /// Gets or sets a value that specifies subject alternative name (SAN) of the certificate.
         /// </summary>
@@ -112,6 +190,335 @@ namespace Cysharp.Net.Http
         /// </remarks>
         public TimeSpan? ConnectTimeout { get => _settings.ConnectTimeout; set => _settings.ConnectTimeout = value; }
 
+        /// <summary>
+        /// Gets or sets the order of address families to connect to. Default is <see cref="Cysharp.Net.Http.AddressFamilyPreference.Default"/>.
+        /// </summary>
+        /// <remarks>
+        /// The addresses of the other family are not used with <see cref="Cysharp.Net.Http.AddressFamilyPreference.IPv4Only"/> and <see cref="Cysharp.Net.Http.AddressFamilyPreference.IPv6Only"/>.
+        /// This option doesn't apply to IP address literals in the request URI and <see cref="ResolveOverrides"/>.
+        /// </remarks>
+        public AddressFamilyPreference? AddressFamilyPreference { get => _settings.AddressFamilyPreference; set => _settings.AddressFamilyPreference = value; }
+
+        /// <summary>
+        /// Gets or sets the delay before connecting to the addresses of the other family while connecting to the preferred family (Happy Eyeballs, RFC 8305).
+        /// Default is 300 milliseconds.
+        /// </summary>
+        /// <remarks>
+        /// <see href="https://docs.rs/hyper-util/latest/hyper_util/client/legacy/connect/struct.HttpConnector.html#method.set_happy_eyeballs_timeout">hyper: set_happy_eyeballs_timeout</see>
+        /// </remarks>
+        public TimeSpan? HappyEyeballsFallbackDelay { get => _settings.HappyEyeballsFallbackDelay; set => _settings.HappyEyeballsFallbackDelay = value; }
+
+        /// <summary>
+        /// Gets or sets the policy to distribute the requests across the resolved addresses of a host. Default is disabled.
+        /// </summary>
+        /// <remarks>
+        /// By default, the connections to a host are pooled together, so the requests (e.g. gRPC calls over an HTTP/2 connection) tend to go to the address connected first.
+        /// If this option is set, each address has its own connection pool and the requests are distributed across them.
+        /// The requests through a proxy, to an IP address, to a host with <see cref="ResolveOverrides"/> or <see cref="ConnectTo"/>, and to a service name of SRV discovery are not load balanced.
+        /// </remarks>
+        public LoadBalancingPolicy? LoadBalancingPolicy { get => _settings.LoadBalancingPolicy; set => _settings.LoadBalancingPolicy = value; }
+
+        /// <summary>
+        /// Gets or sets the interval to resolve the host names again to update the addresses for load balancing. Default is 30 seconds.
+        /// </summary>
+        public TimeSpan? LoadBalancingReResolveInterval { get => _settings.LoadBalancingReResolveInterval; set => _settings.LoadBalancingReResolveInterval = value; }
+
+        /// <summary>
+        /// Gets or sets a value that indicates whether to fail the requests fast while the destination keeps failing. Default is <c>false</c>.
+        /// </summary>
+        /// <remarks>
+        /// The circuit is kept for each scheme, host and port (and for each address with <see cref="LoadBalancingPolicy"/>). Connection errors and 5xx, 408 and 429 responses are counted as failures.
+        /// While the circuit is open, the requests fail with <see cref="HttpRequestException"/> wrapping <see cref="CircuitBreakerOpenException"/>.
+        /// After <see cref="CircuitBreakerCooldown"/>, a probe request is sent and the circuit is closed if it succeeds.
+        /// With <see cref="LoadBalancingPolicy"/>, the addresses with the open circuit are ejected from the load balancing instead.
+        /// </remarks>
+        public bool? EnableCircuitBreaker { get => _settings.EnableCircuitBreaker; set => _settings.EnableCircuitBreaker = value; }
+
+        /// <summary>
+        /// Gets or sets the number of consecutive failures to open the circuit. <c>0</c> disables this condition. Default is <c>5</c>.
+        /// </summary>
+        public uint? CircuitBreakerConsecutiveFailures { get => _settings.CircuitBreakerConsecutiveFailures; set => _settings.CircuitBreakerConsecutiveFailures = value; }
+
+        /// <summary>
+        /// Gets or sets the failure rate (between <c>0</c> and <c>1</c>) of the recent requests to open the circuit. <c>0</c> disables this condition. Default is <c>0</c>.
+        /// </summary>
+        public double? CircuitBreakerFailureRate { get => _settings.CircuitBreakerFailureRate; set => _settings.CircuitBreakerFailureRate = value; }
+
+        /// <summary>
+        /// Gets or sets the number of the recent requests to calculate the failure rate. Default is <c>20</c>.
+        /// </summary>
+        public uint? CircuitBreakerSamplingRequests { get => _settings.CircuitBreakerSamplingRequests; set => _settings.CircuitBreakerSamplingRequests = value; }
+
+        /// <summary>
+        /// Gets or sets how long the circuit stays open before a probe request is allowed. Default is 30 seconds.
+        /// </summary>
+        public TimeSpan? CircuitBreakerCooldown { get => _settings.CircuitBreakerCooldown; set => _settings.CircuitBreakerCooldown = value; }
+
+        /// <summary>
+        /// Gets or sets a value that indicates whether to retry the requests failed with transient errors. Default is <c>false</c>.
+        /// </summary>
+        /// <remarks>
+        /// The requests that the server hasn't processed (connection failures, HTTP/2 <c>REFUSED_STREAM</c>) are retried regardless of the method.
+        /// The requests that may have been processed (connection reset, HTTP/2 <c>GOAWAY</c>) are retried only for the idempotent methods unless <see cref="RetryNonIdempotentRequests"/> is set.
+        /// The request body is replayed if it is 64 KiB or smaller. The requests with the larger body are not retried.
+        /// </remarks>
+        public bool? EnableRetry { get => _settings.EnableRetry; set => _settings.EnableRetry = value; }
+
+        /// <summary>
+        /// Gets or sets the maximum number of attempts including the first one. Default is <c>3</c>.
+        /// </summary>
+        public uint? RetryMaxAttempts { get => _settings.RetryMaxAttempts; set => _settings.RetryMaxAttempts = value; }
+
+        /// <summary>
+        /// Gets or sets the backoff before the first retry. The backoff is doubled for each retry, and a random delay up to the backoff is used. Default is 100 milliseconds.
+        /// </summary>
+        public TimeSpan? RetryInitialBackoff { get => _settings.RetryInitialBackoff; set => _settings.RetryInitialBackoff = value; }
+
+        /// <summary>
+        /// Gets or sets the maximum backoff between the retries. Default is 2 seconds.
+        /// </summary>
+        public TimeSpan? RetryMaxBackoff { get => _settings.RetryMaxBackoff; set => _settings.RetryMaxBackoff = value; }
+
+        /// <summary>
+        /// Gets or sets a value that indicates whether to retry the requests with non-idempotent methods (e.g. <c>POST</c>) even if the server may have processed them. Default is <c>false</c>.
+        /// </summary>
+        public bool? RetryNonIdempotentRequests { get => _settings.RetryNonIdempotentRequests; set => _settings.RetryNonIdempotentRequests = value; }
+
+        /// <summary>
+        /// Gets or sets the ratio of retries to requests to prevent retry storms. Each request earns this amount of the budget, and each retry spends 1. Up to 10 retries are allowed before the budget is earned. Default is <c>0.2</c>.
+        /// </summary>
+        public double? RetryBudgetRatio { get => _settings.RetryBudgetRatio; set => _settings.RetryBudgetRatio = value; }
+
+        /// <summary>
+        /// Gets or sets the delay before sending a duplicate request if no response headers arrive. Default is disabled.
+        /// </summary>
+        /// <remarks>
+        /// If this option is set, the idempotent requests without body (e.g. <c>GET</c>) are hedged: a duplicate request is sent after the delay, and the first response is used. The other requests in flight are cancelled.
+        /// With <see cref="LoadBalancingPolicy"/>, the duplicate requests prefer the addresses not used by the previous ones. Otherwise, HTTP/1.1 requests use another connection, and HTTP/2 requests share the connection.
+        /// </remarks>
+        public TimeSpan? HedgingDelay { get => _settings.HedgingDelay; set => _settings.HedgingDelay = value; }
+
+        /// <summary>
+        /// Gets or sets the maximum number of hedged requests including the first one. Default is <c>2</c>.
+        /// </summary>
+        public uint? HedgingMaxAttempts { get => _settings.HedgingMaxAttempts; set => _settings.HedgingMaxAttempts = value; }
+
+        /// <summary>
+        /// Gets or sets a value that indicates whether to follow the redirect responses (301, 302, 303, 307 and 308) in the native layer. Default is <c>false</c>.
+        /// </summary>
+        /// <remarks>
+        /// 303 changes the method to <c>GET</c> (except <c>HEAD</c>), and 301 and 302 change <c>POST</c> to <c>GET</c>. The request body is replayed for the other redirects if it is 64 KiB or smaller.
+        /// <c>Authorization</c> and <c>Cookie</c> headers are removed when redirected to another origin.
+        /// The <see cref="HttpRequestMessage.RequestUri"/> of <see cref="HttpResponseMessage.RequestMessage"/> is updated to the URI of the final response.
+        /// If the redirect can't be followed, the redirect response is returned as is.
+        /// </remarks>
+        public bool? AllowAutoRedirect { get => _settings.AllowAutoRedirect; set => _settings.AllowAutoRedirect = value; }
+
+        /// <summary>
+        /// Gets or sets the maximum number of redirects to follow. Default is <c>50</c>.
+        /// </summary>
+        public int? MaxAutomaticRedirections { get => _settings.MaxAutomaticRedirections; set => _settings.MaxAutomaticRedirections = value; }
+
+        /// <summary>
+        /// Gets or sets a value that indicates whether to follow the redirects from HTTPS to HTTP. Default is <c>false</c>.
+        /// </summary>
+        public bool? AllowHttpsToHttpRedirect { get => _settings.AllowHttpsToHttpRedirect; set => _settings.AllowHttpsToHttpRedirect = value; }
+
+        /// <summary>
+        /// Gets or sets a value that indicates whether to store the cookies of the responses and send them with the requests in the native layer. Default is <c>false</c>.
+        /// </summary>
+        /// <remarks>
+        /// The cookies are matched by the domain and the path as specified in RFC 6265, and <c>Secure</c> cookies are sent over HTTPS only.
+        /// <c>SameSite=Strict</c> cookies are sent only to the same site as the request URI (including the redirects), and <c>SameSite=Lax</c> cookies are also sent with the cross-site requests of the safe methods.
+        /// The cookies are appended to the <c>Cookie</c> header of the request.
+        /// </remarks>
+        public bool? UseCookies { get => _settings.UseCookies; set => _settings.UseCookies = value; }
+
+        /// <summary>
+        /// Gets or sets the path of the file to load the cookies from and to save the persistent cookies to. It is used if <see cref="UseCookies"/> is <c>true</c>.
+        /// </summary>
+        /// <remarks>
+        /// The cookies are loaded when the handler is initialized, and saved every time the cookies are changed by the responses. The session cookies are not saved.
+        /// If the file doesn't exist, it is created on the first change.
+        /// </remarks>
+        public string? CookieFilePath { get => _settings.CookieFilePath; set => _settings.CookieFilePath = value; }
+
+        /// <summary>
+        /// Gets or sets the format of the file specified by <see cref="CookieFilePath"/>. Default is <see cref="Cysharp.Net.Http.CookieFileFormat.Json"/>.
+        /// </summary>
+        public CookieFileFormat? CookieFileFormat { get => _settings.CookieFileFormat; set => _settings.CookieFileFormat = value; }
+
+        /// <summary>
+        /// Gets or sets the path of the public suffix list file (e.g. <c>public_suffix_list.dat</c> from https://publicsuffix.org/) to reject the cookies for the public suffixes.
+        /// </summary>
+        /// <remarks>
+        /// If it is not set, only the cookies for the domains with a single label (e.g. <c>com</c>) are rejected.
+        /// </remarks>
+        public string? CookiePublicSuffixListPath { get => _settings.CookiePublicSuffixListPath; set => _settings.CookiePublicSuffixListPath = value; }
+
+        /// <summary>
+        /// Gets or sets a value that indicates whether to cache the responses in the native layer as a private HTTP cache (RFC 9111). Default is <c>false</c>.
+        /// </summary>
+        /// <remarks>
+        /// The responses of <c>GET</c> requests are stored according to <c>Cache-Control</c>, <c>Expires</c> and <c>Vary</c> headers, and the stale responses are revalidated with <c>ETag</c> and <c>Last-Modified</c>.
+        /// The responses have <c>Cache-Status</c> header (RFC 9211) to tell whether the response came from the cache. (e.g. <c>yaha; hit</c>, <c>yaha; fwd=uri-miss; fwd-status=200; stored</c>)
+        /// </remarks>
+        public bool? EnableHttpCache { get => _settings.EnableHttpCache; set => _settings.EnableHttpCache = value; }
+
+        /// <summary>
+        /// Gets or sets the directory to store the cached responses. The responses are kept in memory if it is not set.
+        /// </summary>
+        /// <remarks>
+        /// The directory is created if it doesn't exist. The responses are stored in <c>yaha-http-cache</c> subdirectory and used after the application is restarted.
+        /// The other files in the directory are not touched. The directory must not be shared by the handlers.
+        /// </remarks>
+        public string? HttpCacheDirectory { get => _settings.HttpCacheDirectory; set => _settings.HttpCacheDirectory = value; }
+
+        /// <summary>
+        /// Gets or sets the maximum total size of the cached responses in bytes. The least recently used responses are evicted. Default is 64 MiB.
+        /// </summary>
+        public ulong? HttpCacheMaxSize { get => _settings.HttpCacheMaxSize; set => _settings.HttpCacheMaxSize = value; }
+
+        /// <summary>
+        /// Gets or sets the maximum size of a cached response in bytes. The larger responses are not cached. Default is 8 MiB.
+        /// </summary>
+        public ulong? HttpCacheMaxEntrySize { get => _settings.HttpCacheMaxEntrySize; set => _settings.HttpCacheMaxEntrySize = value; }
+
+        /// <summary>
+        /// Gets or sets a value that indicates whether to decompress the response bodies (<c>gzip</c>, <c>deflate</c>, <c>br</c> and <c>zstd</c>) in the native layer. Default is <c>false</c>.
+        /// </summary>
+        /// <remarks>
+        /// <c>Accept-Encoding</c> header is added to the requests if the application doesn't set it. If the application sets <c>Accept-Encoding</c> header, the response bodies are returned as is.
+        /// The decompressed responses don't have <c>Content-Encoding</c> and <c>Content-Length</c> headers.
+        /// </remarks>
+        public bool? EnableAutomaticDecompression { get => _settings.EnableAutomaticDecompression; set => _settings.EnableAutomaticDecompression = value; }
+
+        /// <summary>
+        /// Gets or sets the URI of the proxy server (e.g. <c>http://proxy.example.com:8080</c>, <c>socks5h://proxy.example.com:1080</c>).
+        /// Supported schemes are <c>http</c>, <c>https</c>, <c>socks5</c>, <c>socks5h</c> and <c>socks4a</c>. If the URI contains user info, it is used for the proxy authentication.
+        /// </summary>
+        public string? Proxy { get => _settings.Proxy; set => _settings.Proxy = value; }
+
+        /// <summary>
+        /// Gets or sets a value that indicates whether to use the proxy configured by <c>HTTP_PROXY</c>, <c>HTTPS_PROXY</c>, <c>ALL_PROXY</c> and <c>NO_PROXY</c> environment variables.
+        /// If <see cref="Proxy"/> is set, this option is ignored.
+        /// </summary>
+        public bool? UseEnvironmentProxy { get => _settings.UseEnvironmentProxy; set => _settings.UseEnvironmentProxy = value; }
+
+        /// <summary>
+        /// Gets or sets a custom handler that selects the proxy for each destination. The handler returns the proxy URI, or <value>null</value> to connect directly.
+        /// If this handler is set, <see cref="Proxy"/> and <see cref="UseEnvironmentProxy"/> are ignored.
+        /// </summary>
+        /// <remarks>
+        /// The handler is called when a new connection is established, so the selected proxy is used for all requests on that connection.
+        /// </remarks>
+        public ProxySelectionHandler? OnSelectProxy { get => _settings.OnSelectProxy; set => _settings.OnSelectProxy = value; }
+
+        /// <summary>
+        /// Gets or sets the credentials for the proxy authentication. The credentials are sent with <c>Basic</c> scheme when the proxy requests the authentication.
+        /// If the proxy URI contains user info, it takes precedence over this option.
+        /// </summary>
+        public NetworkCredential? ProxyCredentials { get => _settings.ProxyCredentials; set => _settings.ProxyCredentials = value; }
+
+        /// <summary>
+        /// Gets or sets the token for the proxy authentication. The token is sent with <c>Bearer</c> scheme when the proxy requests the authentication.
+        /// </summary>
+        public string? ProxyBearerToken { get => _settings.ProxyBearerToken; set => _settings.ProxyBearerToken = value; }
+
+        /// <summary>
+        /// Gets or sets the addresses to connect to for <c>host:port</c> instead of resolving the host name, like curl's <c>--resolve</c> option.
+        /// The key is <c>host:port</c> and the value is the addresses (e.g. <c>192.0.2.1:443</c> or <c>192.0.2.1</c> to use the same port). The addresses are tried in order.
+        /// </summary>
+        /// <remarks>
+        /// The request URI is not changed, so <c>Host</c> header and the server name for TLS (SNI) keep the original host name.
+        /// </remarks>
+        public IDictionary<string, string[]>? ResolveOverrides { get => _settings.ResolveOverrides; set => _settings.ResolveOverrides = value; }
+
+        /// <summary>
+        /// Gets or sets the alternative <c>host:port</c> to connect to for <c>host:port</c>, like curl's <c>--connect-to</c> option.
+        /// <see cref="ResolveOverrides"/> is applied to the alternative <c>host:port</c>.
+        /// </summary>
+        /// <remarks>
+        /// The request URI is not changed, so <c>Host</c> header and the server name for TLS (SNI) keep the original host name.
+        /// </remarks>
+        public IDictionary<string, string>? ConnectTo { get => _settings.ConnectTo; set => _settings.ConnectTo = value; }
+
+        /// <summary>
+        /// Gets or sets a custom handler that resolves host names to IP addresses. By default, the host names are resolved by the operating system (getaddrinfo).
+        /// </summary>
+        /// <remarks>
+        /// The handler is called on the native worker thread, so it should not block and should complete the returned task asynchronously.
+        /// If the task throws an exception or returns no addresses, the connection fails.
+        /// If the task doesn't complete within <see cref="ConnectTimeout"/> (30 seconds if not set), the connection fails.
+        /// </remarks>
+        public HostNameResolutionHandler? OnResolveHostName { get => _settings.OnResolveHostName; set => _settings.OnResolveHostName = value; }
+
+        /// <summary>
+        /// Gets or sets a value that indicates whether to use the built-in asynchronous DNS resolver instead of the operating system's resolver (getaddrinfo).
+        /// The built-in resolver reads the system DNS configuration (e.g. <c>/etc/resolv.conf</c>) and provides TTL and negative answers for the DNS cache.
+        /// If the system DNS configuration is not available, the operating system's resolver is used.
+        /// </summary>
+        public bool? UseBuiltInDnsResolver { get => _settings.UseBuiltInDnsResolver; set => _settings.UseBuiltInDnsResolver = value; }
+
+        /// <summary>
+        /// Gets or sets the DNS servers used by the built-in resolver and SRV discovery. If the port is 0, the default port (53) is used.
+        /// By default, the servers of the system DNS configuration are used.
+        /// </summary>
+        public IPEndPoint[]? DnsServers { get => _settings.DnsServers; set => _settings.DnsServers = value; }
+
+        /// <summary>
+        /// Gets or sets a value that indicates whether to discover the endpoints of the service names (e.g. <c>_grpc._tcp.svc.internal</c>) with DNS SRV records.
+        /// </summary>
+        /// <remarks>
+        /// The targets are tried in order of the priority and the weight of the records, and the port of the request URI is ignored.
+        /// The request keeps the service name as <c>Host</c> header and the server name for TLS.
+        /// SRV records are always looked up with the built-in resolver, and the host names of the targets are resolved as usual.
+        /// </remarks>
+        public bool? EnableSrvDiscovery { get => _settings.EnableSrvDiscovery; set => _settings.EnableSrvDiscovery = value; }
+
+        /// <summary>
+        /// Gets or sets the endpoint of DNS-over-HTTPS (RFC 8484) to resolve host names (e.g. <c>https://dns.example/dns-query</c>).
+        /// The connection to the endpoint uses the same TLS settings as the destination (e.g. <see cref="RootCertificates"/>).
+        /// </summary>
+        /// <remarks>
+        /// <see cref="OnResolveHostName"/> takes precedence over this option.
+        /// </remarks>
+        public Uri? DnsOverHttpsEndpoint { get => _settings.DnsOverHttpsEndpoint; set => _settings.DnsOverHttpsEndpoint = value; }
+
+        /// <summary>
+        /// Gets or sets the addresses to connect to the endpoint of DNS-over-HTTPS instead of resolving its host name.
+        /// </summary>
+        public IPAddress[]? DnsOverHttpsBootstrapAddresses { get => _settings.DnsOverHttpsBootstrapAddresses; set => _settings.DnsOverHttpsBootstrapAddresses = value; }
+
+        /// <summary>
+        /// Gets or sets a value that indicates whether to cache the resolved addresses. Default is disabled.
+        /// </summary>
+        /// <remarks>
+        /// The cache is shared by all connections of the handler and can be cleared with <see cref="FlushDnsCache"/>.
+        /// </remarks>
+        public bool? EnableDnsCache { get => _settings.EnableDnsCache; set => _settings.EnableDnsCache = value; }
+
+        /// <summary>
+        /// Gets or sets the time to cache the addresses resolved by the resolver that doesn't provide TTL (the operating system's resolver or <see cref="OnResolveHostName"/>).
+        /// Default is 60 seconds.
+        /// </summary>
+        public TimeSpan? DnsCacheDefaultTtl { get => _settings.DnsCacheDefaultTtl; set => _settings.DnsCacheDefaultTtl = value; }
+
+        /// <summary>
+        /// Gets or sets the upper bound of the time to cache the resolved addresses. Default is 1 hour.
+        /// </summary>
+        public TimeSpan? DnsCacheMaxTtl { get => _settings.DnsCacheMaxTtl; set => _settings.DnsCacheMaxTtl = value; }
+
+        /// <summary>
+        /// Gets or sets the time to cache that the host name doesn't exist, if the DNS server doesn't provide it. Default is 10 seconds.
+        /// </summary>
+        public TimeSpan? DnsCacheNegativeTtl { get => _settings.DnsCacheNegativeTtl; set => _settings.DnsCacheNegativeTtl = value; }
+
+        /// <summary>
+        /// Gets or sets how long the expired addresses can be used while they are refreshed in background. Default is zero (disabled).
+        /// </summary>
+        public TimeSpan? DnsCacheStaleWhileRevalidate { get => _settings.DnsCacheStaleWhileRevalidate; set => _settings.DnsCacheStaleWhileRevalidate = value; }
+
         /// <summary>
         /// Gets or sets an interval for HTTP2 Ping frames should be sent to keep a connection alive.
         /// Pass <value>null</value> to disable HTTP2 keep-alive.
@@ -192,6 +599,15 @@ namespace Cysharp.Net.Http
             NativeRuntime.Instance.SetWorkerThreads(workerThreads);
         }
 
+        /// <summary>
+        /// Clears the DNS cache of the handler. The established connections are not affected.
+        /// </summary>
+        public void FlushDnsCache()
+        {
+            ThrowIfDisposed();
+            _handler?.FlushDnsCache();
+        }
+
         private NativeHttpHandlerCore SetupHandler()
         {
             var settings = _settings.Clone();
@@ -237,6 +653,110 @@ namespace Cysharp.Net.Http
     /// <returns></returns>
     public delegate bool ServerCertificateVerificationHandler(string serverName, ReadOnlySpan<byte> certificate, DateTimeOffset now);
 
+    /// <summary>
+    /// Represents a method that validates server certificates with the full chain and the result of the standard verification.
+    /// </summary>
+    /// <param name="context">The information of the verification.</param>
+    /// <returns><c>true</c> to trust the server; otherwise, <c>false</c>.</returns>
+    public delegate bool ServerCertificateChainVerificationHandler(ServerCertificateVerificationContext context);
+
+    /// <summary>
+    /// Represents a method that validates server certificates asynchronously with the full chain and the result of the standard verification.
+    /// </summary>
+    /// <param name="context">The information of the verification.</param>
+    /// <returns>A task that completes with <c>true</c> to trust the server; otherwise, <c>false</c>.</returns>
+    public delegate Task<bool> ServerCertificateChainVerificationAsyncHandler(ServerCertificateVerificationContext context);
+
+    /// <summary>
+    /// Represents a method that selects the proxy for the destination.
+    /// </summary>
+    /// <param name="destination">The destination URI.</param>
+    /// <returns>The proxy URI, or <value>null</value> to connect directly.</returns>
+    public delegate string? ProxySelectionHandler(Uri destination);
+
+    /// <summary>
+    /// Represents a method that resolves the host name to IP addresses.
+    /// </summary>
+    /// <param name="hostName">The host name to resolve.</param>
+    /// <returns>The IP addresses of the host. The addresses are tried in order.</returns>
+    public delegate Task<IPAddress[]> HostNameResolutionHandler(string hostName);
+
+    /// <summary>
+    /// Specifies the order of address families to connect to.
+    /// </summary>
+    public enum AddressFamilyPreference
+    {
+        /// <summary>Keeps the order of the addresses returned by the resolver.</summary>
+        Default,
+        /// <summary>Connects to IPv4 addresses only.</summary>
+        IPv4Only,
+        /// <summary>Connects to IPv6 addresses only.</summary>
+        IPv6Only,
+        /// <summary>Connects to IPv4 addresses first, and falls back to IPv6 addresses.</summary>
+        PreferIPv4,
+        /// <summary>Connects to IPv6 addresses first, and falls back to IPv4 addresses.</summary>
+        PreferIPv6,
+    }
+
+    /// <summary>
+    /// Specifies how to distribute the requests across the resolved addresses of a host.
+    /// </summary>
+    public enum LoadBalancingPolicy
+    {
+        /// <summary>Selects the addresses in turn.</summary>
+        RoundRobin,
+        /// <summary>Selects the address with the fewest outstanding requests.</summary>
+        LeastOutstandingRequests,
+        /// <summary>Selects two addresses at random and uses the one with fewer outstanding requests.</summary>
+        PowerOfTwoChoices,
+    }
+
+    /// <summary>
+    /// Specifies the format of the cookie file.
+    /// </summary>
+    public enum CookieFileFormat
+    {
+        /// <summary>The JSON format. All attributes of the cookies are kept.</summary>
+        Json,
+        /// <summary>The Netscape cookie file format used by curl and wget. <c>SameSite</c> attribute is not kept.</summary>
+        Netscape,
+    }
+
+    /// <summary>
+    /// Specifies the algorithm to compress the request body.
+    /// </summary>
+    public enum RequestBodyCompression
+    {
+        /// <summary>gzip (<c>Content-Encoding: gzip</c>). The level is 0-9.</summary>
+        Gzip,
+        /// <summary>Brotli (<c>Content-Encoding: br</c>). The level is 0-11.</summary>
+        Brotli,
+        /// <summary>Zstandard (<c>Content-Encoding: zstd</c>). The level is 1-22.</summary>
+        Zstd,
+    }
+
+    /// <summary>
+    /// Specifies the certificates to check the revocation status.
+    /// </summary>
+    public enum RevocationCheckDepth
+    {
+        /// <summary>Checks the end-entity certificate and the intermediate certificates. The trust anchor is not checked.</summary>
+        Chain,
+        /// <summary>Checks the end-entity certificate only.</summary>
+        EndEntity,
+    }
+
+    /// <summary>
+    /// Specifies how to handle the certificates whose revocation status is unknown.
+    /// </summary>
+    public enum UnknownRevocationStatusPolicy
+    {
+        /// <summary>Untrusts the certificate.</summary>
+        Deny,
+        /// <summary>Trusts the certificate.</summary>
+        Allow,
+    }
+
     internal class NativeClientSettings
     {
         public TimeSpan? PoolIdleTimeout { get; set; }
@@ -244,7 +764,17 @@ namespace Cysharp.Net.Http
         public bool? Http2Only { get; set; }
         public bool? SkipCertificateVerification { get; set; }
         public ServerCertificateVerificationHandler? OnVerifyServerCertificate { get; set; }
+        public ServerCertificateChainVerificationHandler? OnVerifyServerCertificateChain { get; set; }
+        public ServerCertificateChainVerificationAsyncHandler? OnVerifyServerCertificateChainAsync { get; set; }
+        public TimeSpan? ServerCertificateVerificationTimeout { get; set; }
         public string? RootCertificates { get; set; }
+        public bool? UseSystemRootCertificates { get; set; }
+        public bool? UseBuiltInRootCertificates { get; set; }
+        public IList<byte[]>? CertificateRevocationLists { get; set; }
+        public IList<string>? CertificateRevocationListFiles { get; set; }
+        public RevocationCheckDepth? RevocationCheckDepth { get; set; }
+        public UnknownRevocationStatusPolicy? UnknownRevocationStatusPolicy { get; set; }
+        public IList<PublicKeyPinSet>? PublicKeyPins { get; set; }
         public string? OverrideServerName { get; set; }
         public string? ClientAuthCertificates { get; set; }
         public string? ClientAuthKey { get; set; }
@@ -253,6 +783,53 @@ namespace Cysharp.Net.Http
         public bool? Http2AdaptiveWindow { get; set; }
         public uint? Http2MaxFrameSize { get; set; }
         public TimeSpan? ConnectTimeout { get; set; }
+        public AddressFamilyPreference? AddressFamilyPreference { get; set; }
+        public TimeSpan? HappyEyeballsFallbackDelay { get; set; }
+        public LoadBalancingPolicy? LoadBalancingPolicy { get; set; }
+        public TimeSpan? LoadBalancingReResolveInterval { get; set; }
+        public bool? EnableCircuitBreaker { get; set; }
+        public uint? CircuitBreakerConsecutiveFailures { get; set; }
+        public double? CircuitBreakerFailureRate { get; set; }
+        public uint? CircuitBreakerSamplingRequests { get; set; }
+        public TimeSpan? CircuitBreakerCooldown { get; set; }
+        public bool? EnableRetry { get; set; }
+        public uint? RetryMaxAttempts { get; set; }
+        public TimeSpan? RetryInitialBackoff { get; set; }
+        public TimeSpan? RetryMaxBackoff { get; set; }
+        public bool? RetryNonIdempotentRequests { get; set; }
+        public double? RetryBudgetRatio { get; set; }
+        public TimeSpan? HedgingDelay { get; set; }
+        public uint? HedgingMaxAttempts { get; set; }
+        public bool? AllowAutoRedirect { get; set; }
+        public int? MaxAutomaticRedirections { get; set; }
+        public bool? AllowHttpsToHttpRedirect { get; set; }
+        public bool? UseCookies { get; set; }
+        public string? CookieFilePath { get; set; }
+        public CookieFileFormat? CookieFileFormat { get; set; }
+        public string? CookiePublicSuffixListPath { get; set; }
+        public bool? EnableHttpCache { get; set; }
+        public string? HttpCacheDirectory { get; set; }
+        public ulong? HttpCacheMaxSize { get; set; }
+        public ulong? HttpCacheMaxEntrySize { get; set; }
+        public bool? EnableAutomaticDecompression { get; set; }
+        public string? Proxy { get; set; }
+        public bool? UseEnvironmentProxy { get; set; }
+        public ProxySelectionHandler? OnSelectProxy { get; set; }
+        public NetworkCredential? ProxyCredentials { get; set; }
+        public string? ProxyBearerToken { get; set; }
+        public IDictionary<string, string[]>? ResolveOverrides { get; set; }
+        public IDictionary<string, string>? ConnectTo { get; set; }
+        public HostNameResolutionHandler? OnResolveHostName { get; set; }
+        public bool? UseBuiltInDnsResolver { get; set; }
+        public IPEndPoint[]? DnsServers { get; set; }
+        public bool? EnableSrvDiscovery { get; set; }
+        public Uri? DnsOverHttpsEndpoint { get; set; }
+        public IPAddress[]? DnsOverHttpsBootstrapAddresses { get; set; }
+        public bool? EnableDnsCache { get; set; }
+        public TimeSpan? DnsCacheDefaultTtl { get; set; }
+        public TimeSpan? DnsCacheMaxTtl { get; set; }
+        public TimeSpan? DnsCacheNegativeTtl { get; set; }
+        public TimeSpan? DnsCacheStaleWhileRevalidate { get; set; }
         public TimeSpan? Http2KeepAliveInterval { get; set; }
         public TimeSpan? Http2KeepAliveTimeout { get; set; }
         public bool? Http2KeepAliveWhileIdle { get; set; }
@@ -271,7 +848,17 @@ namespace Cysharp.Net.Http
                 Http2Only = this.Http2Only,
                 SkipCertificateVerification = this.SkipCertificateVerification,
                 OnVerifyServerCertificate = this.OnVerifyServerCertificate,
+                OnVerifyServerCertificateChain = this.OnVerifyServerCertificateChain,
+                OnVerifyServerCertificateChainAsync = this.OnVerifyServerCertificateChainAsync,
+                ServerCertificateVerificationTimeout = this.ServerCertificateVerificationTimeout,
                 RootCertificates = this.RootCertificates,
+                UseSystemRootCertificates = this.UseSystemRootCertificates,
+                UseBuiltInRootCertificates = this.UseBuiltInRootCertificates,
+                CertificateRevocationLists = this.CertificateRevocationLists,
+                CertificateRevocationListFiles = this.CertificateRevocationListFiles,
+                RevocationCheckDepth = this.RevocationCheckDepth,
+                UnknownRevocationStatusPolicy = this.UnknownRevocationStatusPolicy,
+                PublicKeyPins = this.PublicKeyPins,
                 OverrideServerName = this.OverrideServerName,
                 ClientAuthCertificates = this.ClientAuthCertificates,
                 ClientAuthKey = this.ClientAuthKey,
@@ -280,6 +867,53 @@ namespace Cysharp.Net.Http
                 Http2AdaptiveWindow = this.Http2AdaptiveWindow,
                 Http2MaxFrameSize = this.Http2MaxFrameSize,
                 ConnectTimeout = this.ConnectTimeout,
+                AddressFamilyPreference = this.AddressFamilyPreference,
+                HappyEyeballsFallbackDelay = this.HappyEyeballsFallbackDelay,
+                LoadBalancingPolicy = this.LoadBalancingPolicy,
+                LoadBalancingReResolveInterval = this.LoadBalancingReResolveInterval,
+                EnableCircuitBreaker = this.EnableCircuitBreaker,
+                CircuitBreakerConsecutiveFailures = this.CircuitBreakerConsecutiveFailures,
+                CircuitBreakerFailureRate = this.CircuitBreakerFailureRate,
+                CircuitBreakerSamplingRequests = this.CircuitBreakerSamplingRequests,
+                CircuitBreakerCooldown = this.CircuitBreakerCooldown,
+                EnableRetry = this.EnableRetry,
+                RetryMaxAttempts = this.RetryMaxAttempts,
+                RetryInitialBackoff = this.RetryInitialBackoff,
+                RetryMaxBackoff = this.RetryMaxBackoff,
+                RetryNonIdempotentRequests = this.RetryNonIdempotentRequests,
+                RetryBudgetRatio = this.RetryBudgetRatio,
+                HedgingDelay = this.HedgingDelay,
+                HedgingMaxAttempts = this.HedgingMaxAttempts,
+                AllowAutoRedirect = this.AllowAutoRedirect,
+                MaxAutomaticRedirections = this.MaxAutomaticRedirections,
+                AllowHttpsToHttpRedirect = this.AllowHttpsToHttpRedirect,
+                UseCookies = this.UseCookies,
+                CookieFilePath = this.CookieFilePath,
+                CookieFileFormat = this.CookieFileFormat,
+                CookiePublicSuffixListPath = this.CookiePublicSuffixListPath,
+                EnableHttpCache = this.EnableHttpCache,
+                HttpCacheDirectory = this.HttpCacheDirectory,
+                HttpCacheMaxSize = this.HttpCacheMaxSize,
+                HttpCacheMaxEntrySize = this.HttpCacheMaxEntrySize,
+                EnableAutomaticDecompression = this.EnableAutomaticDecompression,
+                Proxy = this.Proxy,
+                UseEnvironmentProxy = this.UseEnvironmentProxy,
+                OnSelectProxy = this.OnSelectProxy,
+                ProxyCredentials = this.ProxyCredentials,
+                ProxyBearerToken = this.ProxyBearerToken,
+                ResolveOverrides = this.ResolveOverrides,
+                ConnectTo = this.ConnectTo,
+                OnResolveHostName = this.OnResolveHostName,
+                UseBuiltInDnsResolver = this.UseBuiltInDnsResolver,
+                DnsServers = this.DnsServers,
+                EnableSrvDiscovery = this.EnableSrvDiscovery,
+                DnsOverHttpsEndpoint = this.DnsOverHttpsEndpoint,
+                DnsOverHttpsBootstrapAddresses = this.DnsOverHttpsBootstrapAddresses,
+                EnableDnsCache = this.EnableDnsCache,
+                DnsCacheDefaultTtl = this.DnsCacheDefaultTtl,
+                DnsCacheMaxTtl = this.DnsCacheMaxTtl,
+                DnsCacheNegativeTtl = this.DnsCacheNegativeTtl,
+                DnsCacheStaleWhileRevalidate = this.DnsCacheStaleWhileRevalidate,
                 Http2KeepAliveInterval = this.Http2KeepAliveInterval,
                 Http2KeepAliveTimeout = this.Http2KeepAliveTimeout,
                 Http2KeepAliveWhileIdle = this.Http2KeepAliveWhileIdle,
@@ -0,0 +1,113 @@
+using System.Net;
+using Cysharp.Net.Http;
+
+namespace _YetAnotherHttpHandler.Test;
+
+public class AddressFamilyPreferenceTest(ITestOutputHelper testOutputHelper) : UseTestServerTestBase(testOutputHelper)
+{
+    // The documentation address (RFC 3849). It is not reachable.
+    private static readonly IPAddress UnreachableIPv6Address = IPAddress.Parse("2001:db8::1");
+
+    [Fact]
+    public async Task IPv4Only()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(TestServerListenMode.InsecureHttp1Only);
+        var port = new Uri(server.BaseUri).Port;
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            AddressFamilyPreference = AddressFamilyPreference.IPv4Only,
+            OnResolveHostName = hostName => Task.FromResult(new[] { UnreachableIPv6Address, IPAddress.Loopback }),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync($"http://service.example.invalid:{port}/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("__OK__", result);
+    }
+
+    [Fact]
+    public async Task IPv6Only_No_IPv6_Addresses()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(TestServerListenMode.InsecureHttp1Only);
+        var port = new Uri(server.BaseUri).Port;
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            AddressFamilyPreference = AddressFamilyPreference.IPv6Only,
+            OnResolveHostName = hostName => Task.FromResult(new[] { IPAddress.Loopback }),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync($"http://service.example.invalid:{port}/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<HttpRequestException>(ex);
+    }
+
+    [Fact]
+    public async Task PreferIPv4()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(TestServerListenMode.InsecureHttp1Only);
+        var port = new Uri(server.BaseUri).Port;
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            AddressFamilyPreference = AddressFamilyPreference.PreferIPv4,
+            // If the IPv6 address is tried first, the connection waits for the fallback delay.
+            HappyEyeballsFallbackDelay = TimeSpan.FromMinutes(10),
+            ConnectTimeout = TimeSpan.FromMinutes(10),
+            OnResolveHostName = hostName => Task.FromResult(new[] { UnreachableIPv6Address, IPAddress.Loopback }),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync($"http://service.example.invalid:{port}/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("__OK__", result);
+    }
+
+    [Fact]
+    public async Task HappyEyeballs_Fallback_To_IPv4()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(TestServerListenMode.InsecureHttp1Only);
+        var port = new Uri(server.BaseUri).Port;
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            // The unreachable IPv6 address is tried first, and the IPv4 address is tried after the delay.
+            AddressFamilyPreference = AddressFamilyPreference.PreferIPv6,
+            HappyEyeballsFallbackDelay = TimeSpan.FromMilliseconds(100),
+            ConnectTimeout = TimeSpan.FromMinutes(10),
+            OnResolveHostName = hostName => Task.FromResult(new[] { IPAddress.Loopback, UnreachableIPv6Address }),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync($"http://service.example.invalid:{port}/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("__OK__", result);
+    }
+
+    [Fact]
+    public async Task Undefined_Value()
+    {
+        // Arrange
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            AddressFamilyPreference = (AddressFamilyPreference)42,
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetAsync("http://localhost/"));
+
+        // Assert
+        Assert.IsType<ArgumentOutOfRangeException>(ex);
+    }
+}
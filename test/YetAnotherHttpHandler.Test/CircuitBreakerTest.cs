@@ -0,0 +1,160 @@
+using System.Net;
+using Cysharp.Net.Http;
+using Microsoft.AspNetCore.Builder;
+using Microsoft.AspNetCore.Http;
+
+namespace _YetAnotherHttpHandler.Test;
+
+public class CircuitBreakerTest(ITestOutputHelper testOutputHelper) : UseTestServerTestBase(testOutputHelper)
+{
+    [Fact]
+    public async Task Open_After_Consecutive_Connection_Failures()
+    {
+        // Arrange
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            EnableCircuitBreaker = true,
+            CircuitBreakerConsecutiveFailures = 2,
+            CircuitBreakerCooldown = TimeSpan.FromMinutes(1),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        // The port 1 is not listened.
+        var ex1 = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync("http://127.0.0.1:1/").WaitAsync(TimeoutToken));
+        var ex2 = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync("http://127.0.0.1:1/").WaitAsync(TimeoutToken));
+        var ex3 = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync("http://127.0.0.1:1/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<HttpRequestException>(ex1);
+        Assert.IsNotType<CircuitBreakerOpenException>(ex1.InnerException);
+        Assert.IsType<HttpRequestException>(ex2);
+        Assert.IsNotType<CircuitBreakerOpenException>(ex2.InnerException);
+        Assert.IsType<HttpRequestException>(ex3);
+        Assert.IsType<CircuitBreakerOpenException>(ex3.InnerException);
+    }
+
+    [Fact]
+    public async Task Open_After_Server_Errors()
+    {
+        // Arrange
+        var requestCount = 0;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/", (HttpContext context) =>
+        {
+            Interlocked.Increment(ref requestCount);
+            context.Response.StatusCode = StatusCodes.Status503ServiceUnavailable;
+            return context.Response.WriteAsync("__OK__");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            EnableCircuitBreaker = true,
+            CircuitBreakerConsecutiveFailures = 2,
+            CircuitBreakerCooldown = TimeSpan.FromMinutes(1),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var response1 = await httpClient.GetAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+        var response2 = await httpClient.GetAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.Equal(HttpStatusCode.ServiceUnavailable, response1.StatusCode);
+        Assert.Equal(HttpStatusCode.ServiceUnavailable, response2.StatusCode);
+        Assert.IsType<HttpRequestException>(ex);
+        Assert.IsType<CircuitBreakerOpenException>(ex.InnerException);
+        Assert.Equal(2, requestCount);
+    }
+
+    [Fact]
+    public async Task Close_After_Successful_Probe()
+    {
+        // Arrange
+        var statusCode = HttpStatusCode.ServiceUnavailable;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/", (HttpContext context) =>
+        {
+            context.Response.StatusCode = (int)statusCode;
+            return context.Response.WriteAsync("__OK__");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            EnableCircuitBreaker = true,
+            CircuitBreakerConsecutiveFailures = 1,
+            CircuitBreakerCooldown = TimeSpan.FromMilliseconds(500),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        await httpClient.GetAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken));
+        statusCode = HttpStatusCode.OK;
+        await Task.Delay(1000);
+        var probeResponse = await httpClient.GetAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+        var response = await httpClient.GetAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.IsType<CircuitBreakerOpenException>(ex?.InnerException);
+        Assert.Equal(HttpStatusCode.OK, probeResponse.StatusCode);
+        Assert.Equal(HttpStatusCode.OK, response.StatusCode);
+    }
+
+    [Fact]
+    public async Task Failure_Rate()
+    {
+        // Arrange
+        var statusCode = HttpStatusCode.OK;
+        var requestCount = 0;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/", (HttpContext context) =>
+        {
+            Interlocked.Increment(ref requestCount);
+            context.Response.StatusCode = (int)statusCode;
+            return context.Response.WriteAsync("__OK__");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            EnableCircuitBreaker = true,
+            CircuitBreakerConsecutiveFailures = 0,
+            CircuitBreakerFailureRate = 0.5,
+            CircuitBreakerSamplingRequests = 4,
+            CircuitBreakerCooldown = TimeSpan.FromMinutes(1),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        await httpClient.GetAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+        await httpClient.GetAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+        statusCode = HttpStatusCode.InternalServerError;
+        await httpClient.GetAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+        await httpClient.GetAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<CircuitBreakerOpenException>(ex?.InnerException);
+        Assert.Equal(4, requestCount);
+    }
+
+    [Fact]
+    public async Task Disabled()
+    {
+        // Arrange
+        var requestCount = 0;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/", (HttpContext context) =>
+        {
+            Interlocked.Increment(ref requestCount);
+            context.Response.StatusCode = StatusCodes.Status503ServiceUnavailable;
+            return context.Response.WriteAsync("__OK__");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler();
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        for (var i = 0; i < 10; i++)
+        {
+            var response = await httpClient.GetAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+            Assert.Equal(HttpStatusCode.ServiceUnavailable, response.StatusCode);
+        }
+
+        // Assert
+        Assert.Equal(10, requestCount);
+    }
+}
@@ -0,0 +1,93 @@
+using Cysharp.Net.Http;
+
+namespace _YetAnotherHttpHandler.Test;
+
+public class ConnectOverridesTest(ITestOutputHelper testOutputHelper) : UseTestServerTestBase(testOutputHelper)
+{
+    [Fact]
+    public async Task ResolveOverrides()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(TestServerListenMode.InsecureHttp1Only);
+        var port = new Uri(server.BaseUri).Port;
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            ResolveOverrides = new Dictionary<string, string[]>()
+            {
+                [$"example.invalid:{port}"] = ["127.0.0.1"],
+            },
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync($"http://example.invalid:{port}/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("__OK__", result);
+    }
+
+    [Fact]
+    public async Task ResolveOverrides_Fallback_To_Next_Address()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(TestServerListenMode.InsecureHttp1Only);
+        var port = new Uri(server.BaseUri).Port;
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            ResolveOverrides = new Dictionary<string, string[]>()
+            {
+                ["example.invalid:80"] = ["127.0.0.1:1", $"127.0.0.1:{port}"],
+            },
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync("http://example.invalid/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("__OK__", result);
+    }
+
+    [Fact]
+    public async Task ConnectTo_Keeps_ServerName()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(TestServerListenMode.SecureHttp1AndHttp2);
+        var port = new Uri(server.BaseUri).Port;
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            ConnectTo = new Dictionary<string, string>()
+            {
+                ["localhost:1"] = $"127.0.0.1:{port}",
+            },
+            RootCertificates = File.ReadAllText("./Certificates/localhost.crt"),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        // The certificate is verified with the original host name (localhost).
+        var result = await httpClient.GetStringAsync("https://localhost:1/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("__OK__", result);
+    }
+
+    [Theory]
+    [InlineData("example.com", "127.0.0.1")]
+    [InlineData("example.com:443", "not-an-address")]
+    public async Task ResolveOverrides_Invalid(string hostPort, string address)
+    {
+        // Arrange
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            ResolveOverrides = new Dictionary<string, string[]>() { [hostPort] = [address] },
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync("http://localhost/"));
+
+        // Assert
+        Assert.IsType<ArgumentException>(ex);
+    }
+}
@@ -0,0 +1,200 @@
+using Cysharp.Net.Http;
+using Microsoft.AspNetCore.Builder;
+using Microsoft.AspNetCore.Http;
+
+namespace _YetAnotherHttpHandler.Test;
+
+public class CookieTest(ITestOutputHelper testOutputHelper) : UseTestServerTestBase(testOutputHelper)
+{
+    [Fact]
+    public async Task Disabled()
+    {
+        // Arrange
+        // The server sets cookies at `/set`, and echoes the `Cookie` header of the request at `/echo`.
+        await using var server = await LaunchServerAsync(app =>
+        {
+            app.MapGet("/set", (HttpContext context) =>
+            {
+                context.Response.StatusCode = StatusCodes.Status204NoContent;
+                context.Response.Headers.Append("Set-Cookie", "session=__SESSION__; Path=/");
+            });
+            app.MapGet("/echo", (HttpContext context) => context.Response.WriteAsync(context.Request.Headers.Cookie.ToString()));
+        });
+        using var httpHandler = new YetAnotherHttpHandler();
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        await httpClient.GetAsync($"{server.BaseUri}/set").WaitAsync(TimeoutToken);
+        var result = await httpClient.GetStringAsync($"{server.BaseUri}/echo").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("", result);
+    }
+
+    [Fact]
+    public async Task Store_And_Send()
+    {
+        // Arrange
+        // The server sets cookies at `/set`, and echoes the `Cookie` header of the request at `/echo`.
+        await using var server = await LaunchServerAsync(app =>
+        {
+            app.MapGet("/set", (HttpContext context) =>
+            {
+                context.Response.StatusCode = StatusCodes.Status204NoContent;
+                context.Response.Headers.Append("Set-Cookie", "session=__SESSION__; Path=/");
+                context.Response.Headers.Append("Set-Cookie", "persistent=__PERSISTENT__; Path=/; Max-Age=3600");
+                context.Response.Headers.Append("Set-Cookie", "other=__OTHER__; Path=/other");
+                context.Response.Headers.Append("Set-Cookie", "secure=__SECURE__; Path=/; Secure");
+            });
+            app.MapGet("/echo", (HttpContext context) => context.Response.WriteAsync(context.Request.Headers.Cookie.ToString()));
+        });
+        using var httpHandler = new YetAnotherHttpHandler() { UseCookies = true };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        await httpClient.GetAsync($"{server.BaseUri}/set").WaitAsync(TimeoutToken);
+        var result = await httpClient.GetStringAsync($"{server.BaseUri}/echo").WaitAsync(TimeoutToken);
+
+        // Assert
+        // The cookie with `Path=/other` and the `Secure` cookie are not sent.
+        Assert.Equal("session=__SESSION__; persistent=__PERSISTENT__", result);
+    }
+
+    [Fact]
+    public async Task Append_To_Cookie_Header()
+    {
+        // Arrange
+        // The server sets cookies at `/set`, and echoes the `Cookie` header of the request at `/echo`.
+        await using var server = await LaunchServerAsync(app =>
+        {
+            app.MapGet("/set", (HttpContext context) =>
+            {
+                context.Response.StatusCode = StatusCodes.Status204NoContent;
+                context.Response.Headers.Append("Set-Cookie", "session=__SESSION__; Path=/");
+                context.Response.Headers.Append("Set-Cookie", "persistent=__PERSISTENT__; Path=/; Max-Age=3600");
+            });
+            app.MapGet("/echo", (HttpContext context) => context.Response.WriteAsync(context.Request.Headers.Cookie.ToString()));
+        });
+        using var httpHandler = new YetAnotherHttpHandler() { UseCookies = true };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        await httpClient.GetAsync($"{server.BaseUri}/set").WaitAsync(TimeoutToken);
+        var request = new HttpRequestMessage(HttpMethod.Get, $"{server.BaseUri}/echo");
+        request.Headers.Add("Cookie", "custom=__CUSTOM__");
+        var response = await httpClient.SendAsync(request).WaitAsync(TimeoutToken);
+        var result = await response.Content.ReadAsStringAsync().WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("custom=__CUSTOM__; session=__SESSION__; persistent=__PERSISTENT__", result);
+    }
+
+    [Fact]
+    public async Task Send_On_Redirect()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(app =>
+        {
+            app.MapGet("/set-and-redirect", (HttpContext context) =>
+            {
+                context.Response.Headers.Append("Set-Cookie", "redirect=__REDIRECT__; Path=/");
+                context.Response.Redirect("/echo");
+            });
+            app.MapGet("/echo", (HttpContext context) => context.Response.WriteAsync(context.Request.Headers.Cookie.ToString()));
+        });
+        using var httpHandler = new YetAnotherHttpHandler() { UseCookies = true, AllowAutoRedirect = true };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync($"{server.BaseUri}/set-and-redirect").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("redirect=__REDIRECT__", result);
+    }
+
+    [Theory]
+    [InlineData(CookieFileFormat.Json)]
+    [InlineData(CookieFileFormat.Netscape)]
+    public async Task Persist_To_File(CookieFileFormat format)
+    {
+        // Arrange
+        // The server sets cookies at `/set`, and echoes the `Cookie` header of the request at `/echo`.
+        await using var server = await LaunchServerAsync(app =>
+        {
+            app.MapGet("/set", (HttpContext context) =>
+            {
+                context.Response.StatusCode = StatusCodes.Status204NoContent;
+                context.Response.Headers.Append("Set-Cookie", "session=__SESSION__; Path=/");
+                context.Response.Headers.Append("Set-Cookie", "persistent=__PERSISTENT__; Path=/; Max-Age=3600");
+            });
+            app.MapGet("/echo", (HttpContext context) => context.Response.WriteAsync(context.Request.Headers.Cookie.ToString()));
+        });
+        var cookieFilePath = Path.Combine(Path.GetTempPath(), $"yaha-cookies-{Guid.NewGuid()}.txt");
+        try
+        {
+            using (var httpHandler = new YetAnotherHttpHandler() { UseCookies = true, CookieFilePath = cookieFilePath, CookieFileFormat = format })
+            {
+                var httpClient = new HttpClient(httpHandler);
+                await httpClient.GetAsync($"{server.BaseUri}/set").WaitAsync(TimeoutToken);
+            }
+            // The cookies are saved in the background.
+            while (!File.Exists(cookieFilePath))
+            {
+                await Task.Delay(10, TimeoutToken);
+            }
+
+            // Act
+            using var httpHandler2 = new YetAnotherHttpHandler() { UseCookies = true, CookieFilePath = cookieFilePath, CookieFileFormat = format };
+            var httpClient2 = new HttpClient(httpHandler2);
+            var result = await httpClient2.GetStringAsync($"{server.BaseUri}/echo").WaitAsync(TimeoutToken);
+
+            // Assert
+            // The session cookie is not saved.
+            Assert.Equal("persistent=__PERSISTENT__", result);
+        }
+        finally
+        {
+            File.Delete(cookieFilePath);
+        }
+    }
+
+    [Fact]
+    public async Task Invalid_Cookie_File()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(app => app.MapGet("/echo", (HttpContext context) => context.Response.WriteAsync(context.Request.Headers.Cookie.ToString())));
+        var cookieFilePath = Path.Combine(Path.GetTempPath(), $"yaha-cookies-{Guid.NewGuid()}.txt");
+        await File.WriteAllTextAsync(cookieFilePath, "invalid\tline");
+        try
+        {
+            using var httpHandler = new YetAnotherHttpHandler() { UseCookies = true, CookieFilePath = cookieFilePath, CookieFileFormat = CookieFileFormat.Netscape };
+            var httpClient = new HttpClient(httpHandler);
+
+            // Act
+            var ex = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync($"{server.BaseUri}/echo").WaitAsync(TimeoutToken));
+
+            // Assert
+            Assert.IsType<ArgumentException>(ex);
+        }
+        finally
+        {
+            File.Delete(cookieFilePath);
+        }
+    }
+
+    [Fact]
+    public async Task Undefined_Cookie_File_Format()
+    {
+        // Arrange
+        var cookieFilePath = Path.Combine(Path.GetTempPath(), $"yaha-cookies-{Guid.NewGuid()}.txt");
+        using var httpHandler = new YetAnotherHttpHandler() { UseCookies = true, CookieFilePath = cookieFilePath, CookieFileFormat = (CookieFileFormat)42 };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetAsync("http://localhost/"));
+
+        // Assert
+        Assert.IsType<ArgumentException>(ex);
+        Assert.False(File.Exists(cookieFilePath));
+    }
+}
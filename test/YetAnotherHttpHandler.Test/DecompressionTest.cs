@@ -0,0 +1,146 @@
+using System.IO.Compression;
+using System.Text;
+using Cysharp.Net.Http;
+using Microsoft.AspNetCore.Builder;
+using Microsoft.AspNetCore.Http;
+
+namespace _YetAnotherHttpHandler.Test;
+
+public class DecompressionTest(ITestOutputHelper testOutputHelper) : UseTestServerTestBase(testOutputHelper)
+{
+    private static readonly string Content = string.Concat(Enumerable.Repeat("Hello, compressed world! ", 1000));
+
+    [Fact]
+    public async Task Disabled()
+    {
+        // Arrange
+        string? acceptEncoding = null;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/", (HttpContext context) =>
+        {
+            acceptEncoding = context.Request.Headers.AcceptEncoding;
+            var body = Compress("gzip", Encoding.UTF8.GetBytes(Content));
+            context.Response.Headers.ContentEncoding = "gzip";
+            context.Response.ContentLength = body.Length;
+            return context.Response.Body.WriteAsync(body).AsTask();
+        }));
+        using var httpHandler = new YetAnotherHttpHandler();
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var response = await httpClient.GetAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Null(acceptEncoding);
+        Assert.Equal("gzip", response.Content.Headers.ContentEncoding.Single());
+    }
+
+    [Theory]
+    [InlineData("gzip")]
+    [InlineData("deflate")]
+    [InlineData("br")]
+    public async Task Decompress(string coding)
+    {
+        // Arrange
+        string? acceptEncoding = null;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/", (HttpContext context) =>
+        {
+            acceptEncoding = context.Request.Headers.AcceptEncoding;
+            var body = Compress(coding, Encoding.UTF8.GetBytes(Content));
+            context.Response.Headers.ContentEncoding = coding;
+            context.Response.ContentLength = body.Length;
+            return context.Response.Body.WriteAsync(body).AsTask();
+        }));
+        using var httpHandler = new YetAnotherHttpHandler() { EnableAutomaticDecompression = true };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var response = await httpClient.GetAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+        var result = await response.Content.ReadAsStringAsync().WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("gzip, deflate, br, zstd", acceptEncoding);
+        Assert.Equal(Content, result);
+        Assert.Empty(response.Content.Headers.ContentEncoding);
+        Assert.Null(response.Content.Headers.ContentLength);
+    }
+
+    [Fact]
+    public async Task Uncompressed()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(app => app.MapGet("/", () => Content));
+        using var httpHandler = new YetAnotherHttpHandler() { EnableAutomaticDecompression = true };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal(Content, result);
+    }
+
+    [Fact]
+    public async Task Accept_Encoding_Set_By_Application()
+    {
+        // Arrange
+        string? acceptEncoding = null;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/", (HttpContext context) =>
+        {
+            acceptEncoding = context.Request.Headers.AcceptEncoding;
+            var body = Compress("gzip", Encoding.UTF8.GetBytes(Content));
+            context.Response.Headers.ContentEncoding = "gzip";
+            context.Response.ContentLength = body.Length;
+            return context.Response.Body.WriteAsync(body).AsTask();
+        }));
+        using var httpHandler = new YetAnotherHttpHandler() { EnableAutomaticDecompression = true };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var request = new HttpRequestMessage(HttpMethod.Get, $"{server.BaseUri}/");
+        request.Headers.Add("Accept-Encoding", "gzip");
+        var response = await httpClient.SendAsync(request).WaitAsync(TimeoutToken);
+        var result = await response.Content.ReadAsByteArrayAsync().WaitAsync(TimeoutToken);
+
+        // Assert
+        // The response body is returned as is.
+        Assert.Equal("gzip", acceptEncoding);
+        Assert.Equal("gzip", response.Content.Headers.ContentEncoding.Single());
+        using var decompressed = new StreamReader(new GZipStream(new MemoryStream(result), CompressionMode.Decompress));
+        Assert.Equal(Content, await decompressed.ReadToEndAsync());
+    }
+
+    [Fact]
+    public async Task Invalid_Compressed_Body()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(app => app.MapGet("/", (HttpContext context) =>
+        {
+            context.Response.Headers.ContentEncoding = "gzip";
+            return context.Response.WriteAsync("not a gzip stream");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler() { EnableAutomaticDecompression = true };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<HttpRequestException>(ex);
+    }
+
+    private static byte[] Compress(string coding, byte[] data)
+    {
+        var output = new MemoryStream();
+        using (Stream stream = coding switch
+        {
+            "gzip" => new GZipStream(output, CompressionLevel.Optimal),
+            "deflate" => new ZLibStream(output, CompressionLevel.Optimal),
+            "br" => new BrotliStream(output, CompressionLevel.Optimal),
+            _ => throw new ArgumentOutOfRangeException(nameof(coding)),
+        })
+        {
+            stream.Write(data);
+        }
+        return output.ToArray();
+    }
+}
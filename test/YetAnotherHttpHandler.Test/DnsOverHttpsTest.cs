@@ -0,0 +1,150 @@
+using System.Net;
+using System.Security.Cryptography.X509Certificates;
+using _YetAnotherHttpHandler.Test.Helpers;
+using Cysharp.Net.Http;
+
+namespace _YetAnotherHttpHandler.Test;
+
+public class DnsOverHttpsTest(ITestOutputHelper testOutputHelper) : UseTestServerTestBase(testOutputHelper)
+{
+    [Fact]
+    public async Task Resolve()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(TestServerListenMode.InsecureHttp1Only);
+        var port = new Uri(server.BaseUri).Port;
+        await using var dohServer = new TestDohServer(LoadLocalhostCertificate());
+        dohServer.Records["service.example.invalid"] = [IPAddress.Loopback];
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            RootCertificates = File.ReadAllText("./Certificates/localhost.crt"),
+            DnsOverHttpsEndpoint = dohServer.Uri,
+            DnsOverHttpsBootstrapAddresses = [IPAddress.Loopback],
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync($"http://service.example.invalid:{port}/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("__OK__", result);
+        Assert.Equal(["service.example.invalid/A", "service.example.invalid/AAAA"], dohServer.ReceivedQueries.Order());
+    }
+
+    [Fact]
+    public async Task Bootstrap_Address()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(TestServerListenMode.InsecureHttp1Only);
+        var port = new Uri(server.BaseUri).Port;
+        await using var dohServer = new TestDohServer(LoadLocalhostCertificate());
+        dohServer.Records["service.example.invalid"] = [IPAddress.Loopback];
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            SkipCertificateVerification = true, // The certificate of the DoH server is for `localhost`.
+            // The host name of the endpoint can't be resolved, so the bootstrap address is required.
+            DnsOverHttpsEndpoint = new Uri($"https://dns.example.invalid:{dohServer.Port}/dns-query"),
+            DnsOverHttpsBootstrapAddresses = [IPAddress.Loopback],
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync($"http://service.example.invalid:{port}/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("__OK__", result);
+        Assert.NotEmpty(dohServer.ReceivedQueries);
+    }
+
+    [Fact]
+    public async Task NonExistentDomain()
+    {
+        // Arrange
+        await using var dohServer = new TestDohServer(LoadLocalhostCertificate());
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            RootCertificates = File.ReadAllText("./Certificates/localhost.crt"),
+            DnsOverHttpsEndpoint = dohServer.Uri,
+            DnsOverHttpsBootstrapAddresses = [IPAddress.Loopback],
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync("http://service.example.invalid/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<HttpRequestException>(ex);
+        Assert.NotEmpty(dohServer.ReceivedQueries);
+    }
+
+    [Fact]
+    public async Task NegativeCache()
+    {
+        // Arrange
+        await using var dohServer = new TestDohServer(LoadLocalhostCertificate()) { NegativeTtl = 60 };
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            RootCertificates = File.ReadAllText("./Certificates/localhost.crt"),
+            DnsOverHttpsEndpoint = dohServer.Uri,
+            DnsOverHttpsBootstrapAddresses = [IPAddress.Loopback],
+            EnableDnsCache = true,
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        await Record.ExceptionAsync(async () => await httpClient.GetStringAsync("http://service.example.invalid/").WaitAsync(TimeoutToken));
+        var queryCount = dohServer.ReceivedQueries.Count;
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync("http://service.example.invalid/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<HttpRequestException>(ex);
+        Assert.Equal(2, queryCount); // A and AAAA
+        Assert.Equal(2, dohServer.ReceivedQueries.Count);
+    }
+
+    [Fact]
+    public async Task Untrusted_Certificate()
+    {
+        // Arrange
+        await using var dohServer = new TestDohServer(LoadLocalhostCertificate());
+        dohServer.Records["service.example.invalid"] = [IPAddress.Loopback];
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            // The certificate of the DoH server is not trusted by the built-in root CA.
+            DnsOverHttpsEndpoint = dohServer.Uri,
+            DnsOverHttpsBootstrapAddresses = [IPAddress.Loopback],
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync("http://service.example.invalid/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<HttpRequestException>(ex);
+        Assert.Empty(dohServer.ReceivedQueries);
+    }
+
+    [Fact]
+    public async Task Invalid_Endpoint()
+    {
+        // Arrange
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            DnsOverHttpsEndpoint = new Uri("http://dns.example.invalid/dns-query"),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync("http://service.example.invalid/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<ArgumentException>(ex);
+    }
+
+    private static X509Certificate2 LoadLocalhostCertificate()
+    {
+        // NOTE: SslStream on Windows requires the private key to be persisted, so re-import it from PFX.
+        using var cert = X509Certificate2.CreateFromPemFile("./Certificates/localhost.crt", "./Certificates/localhost.key");
+        return new X509Certificate2(cert.Export(X509ContentType.Pfx));
+    }
+}
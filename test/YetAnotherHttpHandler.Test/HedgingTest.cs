@@ -0,0 +1,201 @@
+using Cysharp.Net.Http;
+using Microsoft.AspNetCore.Builder;
+using Microsoft.AspNetCore.Http;
+
+namespace _YetAnotherHttpHandler.Test;
+
+public class HedgingTest(ITestOutputHelper testOutputHelper) : UseTestServerTestBase(testOutputHelper)
+{
+    [Fact]
+    public async Task Use_First_Response()
+    {
+        // Arrange
+        var requestCount = 0;
+        var stalledConnectionClosed = new TaskCompletionSource(TaskCreationOptions.RunContinuationsAsynchronously);
+        // The server doesn't respond to the first request until the client closes the connection.
+        var stalls = 1;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/", async (HttpContext context) =>
+        {
+            Interlocked.Increment(ref requestCount);
+            if (Interlocked.Decrement(ref stalls) >= 0)
+            {
+                try
+                {
+                    await Task.Delay(Timeout.Infinite, context.RequestAborted);
+                }
+                catch (OperationCanceledException)
+                {
+                    stalledConnectionClosed.TrySetResult();
+                }
+                return;
+            }
+            await context.Response.WriteAsync("__OK__");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            HedgingDelay = TimeSpan.FromMilliseconds(100),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("__OK__", result);
+        Assert.Equal(2, requestCount);
+        // The stalled request is cancelled and its connection is closed.
+        await stalledConnectionClosed.Task.WaitAsync(TimeoutToken);
+    }
+
+    [Fact]
+    public async Task Not_Hedged_If_Responded_Within_Delay()
+    {
+        // Arrange
+        var requestCount = 0;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/", () =>
+        {
+            Interlocked.Increment(ref requestCount);
+            return "__OK__";
+        }));
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            HedgingDelay = TimeSpan.FromMilliseconds(500),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+        await Task.Delay(1000);
+
+        // Assert
+        Assert.Equal("__OK__", result);
+        Assert.Equal(1, requestCount);
+    }
+
+    [Fact]
+    public async Task Max_Attempts()
+    {
+        // Arrange
+        var requestCount = 0;
+        // The server doesn't respond to the first requests until the client closes the connection.
+        var stalls = 2;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/", async (HttpContext context) =>
+        {
+            Interlocked.Increment(ref requestCount);
+            if (Interlocked.Decrement(ref stalls) >= 0)
+            {
+                try
+                {
+                    await Task.Delay(Timeout.Infinite, context.RequestAborted);
+                }
+                catch (OperationCanceledException)
+                {
+                    // The client closed the connection.
+                }
+                return;
+            }
+            await context.Response.WriteAsync("__OK__");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            HedgingDelay = TimeSpan.FromMilliseconds(100),
+            HedgingMaxAttempts = 3,
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("__OK__", result);
+        Assert.Equal(3, requestCount);
+    }
+
+    [Fact]
+    public async Task Replace_Failed_Request_Immediately()
+    {
+        // Arrange
+        var requestCount = 0;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/", (HttpContext context) =>
+        {
+            if (Interlocked.Increment(ref requestCount) == 1)
+            {
+                // Close the connection without a response.
+                context.Abort();
+                return Task.CompletedTask;
+            }
+            return context.Response.WriteAsync("__OK__");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            HedgingDelay = TimeSpan.FromMinutes(1),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("__OK__", result);
+        Assert.Equal(2, requestCount);
+    }
+
+    [Fact]
+    public async Task Report_First_Error_If_All_Requests_Failed()
+    {
+        // Arrange
+        var requestCount = 0;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/", (HttpContext context) =>
+        {
+            Interlocked.Increment(ref requestCount);
+            context.Abort();
+            return Task.CompletedTask;
+        }));
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            HedgingDelay = TimeSpan.FromMinutes(1),
+            HedgingMaxAttempts = 3,
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<HttpRequestException>(ex);
+        Assert.Equal(3, requestCount);
+    }
+
+    [Fact]
+    public async Task Non_Idempotent_Request_Is_Not_Hedged()
+    {
+        // Arrange
+        var requestCount = 0;
+        // The server doesn't respond until the client closes the connection.
+        await using var server = await LaunchServerAsync(app => app.MapPost("/", async (HttpContext context) =>
+        {
+            Interlocked.Increment(ref requestCount);
+            try
+            {
+                await Task.Delay(Timeout.Infinite, context.RequestAborted);
+            }
+            catch (OperationCanceledException)
+            {
+                // The client closed the connection.
+            }
+        }));
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            HedgingDelay = TimeSpan.FromMilliseconds(100),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        using var cts = new CancellationTokenSource(TimeSpan.FromSeconds(1));
+        var ex = await Record.ExceptionAsync(async () => await httpClient.PostAsync($"{server.BaseUri}/", new StringContent("__BODY__"), cts.Token));
+
+        // Assert
+        Assert.IsAssignableFrom<OperationCanceledException>(ex);
+        Assert.Equal(1, requestCount);
+    }
+}
@@ -0,0 +1,138 @@
+using System.Buffers.Binary;
+using System.Collections.Concurrent;
+using System.Net;
+using System.Net.Sockets;
+using System.Text;
+
+namespace _YetAnotherHttpHandler.Test.Helpers;
+
+/// <summary>
+/// A minimal DNS server over UDP for testing. It answers A/AAAA/SRV queries with the registered records.
+/// </summary>
+public class TestDnsServer : IAsyncDisposable
+{
+    private const ushort TypeA = 1;
+    private const ushort TypeAAAA = 28;
+    private const ushort TypeSRV = 33;
+
+    private readonly UdpClient _udpClient;
+    private readonly CancellationTokenSource _cts = new();
+    private readonly Task _receiveLoop;
+
+    public IPEndPoint EndPoint { get; }
+    public ConcurrentDictionary<string, IPAddress[]> Addresses { get; } = new(StringComparer.OrdinalIgnoreCase);
+    public ConcurrentDictionary<string, SrvRecord[]> SrvRecords { get; } = new(StringComparer.OrdinalIgnoreCase);
+    /// <summary>
+    /// The received queries as <c>name/type</c>. (e.g. <c>_grpc._tcp.svc.example.invalid/SRV</c>)
+    /// </summary>
+    public ConcurrentQueue<string> ReceivedQueries { get; } = new();
+
+    public TestDnsServer()
+    {
+        _udpClient = new UdpClient(new IPEndPoint(IPAddress.Loopback, 0));
+        EndPoint = (IPEndPoint)_udpClient.Client.LocalEndPoint!;
+        _receiveLoop = ReceiveLoopAsync(_cts.Token);
+    }
+
+    private async Task ReceiveLoopAsync(CancellationToken cancellationToken)
+    {
+        while (!cancellationToken.IsCancellationRequested)
+        {
+            UdpReceiveResult result;
+            try
+            {
+                result = await _udpClient.ReceiveAsync(cancellationToken);
+            }
+            catch (OperationCanceledException)
+            {
+                break;
+            }
+            var response = CreateResponse(result.Buffer);
+            await _udpClient.SendAsync(response, result.RemoteEndPoint, cancellationToken);
+        }
+    }
+
+    private byte[] CreateResponse(byte[] query)
+    {
+        // Question: QNAME (labels) + QTYPE + QCLASS
+        var offset = 12;
+        var labels = new List<string>();
+        while (query[offset] != 0)
+        {
+            labels.Add(Encoding.ASCII.GetString(query, offset + 1, query[offset]));
+            offset += query[offset] + 1;
+        }
+        offset += 1;
+        var name = string.Join(".", labels);
+        var type = BinaryPrimitives.ReadUInt16BigEndian(query.AsSpan(offset));
+        var questionEnd = offset + 4;
+        ReceivedQueries.Enqueue($"{name}/{type switch { TypeA => "A", TypeAAAA => "AAAA", TypeSRV => "SRV", _ => type.ToString() }}");
+
+        var answers = new List<byte[]>();
+        if (type == TypeSRV && SrvRecords.TryGetValue(name, out var srvRecords))
+        {
+            foreach (var record in srvRecords)
+            {
+                var data = new byte[6];
+                BinaryPrimitives.WriteUInt16BigEndian(data.AsSpan(0), record.Priority);
+                BinaryPrimitives.WriteUInt16BigEndian(data.AsSpan(2), record.Weight);
+                BinaryPrimitives.WriteUInt16BigEndian(data.AsSpan(4), record.Port);
+                answers.Add([.. data, .. EncodeName(record.Target)]);
+            }
+        }
+        else if ((type == TypeA || type == TypeAAAA) && Addresses.TryGetValue(name, out var addresses))
+        {
+            var family = type == TypeAAAA ? AddressFamily.InterNetworkV6 : AddressFamily.InterNetwork;
+            answers.AddRange(addresses.Where(x => x.AddressFamily == family).Select(x => x.GetAddressBytes()));
+        }
+
+        using var stream = new MemoryStream();
+        var header = new byte[12];
+        query.AsSpan(0, 2).CopyTo(header); // ID
+        BinaryPrimitives.WriteUInt16BigEndian(header.AsSpan(2), 0x8180); // QR, RD, RA
+        BinaryPrimitives.WriteUInt16BigEndian(header.AsSpan(4), 1);
+        BinaryPrimitives.WriteUInt16BigEndian(header.AsSpan(6), (ushort)answers.Count);
+        stream.Write(header);
+        stream.Write(query.AsSpan(12, questionEnd - 12));
+
+        foreach (var data in answers)
+        {
+            var record = new byte[12];
+            BinaryPrimitives.WriteUInt16BigEndian(record.AsSpan(0), 0xc00c); // The pointer to QNAME
+            BinaryPrimitives.WriteUInt16BigEndian(record.AsSpan(2), type);
+            BinaryPrimitives.WriteUInt16BigEndian(record.AsSpan(4), 1); // IN
+            BinaryPrimitives.WriteUInt32BigEndian(record.AsSpan(6), 60);
+            BinaryPrimitives.WriteUInt16BigEndian(record.AsSpan(10), (ushort)data.Length);
+            stream.Write(record);
+            stream.Write(data);
+        }
+        return stream.ToArray();
+    }
+
+    private static byte[] EncodeName(string name)
+    {
+        var bytes = new List<byte>();
+        foreach (var label in name.Split('.', StringSplitOptions.RemoveEmptyEntries))
+        {
+            bytes.Add((byte)label.Length);
+            bytes.AddRange(Encoding.ASCII.GetBytes(label));
+        }
+        bytes.Add(0);
+        return bytes.ToArray();
+    }
+
+    public async ValueTask DisposeAsync()
+    {
+        _cts.Cancel();
+        _udpClient.Dispose();
+        try
+        {
+            await _receiveLoop;
+        }
+        catch (ObjectDisposedException)
+        {
+        }
+    }
+
+    public record SrvRecord(ushort Priority, ushort Weight, ushort Port, string Target);
+}
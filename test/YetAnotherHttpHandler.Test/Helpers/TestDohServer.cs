@@ -0,0 +1,182 @@
+using System.Buffers.Binary;
+using System.Collections.Concurrent;
+using System.Net;
+using System.Net.Security;
+using System.Net.Sockets;
+using System.Security.Cryptography.X509Certificates;
+using System.Text;
+
+namespace _YetAnotherHttpHandler.Test.Helpers;
+
+/// <summary>
+/// A minimal DNS-over-HTTPS (RFC 8484) server for testing. It answers A/AAAA queries with the registered addresses over HTTP/1.1 POST.
+/// </summary>
+public class TestDohServer : IAsyncDisposable
+{
+    private const ushort TypeA = 1;
+    private const ushort TypeAAAA = 28;
+    private const ushort TypeSOA = 6;
+
+    private readonly TcpListener _listener;
+    private readonly CancellationTokenSource _cts = new();
+    private readonly Task _acceptLoop;
+    private readonly X509Certificate2 _certificate;
+
+    public int Port { get; }
+    public Uri Uri { get; }
+    public ConcurrentDictionary<string, IPAddress[]> Records { get; } = new(StringComparer.OrdinalIgnoreCase);
+    public uint Ttl { get; set; } = 60;
+    public uint NegativeTtl { get; set; } = 30;
+    /// <summary>
+    /// The received queries as <c>name/type</c>. (e.g. <c>service.example.invalid/A</c>)
+    /// </summary>
+    public ConcurrentQueue<string> ReceivedQueries { get; } = new();
+
+    /// <param name="certificate">The server certificate for <c>localhost</c>.</param>
+    public TestDohServer(X509Certificate2 certificate)
+    {
+        _certificate = certificate;
+        _listener = new TcpListener(IPAddress.Loopback, 0);
+        _listener.Start();
+        Port = ((IPEndPoint)_listener.LocalEndpoint).Port;
+        Uri = new Uri($"https://localhost:{Port}/dns-query");
+        _acceptLoop = AcceptLoopAsync(_cts.Token);
+    }
+
+    private async Task AcceptLoopAsync(CancellationToken cancellationToken)
+    {
+        while (!cancellationToken.IsCancellationRequested)
+        {
+            TcpClient client;
+            try
+            {
+                client = await _listener.AcceptTcpClientAsync(cancellationToken);
+            }
+            catch (OperationCanceledException)
+            {
+                break;
+            }
+            _ = HandleClientAsync(client, cancellationToken);
+        }
+    }
+
+    private async Task HandleClientAsync(TcpClient client, CancellationToken cancellationToken)
+    {
+        using var _ = client;
+        using var sslStream = new SslStream(client.GetStream());
+        try
+        {
+            await sslStream.AuthenticateAsServerAsync(new SslServerAuthenticationOptions()
+            {
+                ServerCertificate = _certificate,
+                ApplicationProtocols = [SslApplicationProtocol.Http11],
+            }, cancellationToken);
+        }
+        catch
+        {
+            // The client rejected the certificate.
+            return;
+        }
+
+        // Handle the requests on the keep-alive connection.
+        while (true)
+        {
+            var head = await ReadRequestHeadAsync(sslStream, cancellationToken);
+            if (head is null) return;
+
+            var contentLength = 0;
+            foreach (var line in head.Split("\r\n"))
+            {
+                if (line.StartsWith("Content-Length:", StringComparison.OrdinalIgnoreCase))
+                {
+                    contentLength = int.Parse(line.Substring("Content-Length:".Length).Trim());
+                }
+            }
+            var query = new byte[contentLength];
+            await sslStream.ReadExactlyAsync(query, cancellationToken);
+
+            var response = CreateResponse(query);
+            var responseHead = $"HTTP/1.1 200 OK\r\nContent-Type: application/dns-message\r\nContent-Length: {response.Length}\r\n\r\n";
+            await sslStream.WriteAsync(Encoding.ASCII.GetBytes(responseHead), cancellationToken);
+            await sslStream.WriteAsync(response, cancellationToken);
+        }
+    }
+
+    private byte[] CreateResponse(byte[] query)
+    {
+        // Question: QNAME (labels) + QTYPE + QCLASS
+        var offset = 12;
+        var labels = new List<string>();
+        while (query[offset] != 0)
+        {
+            labels.Add(Encoding.ASCII.GetString(query, offset + 1, query[offset]));
+            offset += query[offset] + 1;
+        }
+        offset += 1;
+        var name = string.Join(".", labels);
+        var type = BinaryPrimitives.ReadUInt16BigEndian(query.AsSpan(offset));
+        var questionEnd = offset + 4;
+        ReceivedQueries.Enqueue($"{name}/{(type == TypeAAAA ? "AAAA" : type == TypeA ? "A" : type.ToString())}");
+
+        var found = Records.TryGetValue(name, out var addresses);
+        var family = type == TypeAAAA ? AddressFamily.InterNetworkV6 : AddressFamily.InterNetwork;
+        var answers = (addresses ?? []).Where(x => x.AddressFamily == family).ToArray();
+
+        using var stream = new MemoryStream();
+        var header = new byte[12];
+        query.AsSpan(0, 2).CopyTo(header); // ID
+        BinaryPrimitives.WriteUInt16BigEndian(header.AsSpan(2), (ushort)(0x8180 | (found ? 0 : 3))); // QR, RD, RA, RCODE (3 = NXDOMAIN)
+        BinaryPrimitives.WriteUInt16BigEndian(header.AsSpan(4), 1);
+        BinaryPrimitives.WriteUInt16BigEndian(header.AsSpan(6), (ushort)answers.Length);
+        BinaryPrimitives.WriteUInt16BigEndian(header.AsSpan(8), (ushort)(answers.Length == 0 ? 1 : 0));
+        stream.Write(header);
+        stream.Write(query.AsSpan(12, questionEnd - 12));
+
+        foreach (var address in answers)
+        {
+            WriteRecord(stream, type, Ttl, address.GetAddressBytes());
+        }
+        if (answers.Length == 0)
+        {
+            // SOA record in the authority section for the negative TTL.
+            var soa = new byte[2 + 20];
+            BinaryPrimitives.WriteUInt32BigEndian(soa.AsSpan(18), NegativeTtl); // MNAME (root), RNAME (root), SERIAL, REFRESH, RETRY, EXPIRE, MINIMUM
+            WriteRecord(stream, TypeSOA, NegativeTtl, soa);
+        }
+        return stream.ToArray();
+    }
+
+    private static void WriteRecord(Stream stream, ushort type, uint ttl, byte[] data)
+    {
+        var record = new byte[12];
+        BinaryPrimitives.WriteUInt16BigEndian(record.AsSpan(0), 0xc00c); // The pointer to QNAME
+        BinaryPrimitives.WriteUInt16BigEndian(record.AsSpan(2), type);
+        BinaryPrimitives.WriteUInt16BigEndian(record.AsSpan(4), 1); // IN
+        BinaryPrimitives.WriteUInt32BigEndian(record.AsSpan(6), ttl);
+        BinaryPrimitives.WriteUInt16BigEndian(record.AsSpan(10), (ushort)data.Length);
+        stream.Write(record);
+        stream.Write(data);
+    }
+
+    private static async Task<string?> ReadRequestHeadAsync(Stream stream, CancellationToken cancellationToken)
+    {
+        var buffer = new List<byte>();
+        var b = new byte[1];
+        while (true)
+        {
+            if (await stream.ReadAsync(b, cancellationToken) == 0) return null;
+            buffer.Add(b[0]);
+            if (buffer.Count >= 4 && buffer[^4] == '\r' && buffer[^3] == '\n' && buffer[^2] == '\r' && buffer[^1] == '\n')
+            {
+                return Encoding.ASCII.GetString(buffer.ToArray());
+            }
+        }
+    }
+
+    public async ValueTask DisposeAsync()
+    {
+        _cts.Cancel();
+        _listener.Stop();
+        await _acceptLoop;
+    }
+}
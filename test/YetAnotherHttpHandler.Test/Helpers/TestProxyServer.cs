@@ -0,0 +1,125 @@
+using System.Collections.Concurrent;
+using System.Net;
+using System.Net.Security;
+using System.Net.Sockets;
+using System.Security.Cryptography.X509Certificates;
+using System.Text;
+
+namespace _YetAnotherHttpHandler.Test.Helpers;
+
+/// <summary>
+/// A minimal HTTP forward proxy for testing. It supports CONNECT tunneling and absolute-form requests, optionally over TLS.
+/// </summary>
+public class TestProxyServer : IAsyncDisposable
+{
+    private readonly TcpListener _listener;
+    private readonly CancellationTokenSource _cts = new();
+    private readonly Task _acceptLoop;
+    private readonly string? _requiredAuthorization;
+    private readonly X509Certificate2? _certificate;
+
+    public Uri Uri { get; }
+    public ConcurrentQueue<string> ReceivedRequestHeads { get; } = new();
+
+    /// <param name="requiredAuthorization">The expected value of Proxy-Authorization header. If the request doesn't have it, the proxy responds with 407.</param>
+    /// <param name="certificate">The server certificate for <c>localhost</c>. If it is set, the proxy accepts TLS connections (HTTPS proxy).</param>
+    public TestProxyServer(string? requiredAuthorization = null, X509Certificate2? certificate = null)
+    {
+        _requiredAuthorization = requiredAuthorization;
+        _certificate = certificate;
+        _listener = new TcpListener(IPAddress.Loopback, 0);
+        _listener.Start();
+        var port = ((IPEndPoint)_listener.LocalEndpoint).Port;
+        Uri = certificate is null ? new Uri($"http://127.0.0.1:{port}") : new Uri($"https://localhost:{port}");
+        _acceptLoop = AcceptLoopAsync(_cts.Token);
+    }
+
+    private async Task AcceptLoopAsync(CancellationToken cancellationToken)
+    {
+        while (!cancellationToken.IsCancellationRequested)
+        {
+            TcpClient client;
+            try
+            {
+                client = await _listener.AcceptTcpClientAsync(cancellationToken);
+            }
+            catch (OperationCanceledException)
+            {
+                break;
+            }
+            _ = HandleClientAsync(client, cancellationToken);
+        }
+    }
+
+    private async Task HandleClientAsync(TcpClient client, CancellationToken cancellationToken)
+    {
+        using var _ = client;
+        Stream clientStream = client.GetStream();
+        if (_certificate is not null)
+        {
+            var sslStream = new SslStream(clientStream);
+            await sslStream.AuthenticateAsServerAsync(new SslServerAuthenticationOptions()
+            {
+                ServerCertificate = _certificate,
+                ApplicationProtocols = [SslApplicationProtocol.Http11],
+            }, cancellationToken);
+            clientStream = sslStream;
+        }
+
+        var head = await ReadRequestHeadAsync(clientStream, cancellationToken);
+        if (head is null) return;
+        var headString = Encoding.ASCII.GetString(head);
+        ReceivedRequestHeads.Enqueue(headString);
+
+        var requestLine = headString.Substring(0, headString.IndexOf("\r\n", StringComparison.Ordinal)).Split(' ');
+        var method = requestLine[0];
+        var target = requestLine[1];
+
+        if (_requiredAuthorization is not null && !headString.Contains($"\r\nProxy-Authorization: {_requiredAuthorization}\r\n", StringComparison.OrdinalIgnoreCase))
+        {
+            await clientStream.WriteAsync("HTTP/1.1 407 Proxy Authentication Required\r\nProxy-Authenticate: Basic realm=\"proxy\", Bearer\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"u8.ToArray(), cancellationToken);
+            return;
+        }
+
+        using var upstream = new TcpClient();
+        if (method == "CONNECT")
+        {
+            var hostAndPort = target.Split(':');
+            await upstream.ConnectAsync(hostAndPort[0], int.Parse(hostAndPort[1]), cancellationToken);
+            await clientStream.WriteAsync("HTTP/1.1 200 Connection Established\r\n\r\n"u8.ToArray(), cancellationToken);
+        }
+        else
+        {
+            var uri = new Uri(target);
+            await upstream.ConnectAsync(uri.Host, uri.Port, cancellationToken);
+            await upstream.GetStream().WriteAsync(head, cancellationToken);
+        }
+
+        var upstreamStream = upstream.GetStream();
+        await Task.WhenAny(
+            clientStream.CopyToAsync(upstreamStream, cancellationToken),
+            upstreamStream.CopyToAsync(clientStream, cancellationToken));
+    }
+
+    private static async Task<byte[]?> ReadRequestHeadAsync(Stream stream, CancellationToken cancellationToken)
+    {
+        var buffer = new List<byte>();
+        var b = new byte[1];
+        while (true)
+        {
+            if (await stream.ReadAsync(b, cancellationToken) == 0) return null;
+            buffer.Add(b[0]);
+            if (buffer.Count >= 4 && buffer[^4] == '\r' && buffer[^3] == '\n' && buffer[^2] == '\r' && buffer[^1] == '\n')
+            {
+                return buffer.ToArray();
+            }
+        }
+    }
+
+    public async ValueTask DisposeAsync()
+    {
+        _cts.Cancel();
+        _listener.Stop();
+        await _acceptLoop;
+    }
+}
@@ -0,0 +1,148 @@
+using System.Collections.Concurrent;
+using System.Net;
+using System.Net.Sockets;
+using System.Text;
+
+namespace _YetAnotherHttpHandler.Test.Helpers;
+
+/// <summary>
+/// A minimal SOCKS5/SOCKS4a proxy for testing. It supports CONNECT command only.
+/// </summary>
+public class TestSocksProxyServer : IAsyncDisposable
+{
+    private readonly TcpListener _listener;
+    private readonly CancellationTokenSource _cts = new();
+    private readonly Task _acceptLoop;
+    private readonly (string UserName, string Password)? _credentials;
+
+    public int Port { get; }
+    public ConcurrentQueue<string> RequestedDestinations { get; } = new();
+
+    public TestSocksProxyServer((string UserName, string Password)? credentials = null)
+    {
+        _credentials = credentials;
+        _listener = new TcpListener(IPAddress.Loopback, 0);
+        _listener.Start();
+        Port = ((IPEndPoint)_listener.LocalEndpoint).Port;
+        _acceptLoop = AcceptLoopAsync(_cts.Token);
+    }
+
+    private async Task AcceptLoopAsync(CancellationToken cancellationToken)
+    {
+        while (!cancellationToken.IsCancellationRequested)
+        {
+            TcpClient client;
+            try
+            {
+                client = await _listener.AcceptTcpClientAsync(cancellationToken);
+            }
+            catch (OperationCanceledException)
+            {
+                break;
+            }
+            _ = HandleClientAsync(client, cancellationToken);
+        }
+    }
+
+    private async Task HandleClientAsync(TcpClient client, CancellationToken cancellationToken)
+    {
+        using var _ = client;
+        var stream = client.GetStream();
+
+        var version = await ReadBytesAsync(stream, 1, cancellationToken);
+        var destination = version[0] switch
+        {
+            0x05 => await HandshakeSocks5Async(stream, cancellationToken),
+            0x04 => await HandshakeSocks4Async(stream, cancellationToken),
+            _ => null,
+        };
+        if (destination is null) return;
+        RequestedDestinations.Enqueue($"{destination.Value.Host}:{destination.Value.Port}");
+
+        using var upstream = new TcpClient();
+        await upstream.ConnectAsync(destination.Value.Host, destination.Value.Port, cancellationToken);
+
+        if (version[0] == 0x05)
+        {
+            await stream.WriteAsync(new byte[] { 0x05, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0 }, cancellationToken);
+        }
+        else
+        {
+            await stream.WriteAsync(new byte[] { 0x00, 0x5a, 0, 0, 0, 0, 0, 0 }, cancellationToken);
+        }
+
+        var upstreamStream = upstream.GetStream();
+        await Task.WhenAny(
+            stream.CopyToAsync(upstreamStream, cancellationToken),
+            upstreamStream.CopyToAsync(stream, cancellationToken));
+    }
+
+    private async Task<(string Host, int Port)?> HandshakeSocks5Async(Stream stream, CancellationToken cancellationToken)
+    {
+        var methodsCount = (await ReadBytesAsync(stream, 1, cancellationToken))[0];
+        var methods = await ReadBytesAsync(stream, methodsCount, cancellationToken);
+        var expectedMethod = _credentials is null ? (byte)0x00 : (byte)0x02;
+        if (!methods.Contains(expectedMethod))
+        {
+            await stream.WriteAsync(new byte[] { 0x05, 0xff }, cancellationToken);
+            return null;
+        }
+        await stream.WriteAsync(new byte[] { 0x05, expectedMethod }, cancellationToken);
+
+        if (_credentials is { } credentials)
+        {
+            await ReadBytesAsync(stream, 1, cancellationToken);
+            var userName = Encoding.UTF8.GetString(await ReadBytesAsync(stream, (await ReadBytesAsync(stream, 1, cancellationToken))[0], cancellationToken));
+            var password = Encoding.UTF8.GetString(await ReadBytesAsync(stream, (await ReadBytesAsync(stream, 1, cancellationToken))[0], cancellationToken));
+            var success = userName == credentials.UserName && password == credentials.Password;
+            await stream.WriteAsync(new byte[] { 0x01, success ? (byte)0x00 : (byte)0x01 }, cancellationToken);
+            if (!success) return null;
+        }
+
+        var request = await ReadBytesAsync(stream, 4, cancellationToken);
+        var host = request[3] switch
+        {
+            0x01 => new IPAddress(await ReadBytesAsync(stream, 4, cancellationToken)).ToString(),
+            0x04 => new IPAddress(await ReadBytesAsync(stream, 16, cancellationToken)).ToString(),
+            _ => Encoding.ASCII.GetString(await ReadBytesAsync(stream, (await ReadBytesAsync(stream, 1, cancellationToken))[0], cancellationToken)),
+        };
+        var port = await ReadBytesAsync(stream, 2, cancellationToken);
+        return (host, port[0] << 8 | port[1]);
+    }
+
+    private static async Task<(string Host, int Port)?> HandshakeSocks4Async(Stream stream, CancellationToken cancellationToken)
+    {
+        var request = await ReadBytesAsync(stream, 7, cancellationToken);
+        var port = request[1] << 8 | request[2];
+        await ReadNullTerminatedStringAsync(stream, cancellationToken); // User ID
+        var host = (request[3], request[4], request[5]) == (0, 0, 0) && request[6] != 0
+            ? await ReadNullTerminatedStringAsync(stream, cancellationToken)
+            : new IPAddress(request[3..7]).ToString();
+        return (host, port);
+    }
+
+    private static async Task<string> ReadNullTerminatedStringAsync(Stream stream, CancellationToken cancellationToken)
+    {
+        var buffer = new List<byte>();
+        while (true)
+        {
+            var b = (await ReadBytesAsync(stream, 1, cancellationToken))[0];
+            if (b == 0) return Encoding.ASCII.GetString(buffer.ToArray());
+            buffer.Add(b);
+        }
+    }
+
+    private static async Task<byte[]> ReadBytesAsync(Stream stream, int length, CancellationToken cancellationToken)
+    {
+        var buffer = new byte[length];
+        await stream.ReadExactlyAsync(buffer, cancellationToken);
+        return buffer;
+    }
+
+    public async ValueTask DisposeAsync()
+    {
+        _cts.Cancel();
+        _listener.Stop();
+        await _acceptLoop;
+    }
+}
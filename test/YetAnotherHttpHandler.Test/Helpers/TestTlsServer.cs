@@ -0,0 +1,104 @@
+using System.Collections.Concurrent;
+using System.Net;
+using System.Net.Security;
+using System.Net.Sockets;
+using System.Security.Cryptography.X509Certificates;
+using System.Text;
+
+namespace _YetAnotherHttpHandler.Test.Helpers;
+
+/// <summary>
+/// A minimal HTTPS (HTTP/1.1) server for testing. It presents the certificate chain as is, and responds <c>__OK__</c> to every request.
+/// </summary>
+public class TestTlsServer : IAsyncDisposable
+{
+    private readonly TcpListener _listener;
+    private readonly CancellationTokenSource _cts = new();
+    private readonly Task _acceptLoop;
+    private readonly SslStreamCertificateContext _certificateContext;
+
+    public int Port { get; }
+    public string BaseUri => $"https://localhost:{Port}";
+    /// <summary>
+    /// The heads of the requests received after the handshakes.
+    /// </summary>
+    public ConcurrentQueue<string> ReceivedRequestHeads { get; } = new();
+
+    /// <param name="certificate">The server certificate for <c>localhost</c>.</param>
+    /// <param name="intermediates">The certificates presented with the server certificate.</param>
+    public TestTlsServer(X509Certificate2 certificate, X509Certificate2Collection? intermediates = null)
+    {
+        _certificateContext = SslStreamCertificateContext.Create(certificate, intermediates, offline: true);
+        _listener = new TcpListener(IPAddress.Loopback, 0);
+        _listener.Start();
+        Port = ((IPEndPoint)_listener.LocalEndpoint).Port;
+        _acceptLoop = AcceptLoopAsync(_cts.Token);
+    }
+
+    private async Task AcceptLoopAsync(CancellationToken cancellationToken)
+    {
+        while (!cancellationToken.IsCancellationRequested)
+        {
+            TcpClient client;
+            try
+            {
+                client = await _listener.AcceptTcpClientAsync(cancellationToken);
+            }
+            catch (OperationCanceledException)
+            {
+                break;
+            }
+            _ = HandleClientAsync(client, cancellationToken);
+        }
+    }
+
+    private async Task HandleClientAsync(TcpClient client, CancellationToken cancellationToken)
+    {
+        using var _ = client;
+        using var sslStream = new SslStream(client.GetStream());
+        try
+        {
+            await sslStream.AuthenticateAsServerAsync(new SslServerAuthenticationOptions()
+            {
+                ServerCertificateContext = _certificateContext,
+                ApplicationProtocols = [SslApplicationProtocol.Http11],
+            }, cancellationToken);
+
+            // Handle the requests on the keep-alive connection.
+            while (true)
+            {
+                var head = await ReadRequestHeadAsync(sslStream, cancellationToken);
+                if (head is null) return;
+                ReceivedRequestHeads.Enqueue(head);
+
+                await sslStream.WriteAsync("HTTP/1.1 200 OK\r\nContent-Length: 6\r\n\r\n__OK__"u8.ToArray(), cancellationToken);
+            }
+        }
+        catch
+        {
+            // The client rejected the certificate or closed the connection.
+        }
+    }
+
+    private static async Task<string?> ReadRequestHeadAsync(Stream stream, CancellationToken cancellationToken)
+    {
+        var buffer = new List<byte>();
+        var b = new byte[1];
+        while (true)
+        {
+            if (await stream.ReadAsync(b, cancellationToken) == 0) return buffer.Count == 0 ? null : Encoding.ASCII.GetString(buffer.ToArray());
+            buffer.Add(b[0]);
+            if (buffer.Count >= 4 && buffer[^4] == '\r' && buffer[^3] == '\n' && buffer[^2] == '\r' && buffer[^1] == '\n')
+            {
+                return Encoding.ASCII.GetString(buffer.ToArray());
+            }
+        }
+    }
+
+    public async ValueTask DisposeAsync()
+    {
+        _cts.Cancel();
+        _listener.Stop();
+        await _acceptLoop;
+    }
+}
@@ -0,0 +1,53 @@
+using System.Net;
+using Microsoft.AspNetCore.Builder;
+using Microsoft.AspNetCore.Hosting;
+using Microsoft.AspNetCore.Hosting.Server;
+using Microsoft.AspNetCore.Hosting.Server.Features;
+using Microsoft.AspNetCore.Server.Kestrel.Core;
+using Microsoft.Extensions.DependencyInjection;
+using Microsoft.Extensions.Logging;
+
+namespace _YetAnotherHttpHandler.Test.Helpers;
+
+/// <summary>
+/// An in-process HTTP/1.1 server (Kestrel) with the endpoints mapped by each test.
+/// </summary>
+public class TestWebAppServer : IAsyncDisposable
+{
+    private readonly WebApplication _app;
+
+    public int Port { get; }
+    public string BaseUri => $"http://127.0.0.1:{Port}";
+
+    private TestWebAppServer(WebApplication app, int port)
+    {
+        _app = app;
+        Port = port;
+    }
+
+    /// <param name="mapEndpoints">Maps the endpoints of the test to the application.</param>
+    /// <param name="listenAddress">The address to listen on. The default is the IPv4 loopback address.</param>
+    public static async Task<TestWebAppServer> LaunchAsync(Action<WebApplication> mapEndpoints, ILoggerProvider loggerProvider, IPAddress? listenAddress, CancellationToken cancellationToken)
+    {
+        var builder = WebApplication.CreateSlimBuilder();
+        builder.Logging.ClearProviders();
+        builder.Logging.AddProvider(loggerProvider);
+        builder.WebHost.ConfigureKestrel(options =>
+        {
+            options.Listen(listenAddress ?? IPAddress.Loopback, 0, listenOptions => listenOptions.Protocols = HttpProtocols.Http1);
+        });
+
+        var app = builder.Build();
+        mapEndpoints(app);
+        await app.StartAsync(cancellationToken);
+
+        var address = app.Services.GetRequiredService<IServer>().Features.GetRequiredFeature<IServerAddressesFeature>().Addresses.Single();
+        return new TestWebAppServer(app, new Uri(address).Port);
+    }
+
+    public async ValueTask DisposeAsync()
+    {
+        await _app.StopAsync();
+        await _app.DisposeAsync();
+    }
+}
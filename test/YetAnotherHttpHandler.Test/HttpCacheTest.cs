@@ -0,0 +1,275 @@
+using System.Net;
+using Cysharp.Net.Http;
+using Microsoft.AspNetCore.Builder;
+using Microsoft.AspNetCore.Http;
+
+namespace _YetAnotherHttpHandler.Test;
+
+public class HttpCacheTest(ITestOutputHelper testOutputHelper) : UseTestServerTestBase(testOutputHelper)
+{
+    [Fact]
+    public async Task Disabled()
+    {
+        // Arrange
+        var requestCount = 0;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/max-age", (HttpContext context) =>
+        {
+            context.Response.Headers.CacheControl = "max-age=60";
+            return context.Response.WriteAsync($"#{Interlocked.Increment(ref requestCount)}");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler();
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        await httpClient.GetStringAsync($"{server.BaseUri}/max-age").WaitAsync(TimeoutToken);
+        var response = await httpClient.GetAsync($"{server.BaseUri}/max-age").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal(2, requestCount);
+        Assert.False(response.Headers.Contains("Cache-Status"));
+    }
+
+    [Fact]
+    public async Task Fresh_Response_Is_Returned_From_Cache()
+    {
+        // Arrange
+        var requestCount = 0;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/max-age", (HttpContext context) =>
+        {
+            context.Response.Headers.CacheControl = "max-age=60";
+            return context.Response.WriteAsync($"#{Interlocked.Increment(ref requestCount)}");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler() { EnableHttpCache = true };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var response1 = await httpClient.GetAsync($"{server.BaseUri}/max-age").WaitAsync(TimeoutToken);
+        var result1 = await response1.Content.ReadAsStringAsync().WaitAsync(TimeoutToken);
+        var response2 = await httpClient.GetAsync($"{server.BaseUri}/max-age").WaitAsync(TimeoutToken);
+        var result2 = await response2.Content.ReadAsStringAsync().WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal(1, requestCount);
+        Assert.Equal("#1", result1);
+        Assert.Equal("#1", result2);
+        Assert.StartsWith("yaha; fwd=uri-miss", response1.Headers.GetValues("Cache-Status").Single());
+        Assert.StartsWith("yaha; hit", response2.Headers.GetValues("Cache-Status").Single());
+    }
+
+    [Fact]
+    public async Task Revalidate_With_ETag()
+    {
+        // Arrange
+        var requestCount = 0;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/etag", (HttpContext context) =>
+        {
+            var count = Interlocked.Increment(ref requestCount);
+            context.Response.Headers.ETag = "\"v1\"";
+            context.Response.Headers.CacheControl = "no-cache";
+            if (context.Request.Headers.IfNoneMatch == "\"v1\"")
+            {
+                context.Response.StatusCode = StatusCodes.Status304NotModified;
+                return Task.CompletedTask;
+            }
+            return context.Response.WriteAsync($"#{count}");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler() { EnableHttpCache = true };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        await httpClient.GetStringAsync($"{server.BaseUri}/etag").WaitAsync(TimeoutToken);
+        var response = await httpClient.GetAsync($"{server.BaseUri}/etag").WaitAsync(TimeoutToken);
+        var result = await response.Content.ReadAsStringAsync().WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal(2, requestCount);
+        Assert.Equal(HttpStatusCode.OK, response.StatusCode);
+        Assert.Equal("#1", result);
+        Assert.Equal("yaha; fwd=stale; fwd-status=304", response.Headers.GetValues("Cache-Status").Single());
+    }
+
+    [Fact]
+    public async Task Vary()
+    {
+        // Arrange
+        var requestCount = 0;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/vary", (HttpContext context) =>
+        {
+            context.Response.Headers.CacheControl = "max-age=60";
+            context.Response.Headers.Vary = "Accept-Language";
+            return context.Response.WriteAsync($"#{Interlocked.Increment(ref requestCount)} {context.Request.Headers.AcceptLanguage}");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler() { EnableHttpCache = true };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        async Task<string> GetAsync(string language)
+        {
+            var request = new HttpRequestMessage(HttpMethod.Get, $"{server.BaseUri}/vary");
+            request.Headers.Add("Accept-Language", language);
+            var response = await httpClient.SendAsync(request).WaitAsync(TimeoutToken);
+            return await response.Content.ReadAsStringAsync().WaitAsync(TimeoutToken);
+        }
+        var en1 = await GetAsync("en");
+        var ja1 = await GetAsync("ja");
+        var en2 = await GetAsync("en");
+        var ja2 = await GetAsync("ja");
+
+        // Assert
+        Assert.Equal(2, requestCount);
+        Assert.Equal("#1 en", en1);
+        Assert.Equal("#2 ja", ja1);
+        Assert.Equal(en1, en2);
+        Assert.Equal(ja1, ja2);
+    }
+
+    [Fact]
+    public async Task No_Store()
+    {
+        // Arrange
+        var requestCount = 0;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/no-store", (HttpContext context) =>
+        {
+            context.Response.Headers.CacheControl = "no-store";
+            return context.Response.WriteAsync($"#{Interlocked.Increment(ref requestCount)}");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler() { EnableHttpCache = true };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        await httpClient.GetStringAsync($"{server.BaseUri}/no-store").WaitAsync(TimeoutToken);
+        var result = await httpClient.GetStringAsync($"{server.BaseUri}/no-store").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal(2, requestCount);
+        Assert.Equal("#2", result);
+    }
+
+    [Fact]
+    public async Task Do_Not_Store_Response_Without_Freshness_And_Validator()
+    {
+        // Arrange
+        var requestCount = 0;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/", (HttpContext context) =>
+        {
+            context.Response.Headers.CacheControl = "max-age=0";
+            return context.Response.WriteAsync($"#{Interlocked.Increment(ref requestCount)}");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler() { EnableHttpCache = true };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var response1 = await httpClient.GetAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+        await response1.Content.ReadAsStringAsync().WaitAsync(TimeoutToken);
+        var response2 = await httpClient.GetAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+        var result2 = await response2.Content.ReadAsStringAsync().WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal(2, requestCount);
+        Assert.Equal("#2", result2);
+        Assert.Equal("yaha; fwd=uri-miss; fwd-status=200", response1.Headers.GetValues("Cache-Status").Single());
+        Assert.Equal("yaha; fwd=uri-miss; fwd-status=200", response2.Headers.GetValues("Cache-Status").Single());
+    }
+
+    [Fact]
+    public async Task Invalidate_By_Unsafe_Request()
+    {
+        // Arrange
+        var requestCount = 0;
+        await using var server = await LaunchServerAsync(app => app.Map("/max-age", (HttpContext context) =>
+        {
+            context.Response.Headers.CacheControl = "max-age=60";
+            return context.Response.WriteAsync($"#{Interlocked.Increment(ref requestCount)}");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler() { EnableHttpCache = true };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        await httpClient.GetStringAsync($"{server.BaseUri}/max-age").WaitAsync(TimeoutToken);
+        await httpClient.PostAsync($"{server.BaseUri}/max-age", new StringContent("")).WaitAsync(TimeoutToken);
+        var result = await httpClient.GetStringAsync($"{server.BaseUri}/max-age").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal(3, requestCount);
+        Assert.Equal("#3", result);
+    }
+
+    [Fact]
+    public async Task Persist_To_Directory()
+    {
+        // Arrange
+        var requestCount = 0;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/max-age", (HttpContext context) =>
+        {
+            context.Response.Headers.CacheControl = "max-age=60";
+            return context.Response.WriteAsync($"#{Interlocked.Increment(ref requestCount)}");
+        }));
+        var directory = Path.Combine(Path.GetTempPath(), $"yaha-cache-{Guid.NewGuid()}");
+        try
+        {
+            using (var httpHandler = new YetAnotherHttpHandler() { EnableHttpCache = true, HttpCacheDirectory = directory })
+            {
+                var httpClient = new HttpClient(httpHandler);
+                await httpClient.GetStringAsync($"{server.BaseUri}/max-age").WaitAsync(TimeoutToken);
+            }
+            // The responses are written in the background.
+            while (Directory.GetFiles(Path.Combine(directory, "yaha-http-cache"), "yaha-*.cache").Length == 0)
+            {
+                await Task.Delay(10, TimeoutToken);
+            }
+
+            // Act
+            using var httpHandler2 = new YetAnotherHttpHandler() { EnableHttpCache = true, HttpCacheDirectory = directory };
+            var httpClient2 = new HttpClient(httpHandler2);
+            var response = await httpClient2.GetAsync($"{server.BaseUri}/max-age").WaitAsync(TimeoutToken);
+            var result = await response.Content.ReadAsStringAsync().WaitAsync(TimeoutToken);
+
+            // Assert
+            Assert.Equal(1, requestCount);
+            Assert.Equal("#1", result);
+            Assert.StartsWith("yaha; hit", response.Headers.GetValues("Cache-Status").Single());
+        }
+        finally
+        {
+            Directory.Delete(directory, recursive: true);
+        }
+    }
+
+    [Fact]
+    public async Task Remove_Only_Own_Invalid_Files_In_Directory()
+    {
+        // Arrange
+        var requestCount = 0;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/no-store", (HttpContext context) =>
+        {
+            context.Response.Headers.CacheControl = "no-store";
+            return context.Response.WriteAsync($"#{Interlocked.Increment(ref requestCount)}");
+        }));
+        var directory = Path.Combine(Path.GetTempPath(), $"yaha-cache-{Guid.NewGuid()}");
+        var cacheDirectory = Path.Combine(directory, "yaha-http-cache");
+        Directory.CreateDirectory(cacheDirectory);
+        var unrelatedFiles = new[] { Path.Combine(directory, "app.tmp"), Path.Combine(directory, "app.cache"), Path.Combine(cacheDirectory, "app.cache"), Path.Combine(cacheDirectory, "app.tmp") };
+        var invalidFiles = new[] { Path.Combine(cacheDirectory, "yaha-invalid.cache"), Path.Combine(cacheDirectory, "yaha-interrupted.cache.tmp") };
+        foreach (var path in unrelatedFiles.Concat(invalidFiles))
+        {
+            await File.WriteAllTextAsync(path, "__APP__", TimeoutToken);
+        }
+        try
+        {
+            using var httpHandler = new YetAnotherHttpHandler() { EnableHttpCache = true, HttpCacheDirectory = directory };
+            var httpClient = new HttpClient(httpHandler);
+
+            // Act
+            // The directory is loaded when the handler is initialized by the first request.
+            await httpClient.GetStringAsync($"{server.BaseUri}/no-store").WaitAsync(TimeoutToken);
+
+            // Assert
+            Assert.All(unrelatedFiles, path => Assert.Equal("__APP__", File.ReadAllText(path)));
+            Assert.All(invalidFiles, path => Assert.False(File.Exists(path)));
+        }
+        finally
+        {
+            Directory.Delete(directory, recursive: true);
+        }
+    }
+}
@@ -0,0 +1,165 @@
+using System.Collections.Concurrent;
+using System.Net;
+using _YetAnotherHttpHandler.Test.Helpers;
+using Cysharp.Net.Http;
+using Microsoft.AspNetCore.Builder;
+using Microsoft.AspNetCore.Http;
+
+namespace _YetAnotherHttpHandler.Test;
+
+// NOTE: 127.0.0.2 is not available on macOS by default.
+[OSSkipCondition(OperatingSystems.MacOSX)]
+public class LoadBalancingTest(ITestOutputHelper testOutputHelper) : UseTestServerTestBase(testOutputHelper)
+{
+    private static readonly IPAddress Loopback2 = IPAddress.Parse("127.0.0.2");
+
+    [Fact]
+    public async Task Disabled_Pinned_To_First_Connection()
+    {
+        // Arrange
+        var receivedAddresses = new ConcurrentQueue<IPAddress>();
+        // Listens on all addresses and records the local address of the connection.
+        await using var server = await LaunchServerAsync(app => app.MapGet("/", (HttpContext context) =>
+        {
+            receivedAddresses.Enqueue(context.Connection.LocalIpAddress!.MapToIPv4());
+            return "__OK__";
+        }), IPAddress.Any);
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            OnResolveHostName = hostName => Task.FromResult(new[] { IPAddress.Loopback, Loopback2 }),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        for (var i = 0; i < 4; i++)
+        {
+            await httpClient.GetStringAsync($"http://service.example.invalid:{server.Port}/").WaitAsync(TimeoutToken);
+        }
+
+        // Assert
+        Assert.Equal([IPAddress.Loopback, IPAddress.Loopback, IPAddress.Loopback, IPAddress.Loopback], receivedAddresses);
+    }
+
+    [Theory]
+    [InlineData(LoadBalancingPolicy.RoundRobin)]
+    [InlineData(LoadBalancingPolicy.LeastOutstandingRequests)]
+    public async Task Distribute_Requests(LoadBalancingPolicy policy)
+    {
+        // Arrange
+        var receivedAddresses = new ConcurrentQueue<IPAddress>();
+        // Listens on all addresses and records the local address of the connection.
+        await using var server = await LaunchServerAsync(app => app.MapGet("/", (HttpContext context) =>
+        {
+            receivedAddresses.Enqueue(context.Connection.LocalIpAddress!.MapToIPv4());
+            return "__OK__";
+        }), IPAddress.Any);
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            LoadBalancingPolicy = policy,
+            OnResolveHostName = hostName => Task.FromResult(new[] { IPAddress.Loopback, Loopback2 }),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        for (var i = 0; i < 4; i++)
+        {
+            var result = await httpClient.GetStringAsync($"http://service.example.invalid:{server.Port}/").WaitAsync(TimeoutToken);
+            Assert.Equal("__OK__", result);
+        }
+
+        // Assert
+        Assert.Equal(2, receivedAddresses.Count(x => x.Equals(IPAddress.Loopback)));
+        Assert.Equal(2, receivedAddresses.Count(x => x.Equals(Loopback2)));
+    }
+
+    [Fact]
+    public async Task PowerOfTwoChoices()
+    {
+        // Arrange
+        var receivedAddresses = new ConcurrentQueue<IPAddress>();
+        var slowRequestReceived = new TaskCompletionSource(TaskCreationOptions.RunContinuationsAsynchronously);
+        var slowRequestCompleted = new TaskCompletionSource(TaskCreationOptions.RunContinuationsAsynchronously);
+        // Listens on all addresses and records the local address of the connection.
+        await using var server = await LaunchServerAsync(app =>
+        {
+            app.MapGet("/", (HttpContext context) =>
+            {
+                receivedAddresses.Enqueue(context.Connection.LocalIpAddress!.MapToIPv4());
+                return "__OK__";
+            });
+            app.MapGet("/slow", async (HttpContext context) =>
+            {
+                receivedAddresses.Enqueue(context.Connection.LocalIpAddress!.MapToIPv4());
+                context.Response.ContentLength = 6;
+                await context.Response.StartAsync();
+                slowRequestReceived.TrySetResult();
+                await slowRequestCompleted.Task.WaitAsync(context.RequestAborted);
+                await context.Response.WriteAsync("__OK__");
+            });
+        }, IPAddress.Any);
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            LoadBalancingPolicy = LoadBalancingPolicy.PowerOfTwoChoices,
+            OnResolveHostName = hostName => Task.FromResult(new[] { IPAddress.Loopback, Loopback2 }),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        // The outstanding requests of the other endpoint are fewer, so the second request goes to it.
+        var first = httpClient.GetAsync($"http://service.example.invalid:{server.Port}/slow", HttpCompletionOption.ResponseHeadersRead).WaitAsync(TimeoutToken);
+        await slowRequestReceived.Task.WaitAsync(TimeoutToken);
+        var result = await httpClient.GetStringAsync($"http://service.example.invalid:{server.Port}/").WaitAsync(TimeoutToken);
+        slowRequestCompleted.TrySetResult();
+        await first;
+
+        // Assert
+        Assert.Equal("__OK__", result);
+        Assert.Equal(2, receivedAddresses.Distinct().Count());
+    }
+
+    [Fact]
+    public async Task ReResolve()
+    {
+        // Arrange
+        var receivedAddresses = new ConcurrentQueue<IPAddress>();
+        // Listens on all addresses and records the local address of the connection.
+        await using var server = await LaunchServerAsync(app => app.MapGet("/", (HttpContext context) =>
+        {
+            receivedAddresses.Enqueue(context.Connection.LocalIpAddress!.MapToIPv4());
+            return "__OK__";
+        }), IPAddress.Any);
+        var addresses = new[] { IPAddress.Loopback };
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            LoadBalancingPolicy = LoadBalancingPolicy.RoundRobin,
+            LoadBalancingReResolveInterval = TimeSpan.Zero,
+            OnResolveHostName = hostName => Task.FromResult(addresses),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        await httpClient.GetStringAsync($"http://service.example.invalid:{server.Port}/").WaitAsync(TimeoutToken);
+        addresses = [Loopback2];
+        await httpClient.GetStringAsync($"http://service.example.invalid:{server.Port}/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal([IPAddress.Loopback, Loopback2], receivedAddresses);
+    }
+
+    [Fact]
+    public async Task Undefined_Policy()
+    {
+        // Arrange
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            LoadBalancingPolicy = (LoadBalancingPolicy)42,
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetAsync("http://localhost/"));
+
+        // Assert
+        Assert.IsType<ArgumentOutOfRangeException>(ex);
+    }
+}
@@ -0,0 +1,183 @@
+using System.Net;
+using System.Net.Http.Headers;
+using Cysharp.Net.Http;
+using Microsoft.AspNetCore.Builder;
+using Microsoft.AspNetCore.Http;
+
+namespace _YetAnotherHttpHandler.Test;
+
+public class RedirectTest(ITestOutputHelper testOutputHelper) : UseTestServerTestBase(testOutputHelper)
+{
+    [Fact]
+    public async Task Disabled()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(app => app.MapGet("/redirect/302", () => Results.Redirect("/echo")));
+        using var httpHandler = new YetAnotherHttpHandler();
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var response = await httpClient.GetAsync($"{server.BaseUri}/redirect/302").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal(HttpStatusCode.Found, response.StatusCode);
+        Assert.Equal(new Uri($"{server.BaseUri}/redirect/302"), response.RequestMessage!.RequestUri);
+    }
+
+    [Theory]
+    [InlineData(301)]
+    [InlineData(302)]
+    [InlineData(303)]
+    [InlineData(307)]
+    [InlineData(308)]
+    public async Task Follow_Redirect(int statusCode)
+    {
+        // Arrange
+        // The server echoes the method, `Authorization` header and body of the request at `/echo`.
+        await using var server = await LaunchServerAsync(app =>
+        {
+            app.Map("/redirect/{code:int}", (HttpContext context, int code) =>
+            {
+                context.Response.StatusCode = code;
+                context.Response.Headers.Location = "/echo";
+            });
+            app.Map("/echo", async (HttpContext context) =>
+            {
+                using var reader = new StreamReader(context.Request.Body);
+                var body = await reader.ReadToEndAsync();
+                await context.Response.WriteAsync($"{context.Request.Method};Authorization={context.Request.Headers.Authorization};Body={body}");
+            });
+        });
+        using var httpHandler = new YetAnotherHttpHandler() { AllowAutoRedirect = true };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var response = await httpClient.GetAsync($"{server.BaseUri}/redirect/{statusCode}").WaitAsync(TimeoutToken);
+        var result = await response.Content.ReadAsStringAsync().WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal(HttpStatusCode.OK, response.StatusCode);
+        Assert.Equal("GET;Authorization=;Body=", result);
+        Assert.Equal(new Uri($"{server.BaseUri}/echo"), response.RequestMessage!.RequestUri);
+    }
+
+    [Theory]
+    [InlineData(301, "GET;Authorization=;Body=")]
+    [InlineData(302, "GET;Authorization=;Body=")]
+    [InlineData(303, "GET;Authorization=;Body=")]
+    [InlineData(307, "POST;Authorization=;Body=__BODY__")]
+    [InlineData(308, "POST;Authorization=;Body=__BODY__")]
+    public async Task Method_Rewriting(int statusCode, string expected)
+    {
+        // Arrange
+        // The server echoes the method, `Authorization` header and body of the request at `/echo`.
+        await using var server = await LaunchServerAsync(app =>
+        {
+            app.Map("/redirect/{code:int}", (HttpContext context, int code) =>
+            {
+                context.Response.StatusCode = code;
+                context.Response.Headers.Location = "/echo";
+            });
+            app.Map("/echo", async (HttpContext context) =>
+            {
+                using var reader = new StreamReader(context.Request.Body);
+                var body = await reader.ReadToEndAsync();
+                await context.Response.WriteAsync($"{context.Request.Method};Authorization={context.Request.Headers.Authorization};Body={body}");
+            });
+        });
+        using var httpHandler = new YetAnotherHttpHandler() { AllowAutoRedirect = true };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var response = await httpClient.PostAsync($"{server.BaseUri}/redirect/{statusCode}", new StringContent("__BODY__")).WaitAsync(TimeoutToken);
+        var result = await response.Content.ReadAsStringAsync().WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal(HttpStatusCode.OK, response.StatusCode);
+        Assert.Equal(expected, result);
+    }
+
+    [Fact]
+    public async Task Keep_Authorization_On_Same_Origin()
+    {
+        // Arrange
+        // The server echoes the method, `Authorization` header and body of the request at `/echo`.
+        await using var server = await LaunchServerAsync(app =>
+        {
+            app.Map("/redirect/{code:int}", (HttpContext context, int code) =>
+            {
+                context.Response.StatusCode = code;
+                context.Response.Headers.Location = "/echo";
+            });
+            app.Map("/echo", async (HttpContext context) =>
+            {
+                using var reader = new StreamReader(context.Request.Body);
+                var body = await reader.ReadToEndAsync();
+                await context.Response.WriteAsync($"{context.Request.Method};Authorization={context.Request.Headers.Authorization};Body={body}");
+            });
+        });
+        using var httpHandler = new YetAnotherHttpHandler() { AllowAutoRedirect = true };
+        var httpClient = new HttpClient(httpHandler);
+        httpClient.DefaultRequestHeaders.Authorization = new AuthenticationHeaderValue("Bearer", "__TOKEN__");
+
+        // Act
+        var result = await httpClient.GetStringAsync($"{server.BaseUri}/redirect/302").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("GET;Authorization=Bearer __TOKEN__;Body=", result);
+    }
+
+    [Fact]
+    public async Task Strip_Authorization_On_Cross_Origin()
+    {
+        // Arrange
+        // The server echoes the method, `Authorization` header and body of the request at `/echo`.
+        await using var server = await LaunchServerAsync(app =>
+        {
+            app.MapGet("/cross-origin", (HttpContext context) => Results.Redirect($"http://localhost:{context.Connection.LocalPort}/echo"));
+            app.Map("/echo", async (HttpContext context) =>
+            {
+                using var reader = new StreamReader(context.Request.Body);
+                var body = await reader.ReadToEndAsync();
+                await context.Response.WriteAsync($"{context.Request.Method};Authorization={context.Request.Headers.Authorization};Body={body}");
+            });
+        });
+        using var httpHandler = new YetAnotherHttpHandler() { AllowAutoRedirect = true };
+        var httpClient = new HttpClient(httpHandler);
+        httpClient.DefaultRequestHeaders.Authorization = new AuthenticationHeaderValue("Bearer", "__TOKEN__");
+
+        // Act
+        // `localhost` is another origin of `127.0.0.1`.
+        var response = await httpClient.GetAsync($"{server.BaseUri}/cross-origin").WaitAsync(TimeoutToken);
+        var result = await response.Content.ReadAsStringAsync().WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("GET;Authorization=;Body=", result);
+        Assert.Equal(new Uri($"http://localhost:{server.Port}/echo"), response.RequestMessage!.RequestUri);
+    }
+
+    [Fact]
+    public async Task Max_Redirections()
+    {
+        // Arrange
+        var requestCount = 0;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/loop", () =>
+        {
+            Interlocked.Increment(ref requestCount);
+            return Results.Redirect("/loop");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            AllowAutoRedirect = true,
+            MaxAutomaticRedirections = 3,
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var response = await httpClient.GetAsync($"{server.BaseUri}/loop").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal(HttpStatusCode.Found, response.StatusCode);
+        Assert.Equal(4, requestCount);
+    }
+}
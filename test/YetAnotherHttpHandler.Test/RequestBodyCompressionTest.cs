@@ -0,0 +1,127 @@
+using System.IO.Compression;
+using System.Net;
+using System.Text;
+using Cysharp.Net.Http;
+using Microsoft.AspNetCore.Builder;
+using Microsoft.AspNetCore.Http;
+
+namespace _YetAnotherHttpHandler.Test;
+
+public class RequestBodyCompressionTest(ITestOutputHelper testOutputHelper) : UseTestServerTestBase(testOutputHelper)
+{
+    private static readonly string Content = string.Concat(Enumerable.Repeat("Hello, telemetry! ", 1000));
+
+    [Fact]
+    public async Task Not_Compressed()
+    {
+        // Arrange
+        string? contentEncoding = null;
+        string? body = null;
+        await using var server = await LaunchServerAsync(app => app.MapPost("/", async (HttpContext context) =>
+        {
+            contentEncoding = context.Request.Headers.ContentEncoding;
+            using var reader = new StreamReader(context.Request.Body);
+            body = await reader.ReadToEndAsync();
+        }));
+        using var httpHandler = new YetAnotherHttpHandler();
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.PostAsync($"{server.BaseUri}/", new StringContent(Content)).WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal(HttpStatusCode.OK, result.StatusCode);
+        Assert.Null(contentEncoding);
+        Assert.Equal(Content, body);
+    }
+
+    [Theory]
+    [InlineData(RequestBodyCompression.Gzip, null, "gzip")]
+    [InlineData(RequestBodyCompression.Gzip, 9, "gzip")]
+    [InlineData(RequestBodyCompression.Brotli, null, "br")]
+    [InlineData(RequestBodyCompression.Brotli, 1, "br")]
+    public async Task Compress(RequestBodyCompression compression, int? level, string expectedContentEncoding)
+    {
+        // Arrange
+        string? contentEncoding = null;
+        var chunked = false;
+        var receivedLength = 0L;
+        string? body = null;
+        await using var server = await LaunchServerAsync(app => app.MapPost("/", async (HttpContext context) =>
+        {
+            contentEncoding = context.Request.Headers.ContentEncoding;
+            chunked = context.Request.Headers.TransferEncoding == "chunked";
+            var compressed = new MemoryStream();
+            await context.Request.Body.CopyToAsync(compressed);
+            receivedLength = compressed.Length;
+            compressed.Position = 0;
+            using var reader = new StreamReader(contentEncoding == "br" ? new BrotliStream(compressed, CompressionMode.Decompress) : new GZipStream(compressed, CompressionMode.Decompress));
+            body = await reader.ReadToEndAsync();
+        }));
+        using var httpHandler = new YetAnotherHttpHandler();
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var request = new HttpRequestMessage(HttpMethod.Post, $"{server.BaseUri}/") { Content = new StringContent(Content) };
+        request.SetBodyCompression(compression, level);
+        var result = await httpClient.SendAsync(request).WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal(HttpStatusCode.OK, result.StatusCode);
+        Assert.Equal(expectedContentEncoding, contentEncoding);
+        Assert.True(chunked);
+        Assert.True(receivedLength < Encoding.UTF8.GetByteCount(Content));
+        Assert.Equal(Content, body);
+    }
+
+    [Fact]
+    public async Task Streamed_Body()
+    {
+        // Arrange
+        string? contentEncoding = null;
+        string? body = null;
+        await using var server = await LaunchServerAsync(app => app.MapPost("/", async (HttpContext context) =>
+        {
+            contentEncoding = context.Request.Headers.ContentEncoding;
+            using var reader = new StreamReader(new GZipStream(context.Request.Body, CompressionMode.Decompress));
+            body = await reader.ReadToEndAsync();
+        }));
+        using var httpHandler = new YetAnotherHttpHandler();
+        var httpClient = new HttpClient(httpHandler);
+        var pipe = new System.IO.Pipelines.Pipe();
+
+        // Act
+        var request = new HttpRequestMessage(HttpMethod.Post, $"{server.BaseUri}/") { Content = new StreamContent(pipe.Reader.AsStream()) };
+        request.SetBodyCompression(RequestBodyCompression.Gzip);
+        var responseTask = httpClient.SendAsync(request);
+        for (var i = 0; i < 10; i++)
+        {
+            await pipe.Writer.WriteAsync(Encoding.UTF8.GetBytes($"chunk-{i};"), TimeoutToken);
+            await Task.Delay(10, TimeoutToken);
+        }
+        await pipe.Writer.CompleteAsync();
+        var result = await responseTask.WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal(HttpStatusCode.OK, result.StatusCode);
+        Assert.Equal("gzip", contentEncoding);
+        Assert.Equal(string.Concat(Enumerable.Range(0, 10).Select(x => $"chunk-{x};")), body);
+    }
+
+    [Fact]
+    public async Task Undefined_Compression()
+    {
+        // Arrange
+        using var httpHandler = new YetAnotherHttpHandler();
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var request = new HttpRequestMessage(HttpMethod.Post, "http://localhost/") { Content = new StringContent(Content) };
+        request.SetBodyCompression((RequestBodyCompression)42);
+        var ex = await Record.ExceptionAsync(async () => await httpClient.SendAsync(request).WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<InvalidOperationException>(ex);
+        Assert.Contains("Unsupported request body compression", ex.Message);
+    }
+}
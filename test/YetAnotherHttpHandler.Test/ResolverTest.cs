@@ -0,0 +1,241 @@
+using System.Collections.Concurrent;
+using System.Net;
+using Cysharp.Net.Http;
+
+namespace _YetAnotherHttpHandler.Test;
+
+public class ResolverTest(ITestOutputHelper testOutputHelper) : UseTestServerTestBase(testOutputHelper)
+{
+    [Fact]
+    public async Task OnResolveHostName()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(TestServerListenMode.InsecureHttp1Only);
+        var port = new Uri(server.BaseUri).Port;
+        var resolvedHosts = new ConcurrentQueue<string>();
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            OnResolveHostName = async hostName =>
+            {
+                resolvedHosts.Enqueue(hostName);
+                await Task.Delay(10);
+                return [IPAddress.Loopback];
+            },
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync($"http://service.example.invalid:{port}/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("__OK__", result);
+        Assert.Equal(["service.example.invalid"], resolvedHosts);
+    }
+
+    [Fact]
+    public async Task OnResolveHostName_Not_Called_For_IPAddress()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(TestServerListenMode.InsecureHttp1Only);
+        var port = new Uri(server.BaseUri).Port;
+        var called = false;
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            OnResolveHostName = hostName =>
+            {
+                called = true;
+                return Task.FromResult(Array.Empty<IPAddress>());
+            },
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync($"http://127.0.0.1:{port}/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("__OK__", result);
+        Assert.False(called);
+    }
+
+    [Fact]
+    public async Task OnResolveHostName_Throws()
+    {
+        // Arrange
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            OnResolveHostName = hostName => Task.FromException<IPAddress[]>(new InvalidOperationException("No such host")),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync("http://service.example.invalid/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<HttpRequestException>(ex);
+        Assert.Contains("No such host", ex.Message);
+    }
+
+    [Fact]
+    public async Task OnResolveHostName_Timeout()
+    {
+        // Arrange
+        var never = new TaskCompletionSource<IPAddress[]>();
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            OnResolveHostName = hostName => never.Task,
+            ConnectTimeout = TimeSpan.FromMilliseconds(200),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync("http://service.example.invalid/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<HttpRequestException>(ex);
+        Assert.Contains("didn't complete", ex.Message);
+    }
+
+    [Fact]
+    public async Task OnResolveHostName_No_Addresses()
+    {
+        // Arrange
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            OnResolveHostName = hostName => Task.FromResult(Array.Empty<IPAddress>()),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync("http://service.example.invalid/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<HttpRequestException>(ex);
+    }
+
+    [Fact]
+    public async Task DnsCache()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(TestServerListenMode.InsecureHttp1Only);
+        var port = new Uri(server.BaseUri).Port;
+        var resolveCount = 0;
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            EnableDnsCache = true,
+            OnResolveHostName = hostName =>
+            {
+                Interlocked.Increment(ref resolveCount);
+                return Task.FromResult(new[] { IPAddress.Loopback });
+            },
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        // NOTE: `Connection: close` forces a new connection for each request.
+        await SendWithNewConnectionAsync(httpClient, $"http://service.example.invalid:{port}/");
+        await SendWithNewConnectionAsync(httpClient, $"http://service.example.invalid:{port}/");
+        var resolveCountBeforeFlush = resolveCount;
+        httpHandler.FlushDnsCache();
+        await SendWithNewConnectionAsync(httpClient, $"http://service.example.invalid:{port}/");
+
+        // Assert
+        Assert.Equal(1, resolveCountBeforeFlush);
+        Assert.Equal(2, resolveCount);
+    }
+
+    [Fact]
+    public async Task DnsCache_Disabled()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(TestServerListenMode.InsecureHttp1Only);
+        var port = new Uri(server.BaseUri).Port;
+        var resolveCount = 0;
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            OnResolveHostName = hostName =>
+            {
+                Interlocked.Increment(ref resolveCount);
+                return Task.FromResult(new[] { IPAddress.Loopback });
+            },
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        await SendWithNewConnectionAsync(httpClient, $"http://service.example.invalid:{port}/");
+        await SendWithNewConnectionAsync(httpClient, $"http://service.example.invalid:{port}/");
+
+        // Assert
+        Assert.Equal(2, resolveCount);
+    }
+
+    [Fact]
+    public async Task DnsCache_Negative()
+    {
+        // Arrange
+        var resolveCount = 0;
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            EnableDnsCache = true,
+            DnsCacheNegativeTtl = TimeSpan.FromMinutes(1),
+            OnResolveHostName = hostName =>
+            {
+                Interlocked.Increment(ref resolveCount);
+                return Task.FromResult(Array.Empty<IPAddress>());
+            },
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex1 = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync("http://service.example.invalid/").WaitAsync(TimeoutToken));
+        var ex2 = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync("http://service.example.invalid/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<HttpRequestException>(ex1);
+        Assert.IsType<HttpRequestException>(ex2);
+        Assert.Equal(1, resolveCount);
+    }
+
+    [Fact]
+    public async Task DnsCache_StaleWhileRevalidate()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(TestServerListenMode.InsecureHttp1Only);
+        var port = new Uri(server.BaseUri).Port;
+        var resolveCount = 0;
+        var refreshed = new TaskCompletionSource();
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            EnableDnsCache = true,
+            DnsCacheDefaultTtl = TimeSpan.Zero, // The entry expires immediately.
+            DnsCacheStaleWhileRevalidate = TimeSpan.FromMinutes(1),
+            OnResolveHostName = async hostName =>
+            {
+                if (Interlocked.Increment(ref resolveCount) > 1)
+                {
+                    // The refresh in background never completes until the test finishes.
+                    refreshed.TrySetResult();
+                    await Task.Delay(Timeout.Infinite, TimeoutToken);
+                }
+                return [IPAddress.Loopback];
+            },
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        await SendWithNewConnectionAsync(httpClient, $"http://service.example.invalid:{port}/");
+        // The stale address is used without waiting for the refresh.
+        await SendWithNewConnectionAsync(httpClient, $"http://service.example.invalid:{port}/");
+        await refreshed.Task.WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal(2, resolveCount);
+    }
+
+    private async Task SendWithNewConnectionAsync(HttpClient httpClient, string uri)
+    {
+        var request = new HttpRequestMessage(HttpMethod.Get, uri);
+        request.Headers.ConnectionClose = true;
+        var response = await httpClient.SendAsync(request).WaitAsync(TimeoutToken);
+        Assert.Equal("__OK__", await response.Content.ReadAsStringAsync());
+    }
+}
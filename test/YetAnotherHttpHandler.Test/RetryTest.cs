@@ -0,0 +1,263 @@
+using System.Collections.Concurrent;
+using System.Net;
+using System.Net.Sockets;
+using Cysharp.Net.Http;
+using Microsoft.AspNetCore.Builder;
+using Microsoft.AspNetCore.Http;
+
+namespace _YetAnotherHttpHandler.Test;
+
+public class RetryTest(ITestOutputHelper testOutputHelper) : UseTestServerTestBase(testOutputHelper)
+{
+    [Fact]
+    public async Task Retry_Idempotent_Request_After_Connection_Closed()
+    {
+        // Arrange
+        var receivedBodies = new ConcurrentQueue<string>();
+        // The server closes the connection without a response for the first requests.
+        var failures = 2;
+        await using var server = await LaunchServerAsync(app => app.Map("/", async (HttpContext context) =>
+        {
+            using var reader = new StreamReader(context.Request.Body);
+            receivedBodies.Enqueue(await reader.ReadToEndAsync());
+            if (Interlocked.Decrement(ref failures) >= 0)
+            {
+                context.Abort();
+                return;
+            }
+            await context.Response.WriteAsync("__OK__");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            EnableRetry = true,
+            RetryMaxAttempts = 3,
+            RetryInitialBackoff = TimeSpan.FromMilliseconds(10),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("__OK__", result);
+        Assert.Equal(3, receivedBodies.Count);
+    }
+
+    [Fact]
+    public async Task Give_Up_After_Max_Attempts()
+    {
+        // Arrange
+        var receivedBodies = new ConcurrentQueue<string>();
+        // The server closes the connection without a response for the first requests.
+        var failures = 3;
+        await using var server = await LaunchServerAsync(app => app.Map("/", async (HttpContext context) =>
+        {
+            using var reader = new StreamReader(context.Request.Body);
+            receivedBodies.Enqueue(await reader.ReadToEndAsync());
+            if (Interlocked.Decrement(ref failures) >= 0)
+            {
+                context.Abort();
+                return;
+            }
+            await context.Response.WriteAsync("__OK__");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            EnableRetry = true,
+            RetryMaxAttempts = 2,
+            RetryInitialBackoff = TimeSpan.FromMilliseconds(10),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<HttpRequestException>(ex);
+        Assert.Equal(2, receivedBodies.Count);
+    }
+
+    [Fact]
+    public async Task Do_Not_Retry_Non_Idempotent_Request_By_Default()
+    {
+        // Arrange
+        var receivedBodies = new ConcurrentQueue<string>();
+        // The server closes the connection without a response for the first requests.
+        var failures = 1;
+        await using var server = await LaunchServerAsync(app => app.Map("/", async (HttpContext context) =>
+        {
+            using var reader = new StreamReader(context.Request.Body);
+            receivedBodies.Enqueue(await reader.ReadToEndAsync());
+            if (Interlocked.Decrement(ref failures) >= 0)
+            {
+                context.Abort();
+                return;
+            }
+            await context.Response.WriteAsync("__OK__");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            EnableRetry = true,
+            RetryInitialBackoff = TimeSpan.FromMilliseconds(10),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.PostAsync($"{server.BaseUri}/", new StringContent("__BODY__")).WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<HttpRequestException>(ex);
+        Assert.Single(receivedBodies);
+    }
+
+    [Fact]
+    public async Task Replay_Request_Body()
+    {
+        // Arrange
+        var receivedBodies = new ConcurrentQueue<string>();
+        // The server closes the connection without a response for the first requests.
+        var failures = 1;
+        await using var server = await LaunchServerAsync(app => app.Map("/", async (HttpContext context) =>
+        {
+            using var reader = new StreamReader(context.Request.Body);
+            receivedBodies.Enqueue(await reader.ReadToEndAsync());
+            if (Interlocked.Decrement(ref failures) >= 0)
+            {
+                context.Abort();
+                return;
+            }
+            await context.Response.WriteAsync("__OK__");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            EnableRetry = true,
+            RetryNonIdempotentRequests = true,
+            RetryInitialBackoff = TimeSpan.FromMilliseconds(10),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var response = await httpClient.PostAsync($"{server.BaseUri}/", new StringContent("__BODY__")).WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal(HttpStatusCode.OK, response.StatusCode);
+        Assert.Equal(["__BODY__", "__BODY__"], receivedBodies);
+    }
+
+    [Fact]
+    public async Task Disabled()
+    {
+        // Arrange
+        var receivedBodies = new ConcurrentQueue<string>();
+        // The server closes the connection without a response for the first requests.
+        var failures = 1;
+        await using var server = await LaunchServerAsync(app => app.Map("/", async (HttpContext context) =>
+        {
+            using var reader = new StreamReader(context.Request.Body);
+            receivedBodies.Enqueue(await reader.ReadToEndAsync());
+            if (Interlocked.Decrement(ref failures) >= 0)
+            {
+                context.Abort();
+                return;
+            }
+            await context.Response.WriteAsync("__OK__");
+        }));
+        using var httpHandler = new YetAnotherHttpHandler();
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<HttpRequestException>(ex);
+        Assert.Single(receivedBodies);
+    }
+
+    [Fact]
+    public async Task Retry_Connection_Refused()
+    {
+        // Arrange
+        var listener = new TcpListener(IPAddress.Loopback, 0);
+        listener.Start();
+        var port = ((IPEndPoint)listener.LocalEndpoint).Port;
+        listener.Stop();
+        var resolveCount = 0;
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            EnableRetry = true,
+            RetryMaxAttempts = 3,
+            RetryInitialBackoff = TimeSpan.FromMilliseconds(10),
+            // Count the connection attempts.
+            OnResolveHostName = hostName =>
+            {
+                Interlocked.Increment(ref resolveCount);
+                return Task.FromResult(new[] { IPAddress.Loopback });
+            },
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync($"http://service.example.invalid:{port}/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<HttpRequestException>(ex);
+        Assert.Equal(3, resolveCount);
+    }
+
+    [Fact]
+    public async Task Do_Not_Retry_Untrusted_Certificate()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(TestServerListenMode.SecureHttp1AndHttp2);
+        var verificationCount = 0;
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            EnableRetry = true,
+            RetryMaxAttempts = 3,
+            RetryInitialBackoff = TimeSpan.FromMilliseconds(10),
+            OnVerifyServerCertificate = (name, certificate, now) =>
+            {
+                Interlocked.Increment(ref verificationCount);
+                return false;
+            },
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<HttpRequestException>(ex);
+        Assert.Equal(1, verificationCount);
+    }
+
+    [Fact]
+    public async Task Do_Not_Retry_Public_Key_Pin_Mismatch()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(TestServerListenMode.SecureHttp1AndHttp2);
+        var verificationCount = 0;
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            EnableRetry = true,
+            RetryMaxAttempts = 3,
+            RetryInitialBackoff = TimeSpan.FromMilliseconds(10),
+            RootCertificates = File.ReadAllText("./Certificates/localhost.crt"),
+            OnVerifyServerCertificate = (name, certificate, now) =>
+            {
+                Interlocked.Increment(ref verificationCount);
+                return true;
+            },
+            PublicKeyPins = [new PublicKeyPinSet("localhost", ["sha256/AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA="])],
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync($"{server.BaseUri}/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<HttpRequestException>(ex);
+        Assert.Contains("public key pins", ex.Message);
+        Assert.Equal(1, verificationCount);
+    }
+}
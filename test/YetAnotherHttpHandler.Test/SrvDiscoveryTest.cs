@@ -0,0 +1,138 @@
+using System.Net;
+using _YetAnotherHttpHandler.Test.Helpers;
+using Cysharp.Net.Http;
+using Microsoft.AspNetCore.Builder;
+using Microsoft.AspNetCore.Http;
+
+namespace _YetAnotherHttpHandler.Test;
+
+public class SrvDiscoveryTest(ITestOutputHelper testOutputHelper) : UseTestServerTestBase(testOutputHelper)
+{
+    [Fact]
+    public async Task Connect_To_Target()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(TestServerListenMode.InsecureHttp1Only);
+        var port = (ushort)new Uri(server.BaseUri).Port;
+        await using var dnsServer = new TestDnsServer();
+        dnsServer.SrvRecords["_http._tcp.svc.example.invalid"] = [new(0, 0, port, "backend.example.invalid")];
+        dnsServer.Addresses["backend.example.invalid"] = [IPAddress.Loopback];
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            EnableSrvDiscovery = true,
+            UseBuiltInDnsResolver = true,
+            DnsServers = [dnsServer.EndPoint],
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync("http://_http._tcp.svc.example.invalid/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("__OK__", result);
+        Assert.Contains("_http._tcp.svc.example.invalid/SRV", dnsServer.ReceivedQueries);
+    }
+
+    [Fact]
+    public async Task Priority()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(TestServerListenMode.InsecureHttp1Only);
+        var port = (ushort)new Uri(server.BaseUri).Port;
+        await using var dnsServer = new TestDnsServer();
+        dnsServer.SrvRecords["_http._tcp.svc.example.invalid"] =
+        [
+            new(10, 0, 1, "backup.example.invalid"), // The port 1 is not listened.
+            new(0, 100, port, "primary.example.invalid"),
+        ];
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            EnableSrvDiscovery = true,
+            DnsServers = [dnsServer.EndPoint],
+            OnResolveHostName = hostName => Task.FromResult(new[] { IPAddress.Loopback }),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync("http://_http._tcp.svc.example.invalid/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("__OK__", result);
+    }
+
+    [Fact]
+    public async Task Fallback_To_Next_Target()
+    {
+        // Arrange
+        await using var server = await LaunchServerAsync(TestServerListenMode.InsecureHttp1Only);
+        var port = (ushort)new Uri(server.BaseUri).Port;
+        await using var dnsServer = new TestDnsServer();
+        dnsServer.SrvRecords["_http._tcp.svc.example.invalid"] =
+        [
+            new(0, 0, 1, "primary.example.invalid"), // The port 1 is not listened.
+            new(10, 0, port, "backup.example.invalid"),
+        ];
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            EnableSrvDiscovery = true,
+            DnsServers = [dnsServer.EndPoint],
+            OnResolveHostName = hostName => Task.FromResult(new[] { IPAddress.Loopback }),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync("http://_http._tcp.svc.example.invalid/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("__OK__", result);
+    }
+
+    [Fact]
+    public async Task Keep_Logical_Host()
+    {
+        // Arrange
+        string? receivedHost = null;
+        await using var server = await LaunchServerAsync(app => app.MapGet("/", (HttpContext context) =>
+        {
+            receivedHost = context.Request.Headers.Host;
+            return context.Response.WriteAsync("__OK__");
+        }));
+        await using var dnsServer = new TestDnsServer();
+        dnsServer.SrvRecords["_http._tcp.svc.example.invalid"] = [new(0, 0, (ushort)server.Port, "backend.example.invalid")];
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            EnableSrvDiscovery = true,
+            DnsServers = [dnsServer.EndPoint],
+            OnResolveHostName = hostName => Task.FromResult(new[] { IPAddress.Loopback }),
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var result = await httpClient.GetStringAsync("http://_http._tcp.svc.example.invalid/").WaitAsync(TimeoutToken);
+
+        // Assert
+        Assert.Equal("__OK__", result);
+        Assert.Equal("_http._tcp.svc.example.invalid", receivedHost);
+    }
+
+    [Fact]
+    public async Task Disabled()
+    {
+        // Arrange
+        await using var dnsServer = new TestDnsServer();
+        dnsServer.SrvRecords["_http._tcp.svc.example.invalid"] = [new(0, 0, 80, "backend.example.invalid")];
+        using var httpHandler = new YetAnotherHttpHandler()
+        {
+            DnsServers = [dnsServer.EndPoint],
+            UseBuiltInDnsResolver = true,
+        };
+        var httpClient = new HttpClient(httpHandler);
+
+        // Act
+        var ex = await Record.ExceptionAsync(async () => await httpClient.GetStringAsync("http://_http._tcp.svc.example.invalid/").WaitAsync(TimeoutToken));
+
+        // Assert
+        Assert.IsType<HttpRequestException>(ex);
+        Assert.DoesNotContain("_http._tcp.svc.example.invalid/SRV", dnsServer.ReceivedQueries);
+    }
+}
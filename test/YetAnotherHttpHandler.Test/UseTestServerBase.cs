@@ -1,4 +1,5 @@
-﻿using HttpClientTestServer.Launcher;
+﻿using System.Net;
+using HttpClientTestServer.Launcher;
 using Microsoft.AspNetCore.Builder;
 
 namespace _YetAnotherHttpHandler.Test;
@@ -34,6 +35,19 @@ public abstract class UseTestServerTestBase : TimeoutTestBase, IDisposable
         );
     }
 
+    /// <summary>
+    /// Launches an HTTP/1.1 server with the endpoints mapped by <paramref name="mapEndpoints"/>.
+    /// </summary>
+    protected Task<TestWebAppServer> LaunchServerAsync(Action<WebApplication> mapEndpoints, IPAddress? listenAddress = null)
+    {
+        return TestWebAppServer.LaunchAsync(
+            mapEndpoints,
+            new TestOutputLoggerProvider(TestOutputHelper),
+            listenAddress,
+            TimeoutToken
+        );
+    }
+
     public void Dispose()
     {
         _tokenRegistration.Dispose();